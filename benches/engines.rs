@@ -0,0 +1,43 @@
+//! Compares how long the engines take to run the programs next to this file.
+//! Run with `cargo bench --bench engines`.
+
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+const ENGINES: [&str; 2] = ["reference", "threaded"];
+const RUNS: usize = 5;
+
+/// Returns the fastest of several runs of a program, checking that it succeeds.
+fn time(program: &PathBuf, engine: &str) -> Duration {
+    (0..RUNS).map(|_| {
+        let start = Instant::now();
+        let status = Command::new(env!("CARGO_BIN_EXE_guryvsr"))
+            .arg(program)
+            .args(["--engine", engine])
+            .stdout(Stdio::null())
+            .status()
+            .unwrap();
+        let elapsed = start.elapsed();
+        assert!(status.success(), "{program:?} failed with the {engine} engine");
+        elapsed
+    }).min().unwrap()
+}
+
+fn main() {
+    let mut programs: Vec<PathBuf> = std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/benches/programs")).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "gur"))
+        .collect();
+    programs.sort();
+
+    println!("{:<12} {:>12} {:>12} {:>8}", "program", ENGINES[0], ENGINES[1], "speedup");
+    for program in &programs {
+        let [reference, threaded] = ENGINES.map(|engine| time(program, engine));
+        println!("{:<12} {:>10.1}ms {:>10.1}ms {:>7.2}x",
+                 program.file_stem().unwrap().to_string_lossy(),
+                 reference.as_secs_f64() * 1000.0,
+                 threaded.as_secs_f64() * 1000.0,
+                 reference.as_secs_f64() / threaded.as_secs_f64());
+    }
+}
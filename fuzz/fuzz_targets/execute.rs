@@ -0,0 +1,79 @@
+//! Runs arbitrary programs for a bounded number of steps on the machine the debugger, `visualize` and the library interpreter run them on,
+//! checking that every step either goes on, stops at a `.`, or throws an error the interpreter reports, rather than panicking or hanging.
+//! The same program runs alongside on a machine that keeps its history, as the debugger's does, and both must agree after every step.
+//!
+//! The bytes before the first newline are the program: a byte that is a command stands for itself, and any other byte
+//! stands for the command it picks out of [`SYMBOLS`], so that every input is a program and crashes can be saved as source.
+//! The rest of the bytes are the lines of input the program reads.
+#![no_main]
+#![allow(dead_code)]
+
+use libfuzzer_sys::fuzz_target;
+
+#[path="../../src/frontend/errors.rs"]
+mod errors;
+#[path="../../src/frontend/lexer.rs"]
+mod lexer;
+#[path="../../src/frontend/exec.rs"]
+mod exec;
+#[path="../../src/frontend/debugger.rs"]
+mod debugger;
+use debugger::debug::*;
+use errors::err::Error;
+use lexer::lex::*;
+
+const SYMBOLS: &str = "_.#0123456789UuRrCc?!TtAa@JjKkFfMm+-*/%=Nn>Gg<Ll&|~^isIS";
+/// Enough steps for loops to run many times, but few enough that every input finishes quickly.
+const MAX_STEPS: usize = 10_000;
+
+fuzz_target!(|data: &[u8]| {
+    let (program, input) = match data.iter().position(|&byte| byte == b'\n') {
+        Some(newline) => (&data[..newline], &data[newline + 1..]),
+        None => (data, &[][..])
+    };
+    let tokens: Vec<Token> = program.iter().map(|&byte| {
+        symbol_to_token(byte as char).unwrap_or_else(|| symbol_to_token(SYMBOLS.as_bytes()[byte as usize % SYMBOLS.len()] as char).unwrap())
+    }).collect();
+
+    let targets = match_conditionals(&tokens);
+    let mut machine = Machine::new(tokens.clone(), targets.clone(), false);
+    machine.discard_history();
+    machine.set_input(read_lines(input));
+    let mut debugged = Machine::new(tokens.clone(), targets, false);
+    debugged.set_input(read_lines(input));
+
+    for _ in 0..MAX_STEPS {
+        let outcome = machine.step().cloned();
+        assert_eq!(format!("{:?}", debugged.step()), format!("{outcome:?}"), "keeping the history changed how a step ended");
+        assert_eq!(debugged.take_output(), machine.take_output(), "keeping the history changed the output");
+        let (state, debugged_state) = (machine.state(), debugged.state());
+        assert_eq!(debugged_state.acc.get_details(), state.acc.get_details(), "keeping the history changed the accumulator");
+        assert!(debugged_state.tape == state.tape, "keeping the history changed the tape");
+        assert_eq!((debugged_state.data_ptr_index, debugged_state.data_ptr_dir, debugged_state.code_ptr_index),
+                   (state.data_ptr_index, state.data_ptr_dir, state.code_ptr_index),
+                   "keeping the history changed the pointers");
+        match outcome {
+            None => assert!(machine.state().code_ptr_index < tokens.len(), "the code pointer left the program without an error"),
+            Some(Outcome::Stopped(status)) => {
+                assert_eq!(status, 0, "`.` exited with a status other than 0 without --exit-with-acc");
+                break
+            }
+            Some(Outcome::Failed(fault)) => {
+                assert!(!matches!(fault.error, Error::FileError | Error::UnknownSymbolError), "a running program threw {:?}", fault.error);
+                assert!(!fault.msg.is_empty());
+                break
+            }
+        }
+    }
+});
+
+/// Reads the lines of the input one at a time, and nothing once they run out.
+fn read_lines(input: &[u8]) -> impl FnMut(&mut String) -> std::io::Result<usize> + 'static {
+    let mut lines: Vec<String> = String::from_utf8_lossy(input).split_inclusive('\n').map(str::to_string).collect();
+    lines.reverse();
+    move |line| {
+        let read = lines.pop().unwrap_or_default();
+        line.push_str(&read);
+        Ok(read.len())
+    }
+}
@@ -0,0 +1,27 @@
+//! Feeds arbitrary text to the lexer, checking that it never panics
+//! and that [`try_tokenize`] and [`scan`] agree on the commands and on the first unrecognized symbol.
+#![no_main]
+#![allow(dead_code)]
+
+use libfuzzer_sys::fuzz_target;
+
+#[path="../../src/frontend/errors.rs"]
+mod errors;
+#[path="../../src/frontend/lexer.rs"]
+mod lexer;
+use lexer::lex::*;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(src) = std::str::from_utf8(data) else { return };
+    match (try_tokenize(src), scan(src)) {
+        (Ok(tokens), Ok(lexemes)) => {
+            let commands: Vec<Token> = lexemes.into_iter().filter_map(|lexeme| match lexeme.kind {
+                LexemeKind::Command(token) => Some(token),
+                _ => None
+            }).collect();
+            assert_eq!(tokens, commands);
+        }
+        (Err(unknown), Err(scanned)) => assert_eq!(unknown, scanned),
+        (tokens, lexemes) => panic!("try_tokenize returned {:?} but scan returned {:?}", tokens.err(), lexemes.err())
+    }
+});
@@ -0,0 +1,519 @@
+pub mod deep {
+    use std::collections::{BTreeMap, VecDeque};
+    use crate::errors::err::Error;
+    use crate::lexer::lex::*;
+
+    /// How far from the data pointer cells are tracked while its index isn't known. Cells further away are merged into the
+    /// cells that aren't tracked, which keeps the analysis finite when a loop keeps moving the data pointer the same way.
+    const WINDOW: isize = 64;
+
+    /// What is known about a cell or the accumulator every time a command is reached.
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    enum Value {
+        Empty,
+        Const(isize),
+        /// Holds a value, but not always the same one.
+        Full,
+        /// Might be empty.
+        Unknown,
+    }
+
+    impl Value {
+        /// Returns what is known about a value that is either `self` or `other`.
+        fn join(self, other: Value) -> Value {
+            match (self, other) {
+                _ if self == other => self,
+                (Value::Unknown | Value::Empty, _) | (_, Value::Unknown | Value::Empty) => Value::Unknown,
+                _ => Value::Full
+            }
+        }
+
+        /// Returns what is known about the value once a command that needs it to hold a value has succeeded.
+        fn filled(self) -> Value {
+            match self {
+                Value::Empty | Value::Unknown => Value::Full,
+                value => value
+            }
+        }
+
+        /// Returns what is known about the value on the branch of a conditional where it is (or isn't) zero or empty,
+        /// or [`None`] if that branch is never taken.
+        fn branch(self, zero_or_empty: bool) -> Option<Value> {
+            match (self, zero_or_empty) {
+                (Value::Empty | Value::Const(0), true) => Some(self),
+                (Value::Empty | Value::Const(0), false) | (Value::Const(_), true) => None,
+                (Value::Const(_), false) => Some(self),
+                (Value::Full, true) => Some(Value::Const(0)),
+                (Value::Unknown, true) => Some(Value::Unknown),
+                (Value::Full | Value::Unknown, false) => Some(Value::Full)
+            }
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct State {
+        /// Cells by their offset from the data pointer. Cells that aren't in here are known to be `rest`.
+        cells: BTreeMap<isize, Value>,
+        rest: Value,
+        acc: Value,
+        /// The data pointer's index, if it is the same every time the command is reached.
+        data_ptr_index: Option<isize>,
+        data_ptr_dir: Option<isize>,
+        /// Cells by their index, kept while the data pointer's index isn't known so that they are known again once it is
+        /// moved to a known index. Cells that aren't in here are known to be `anchored_rest`. Empty while the index is known.
+        anchored: BTreeMap<isize, Value>,
+        anchored_rest: Value,
+    }
+
+    impl State {
+        fn start() -> State {
+            State {
+                cells: BTreeMap::new(),
+                rest: Value::Empty,
+                acc: Value::Empty,
+                data_ptr_index: Some(0),
+                data_ptr_dir: Some(1),
+                anchored: BTreeMap::new(),
+                anchored_rest: Value::Empty,
+            }
+        }
+
+        fn cell(&self, offset: isize) -> Value {
+            self.cells.get(&offset).copied().unwrap_or(self.rest)
+        }
+
+        /// Writes to the cell at `offset` from the data pointer. If the data pointer's index isn't known, any of the cells
+        /// known by their index could be the one written to.
+        fn set_cell(&mut self, offset: isize, value: Value) {
+            self.refine_cell(offset, value);
+            if self.data_ptr_index.is_none() {
+                self.anchored.values_mut().for_each(|cell| *cell = cell.join(value));
+                self.anchored_rest = self.anchored_rest.join(value);
+            }
+        }
+
+        /// Narrows down what is known about the cell at `offset` from the data pointer, without writing to it.
+        fn refine_cell(&mut self, offset: isize, value: Value) {
+            self.cells.insert(offset, value);
+        }
+
+        /// Returns the cells by their index, and what the cells that aren't in there are known to be.
+        fn by_index(&self) -> (BTreeMap<isize, Value>, Value) {
+            match self.data_ptr_index {
+                Some(index) => (self.cells.iter().filter_map(|(offset, value)| Some((offset.checked_add(index)?, *value))).collect(), self.rest),
+                None => (self.anchored.clone(), self.anchored_rest)
+            }
+        }
+
+        /// Forgets the cells outside of [`WINDOW`] if the data pointer's index isn't known,
+        /// and drops the cells that are the same as the cells that aren't tracked.
+        fn normalize(&mut self) {
+            if self.data_ptr_index.is_none() {
+                let outside: Vec<isize> = self.cells.keys().copied().filter(|offset| offset.abs() > WINDOW).collect();
+                for offset in outside {
+                    self.rest = self.rest.join(self.cells.remove(&offset).unwrap());
+                }
+            }
+            let rest = self.rest;
+            self.cells.retain(|_, value| *value != rest);
+            let anchored_rest = self.anchored_rest;
+            self.anchored.retain(|_, value| *value != anchored_rest);
+        }
+
+        /// Moves the data pointer by `delta` cells.
+        fn shift(&mut self, delta: isize) {
+            let Some(cells) = self.cells.iter().map(|(offset, value)| Some((offset.checked_sub(delta)?, *value))).collect() else {
+                return self.lose_position()
+            };
+            if let Some(index) = self.data_ptr_index && index.checked_add(delta).is_none() {
+                self.lose_position();
+            }
+            self.cells = cells;
+            self.data_ptr_index = self.data_ptr_index.map(|index| index + delta);
+            self.normalize();
+        }
+
+        /// Moves the data pointer one cell in its direction, like every command that doesn't move it on its own.
+        fn advance(&mut self) {
+            match self.data_ptr_dir {
+                Some(dir) => self.shift(dir),
+                None => {
+                    let mut left = self.clone();
+                    left.shift(-1);
+                    self.shift(1);
+                    *self = self.join(&left);
+                }
+            }
+        }
+
+        /// Moves the data pointer to `index`.
+        fn move_to(&mut self, index: isize) {
+            match self.data_ptr_index {
+                Some(current) if let Some(delta) = index.checked_sub(current) => self.shift(delta),
+                _ => {
+                    let (cells, rest) = self.by_index();
+                    self.cells = cells.into_iter().filter_map(|(cell, value)| Some((cell.checked_sub(index)?, value))).collect();
+                    self.rest = rest;
+                    self.data_ptr_index = Some(index);
+                    self.anchored.clear();
+                    self.anchored_rest = Value::Empty;
+                    self.normalize();
+                }
+            }
+        }
+
+        /// Forgets where the data pointer is, after it has moved by an amount that isn't known.
+        fn lose_position(&mut self) {
+            if self.data_ptr_index.is_some() {
+                (self.anchored, self.anchored_rest) = self.by_index();
+                self.data_ptr_index = None;
+            }
+            self.rest = self.cells.values().fold(self.rest, |rest, value| rest.join(*value));
+            self.cells.clear();
+        }
+
+        /// Returns what is known about a state that is either `self` or `other`.
+        fn join(&self, other: &State) -> State {
+            let mut cells = BTreeMap::new();
+            for offset in self.cells.keys().chain(other.cells.keys()) {
+                cells.insert(*offset, self.cell(*offset).join(other.cell(*offset)));
+            }
+            let data_ptr_index = self.data_ptr_index.filter(|index| other.data_ptr_index == Some(*index));
+            let (mut anchored, mut anchored_rest) = (BTreeMap::new(), Value::Empty);
+            if data_ptr_index.is_none() {
+                let ((left, left_rest), (right, right_rest)) = (self.by_index(), other.by_index());
+                for index in left.keys().chain(right.keys()) {
+                    let value = left.get(index).unwrap_or(&left_rest).join(*right.get(index).unwrap_or(&right_rest));
+                    anchored.insert(*index, value);
+                }
+                anchored_rest = left_rest.join(right_rest);
+            }
+            let mut state = State {
+                cells,
+                rest: self.rest.join(other.rest),
+                acc: self.acc.join(other.acc),
+                data_ptr_index,
+                data_ptr_dir: self.data_ptr_dir.filter(|dir| other.data_ptr_dir == Some(*dir)),
+                anchored,
+                anchored_rest,
+            };
+            state.normalize();
+            state
+        }
+    }
+
+    /// A command that throws an error whenever it is run, or only some of the times it is run, as told by the message.
+    pub struct Finding {
+        pub index: usize,
+        pub message: String,
+    }
+
+    /// Why a command throws an error: `subject` is (or may be) `condition`.
+    struct Problem {
+        index: usize,
+        error: Error,
+        certain: bool,
+        subject: &'static str,
+        condition: &'static str,
+    }
+
+    #[derive(Default)]
+    struct Analysis {
+        problems: Vec<Problem>,
+        computed_jump: bool,
+    }
+
+    impl Analysis {
+        fn report(&mut self, index: usize, error: Error, certain: bool, subject: &'static str, condition: &'static str) {
+            self.problems.push(Problem { index, error, certain, subject, condition });
+        }
+
+        /// Reports the command at `index` if `value` is (or may be) empty when it needs to hold a value, or the other way around.
+        /// Returns false if the command always throws the error.
+        fn require(&mut self, index: usize, error: Error, value: Value, full: bool, subject: &'static str) -> bool {
+            let (certain, possible) = if full {
+                (value == Value::Empty, value == Value::Unknown)
+            } else {
+                (matches!(value, Value::Const(_) | Value::Full), value == Value::Unknown)
+            };
+            if certain || possible {
+                self.report(index, error, certain, subject, if full { "empty" } else { "full" });
+            }
+            !certain
+        }
+    }
+
+    /// Folds a binary command over two known values, or returns [`None`] if it fails.
+    fn fold(command: Token, left: isize, right: isize) -> Option<isize> {
+        match command {
+            Token::Add => left.checked_add(right),
+            Token::Mul => left.checked_mul(right),
+            Token::Div => left.checked_div_euclid(right),
+            Token::Mod => left.checked_rem(right),
+            Token::Eq => Some(isize::from(left == right)),
+            Token::NotEq(_) => Some(isize::from(left != right)),
+            Token::Gt => Some(isize::from(left > right)),
+            Token::GE(_) => Some(isize::from(left >= right)),
+            Token::Lt => Some(isize::from(left < right)),
+            Token::LE(_) => Some(isize::from(left <= right)),
+            Token::BitAnd => Some(left & right),
+            Token::BitOr => Some(left | right),
+            Token::BitXor => Some(left ^ right),
+            _ => unreachable!()
+        }
+    }
+
+    /// Runs the command at `index` on a state, returning the states the commands run next start with.
+    fn transfer(tokens: &[Token], targets: &[Option<usize>], index: usize, state: &State, analysis: &mut Analysis) -> Vec<(usize, State)> {
+        const ACC: &str = "the accumulator";
+        const CELL: &str = "the current cell";
+        let current = tokens[index];
+        let mut state = state.clone();
+        let mut moving = true;
+        // the indices the code pointer is at once the command is done, before it moves to the next command
+        let mut code_ptrs = vec![];
+
+        match current {
+            Token::NoOp | Token::DestinationIfTrue | Token::Nothing => code_ptrs.push((index, state)),
+            Token::Stop => {}
+            Token::CreatingNumber => {
+                if analysis.require(index, Error::AccumulatorError, state.acc, false, ACC) {
+                    state.acc = Value::Empty;
+                    moving = false;
+                    code_ptrs.push((index, state));
+                }
+            }
+            Token::Digit(n) => {
+                state.acc = match state.acc {
+                    Value::Empty => Value::Const(n),
+                    Value::Const(value) => value.checked_mul(10).and_then(|value| value.checked_add(n)).map_or(Value::Full, Value::Const),
+                    _ => Value::Full
+                };
+                moving = false;
+                code_ptrs.push((index, state));
+            }
+            Token::Unload | Token::Distribute => {
+                if analysis.require(index, Error::AccumulatorError, state.acc, true, ACC) {
+                    let value = state.acc.filled();
+                    state.set_cell(0, value);
+                    state.acc = if current == Token::Unload { Value::Empty } else { value };
+                    code_ptrs.push((index, state));
+                }
+            }
+            Token::Recall | Token::Copy => {
+                if analysis.require(index, Error::OpError, state.cell(0), true, CELL) {
+                    state.acc = state.cell(0).filled();
+                    if current == Token::Recall {
+                        state.set_cell(0, Value::Empty);
+                    } else {
+                        state.refine_cell(0, state.acc);
+                    }
+                    code_ptrs.push((index, state));
+                }
+            }
+            Token::ClearAcc => {
+                state.acc = Value::Empty;
+                code_ptrs.push((index, state));
+            }
+            Token::ClearCurrCell => {
+                state.set_cell(0, Value::Empty);
+                code_ptrs.push((index, state));
+            }
+            Token::ZeroOrEmpty | Token::NotZeroOrEmpty | Token::AccZeroOrEmpty | Token::AccNotZeroOrEmpty => {
+                let on_acc = matches!(current, Token::AccZeroOrEmpty | Token::AccNotZeroOrEmpty);
+                let jumps_if_zero_or_empty = matches!(current, Token::ZeroOrEmpty | Token::AccZeroOrEmpty);
+                for (taken, code_ptr) in [(true, targets[index]), (false, Some(index))] {
+                    let value = if on_acc { state.acc } else { state.cell(0) };
+                    if let Some(code_ptr) = code_ptr && let Some(value) = value.branch(taken == jumps_if_zero_or_empty) {
+                        let mut state = state.clone();
+                        if on_acc { state.acc = value } else { state.refine_cell(0, value) }
+                        code_ptrs.push((code_ptr, state));
+                    }
+                }
+            }
+            Token::TgtZeroOrEmpty | Token::TgtNotZeroOrEmpty => {
+                if analysis.require(index, Error::AccumulatorError, state.acc, true, ACC) {
+                    state.acc = Value::Empty;
+                    if let Some(target) = targets[index] {
+                        code_ptrs.push((target, state.clone()));
+                    }
+                    code_ptrs.push((index, state));
+                }
+            }
+            Token::JumpCellsC | Token::JumpToCellC => {
+                if analysis.require(index, Error::OpError, state.acc, true, ACC) {
+                    if let Value::Const(value) = state.acc {
+                        let target = match current {
+                            Token::JumpCellsC => index.checked_add_signed(value),
+                            _ => usize::try_from(value).ok()
+                        };
+                        state.acc = Value::Empty;
+                        if let Some(target) = target.filter(|&target| target < tokens.len()) {
+                            code_ptrs.push((target, state));
+                        }
+                    } else {
+                        analysis.computed_jump = true;
+                    }
+                }
+            }
+            Token::JumpCellsD | Token::JumpToCellD => {
+                if analysis.require(index, Error::OpError, state.acc, true, ACC) {
+                    match (current, state.acc) {
+                        (Token::JumpCellsD, Value::Const(value)) => state.shift(value),
+                        (_, Value::Const(value)) => state.move_to(value),
+                        _ => state.lose_position()
+                    }
+                    state.acc = Value::Empty;
+                    moving = false;
+                    code_ptrs.push((index, state));
+                }
+            }
+            Token::MoveDUntilEmpty | Token::MoveDUntilFull => {
+                let until_empty = current == Token::MoveDUntilEmpty;
+                let stays = if until_empty { state.cell(0) == Value::Empty } else { matches!(state.cell(0), Value::Const(_) | Value::Full) };
+                if !stays {
+                    state.lose_position();
+                    state.refine_cell(0, if until_empty { Value::Empty } else { Value::Full });
+                    state.normalize();
+                }
+                moving = false;
+                code_ptrs.push((index, state));
+            }
+            Token::FlipD(_) => {
+                state.data_ptr_dir = state.data_ptr_dir.map(|dir| -dir);
+                code_ptrs.push((index, state));
+            }
+            Token::Neg => {
+                if analysis.require(index, Error::AccumulatorError, state.acc, true, ACC) {
+                    state.acc = match state.acc {
+                        Value::Const(value) => value.checked_neg().map_or(Value::Full, Value::Const),
+                        value => value.filled()
+                    };
+                    code_ptrs.push((index, state));
+                }
+            }
+            Token::BitNot => {
+                if analysis.require(index, Error::OpError, state.cell(0), true, CELL) {
+                    state.acc = match state.cell(0) {
+                        Value::Const(value) => Value::Const(!value),
+                        _ => Value::Full
+                    };
+                    code_ptrs.push((index, state));
+                }
+            }
+            Token::Add | Token::Mul | Token::Div | Token::Mod | Token::Eq | Token::NotEq(_) | Token::Gt | Token::GE(_)
+            | Token::Lt | Token::LE(_) | Token::BitAnd | Token::BitOr | Token::BitXor => {
+                let (left, right) = (state.cell(-1), state.cell(0));
+                if !analysis.require(index, Error::OpError, left, true, "the cell to the left of the data pointer")
+                    || !analysis.require(index, Error::OpError, right, true, CELL) {
+                    return vec![]
+                }
+                if matches!(current, Token::Div | Token::Mod) && right == Value::Const(0) {
+                    analysis.report(index, Error::OpError, true, CELL, "0");
+                    return vec![]
+                }
+                if analysis.require(index, Error::AccumulatorError, state.acc, false, ACC) {
+                    state.acc = match (left, right) {
+                        (Value::Const(left), Value::Const(right)) => fold(current, left, right).map_or(Value::Full, Value::Const),
+                        _ => Value::Full
+                    };
+                    code_ptrs.push((index, state));
+                }
+            }
+            Token::OutputInt | Token::OutputChar => {
+                if analysis.require(index, Error::OpError, state.cell(0), true, CELL) {
+                    let valid = match state.cell(0) {
+                        Value::Const(value) => u32::try_from(value).ok().and_then(char::from_u32).is_some(),
+                        _ => true
+                    };
+                    if current == Token::OutputChar && !valid {
+                        analysis.report(index, Error::OpError, true, CELL, "not a valid character");
+                        return vec![]
+                    }
+                    state.refine_cell(0, state.cell(0).filled());
+                    code_ptrs.push((index, state));
+                }
+            }
+            Token::InputInt => {
+                state.acc = Value::Full;
+                moving = false;
+                code_ptrs.push((index, state));
+            }
+            Token::InputStr => {
+                // a line of any length is written from the data pointer onwards, so only the cells before it stay as they were
+                for offset in -WINDOW..=WINDOW {
+                    let value = state.cell(offset);
+                    state.refine_cell(offset, if offset < 0 { value } else { value.join(Value::Full) });
+                }
+                state.rest = state.rest.join(Value::Full);
+                if state.data_ptr_index.is_none() {
+                    state.anchored.values_mut().for_each(|cell| *cell = cell.join(Value::Full));
+                    state.anchored_rest = state.anchored_rest.join(Value::Full);
+                }
+                state.normalize();
+                code_ptrs.push((index, state));
+            }
+        }
+
+        code_ptrs.into_iter()
+            .filter(|(code_ptr, _)| code_ptr + 1 < tokens.len())
+            .map(|(code_ptr, mut state)| {
+                if moving {
+                    state.advance();
+                }
+                (code_ptr + 1, state)
+            })
+            .collect()
+    }
+
+    /// Predicts the `OpError`s and `AccumulatorError`s a program throws, without running it.
+    ///
+    /// Runs the program on abstract states that track what is known about the cells around the data pointer, the
+    /// accumulator, and the data pointer's index and direction every time a command is reached, merging the states of
+    /// the paths that meet at a command until nothing changes. A command is reported as certain to throw an error if it
+    /// does on every path that reaches it. Jumps whose value isn't a known constant aren't followed, so when a program
+    /// has one, the commands it might land on could be reached in other ways, and no error is reported as certain.
+    pub fn predict_errors(tokens: &[Token]) -> Vec<Finding> {
+        if tokens.is_empty() {
+            return vec![]
+        }
+        let targets = match_conditionals(tokens);
+        let mut states: Vec<Option<State>> = vec![None; tokens.len()];
+        states[0] = Some(State::start());
+        let mut pending = VecDeque::from([0]);
+        while let Some(index) = pending.pop_front() {
+            let state = states[index].clone().unwrap();
+            for (next, state) in transfer(tokens, &targets, index, &state, &mut Analysis::default()) {
+                let joined = match &states[next] {
+                    Some(old) => old.join(&state),
+                    None => state
+                };
+                if states[next].as_ref() != Some(&joined) {
+                    states[next] = Some(joined);
+                    if !pending.contains(&next) {
+                        pending.push_back(next);
+                    }
+                }
+            }
+        }
+
+        let mut analysis = Analysis::default();
+        for (index, state) in states.iter().enumerate() {
+            if let Some(state) = state {
+                transfer(tokens, &targets, index, state, &mut analysis);
+            }
+        }
+        analysis.problems.into_iter()
+            .map(|problem| {
+                // jumps land after their target, so nothing but the first command is safe from computed jumps
+                let certain = problem.certain && (!analysis.computed_jump || problem.index == 0);
+                let (will, is) = if certain { ("will", "is") } else { ("may", "may be") };
+                Finding {
+                    index: problem.index,
+                    message: format!("`{}` at index {} {will} throw {:?} because {} {is} {}",
+                                     token_to_symbol(&tokens[problem.index]), problem.index, problem.error, problem.subject, problem.condition),
+                }
+            })
+            .collect()
+    }
+}
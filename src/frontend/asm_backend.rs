@@ -0,0 +1,1279 @@
+pub mod x86 {
+    use std::collections::HashMap;
+    use crate::errors::err::Error;
+    use crate::lexer::lex::*;
+
+    /// The routines every compiled program shares. Registers hold the interpreter's state between commands:
+    /// r12 is the data pointer, r13 its direction, r14 the accumulator's value, r15 whether the accumulator is full,
+    /// and rbx whether a number literal is being created. Routines keep rbx, rbp and r12 to r15 intact.
+    ///
+    /// The tape is split into pages of 4096 cells that are only allocated once a cell in them is written.
+    /// Each page starts with a bitmap of which of its cells are full, followed by the cells. Pages are found through an open-addressing hash table
+    /// keyed by page number, and the last page found is cached.
+    const RUNTIME: &str = r#".intel_syntax noprefix
+
+.set PAGE_BITS, 12
+.set PAGE_CELLS, 1 << PAGE_BITS
+.set BITMAP_BYTES, PAGE_CELLS / 8
+.set PAGE_BYTES, BITMAP_BYTES + PAGE_CELLS * 8
+.set TABLE_BITS, 20
+.set TABLE_SIZE, 1 << TABLE_BITS
+.set BUFFER_BYTES, 65536
+.set LINE_BYTES, 1 << 22
+
+.macro APPEND name
+    lea rsi, [rip + \name]
+    mov ecx, \name\()_len
+    rep movsb
+.endm
+
+.section .bss
+.balign 16
+page_table: .skip TABLE_SIZE * 16
+cache_number: .skip 8
+cache_page: .skip 8
+out_buffer: .skip BUFFER_BYTES
+out_len: .skip 8
+in_buffer: .skip BUFFER_BYTES
+in_pos: .skip 8
+in_len: .skip 8
+line_bytes: .skip LINE_BYTES
+line_chars: .skip LINE_BYTES * 8
+err_buffer: .skip 4096
+number: .skip 32
+last: .skip 8
+flag_details: .skip 8
+flag_exit_with_acc: .skip 8
+fail_kind: .skip 8
+fail_msg: .skip 8
+fail_len: .skip 8
+fail_pc: .skip 8
+fail_cur: .skip 8
+fail_details: .skip 8
+details_pc: .skip 8
+details_cur: .skip 8
+
+.section .rodata
+arg_details: .asciz "--details"
+arg_d: .asciz "-d"
+arg_exit_with_acc: .asciz "--exit-with-acc"
+red: .ascii "\033[31;1m"
+.set red_len, . - red
+colon: .ascii ":\033[0m "
+.set colon_len, . - colon
+details_head: .ascii "\033[33;1m[Details]\033[0m\n\033[1m*\033[0m "
+.set details_head_len, . - details_head
+acc_value: .ascii "accumulator value: "
+.set acc_value_len, . - acc_value
+acc_empty: .ascii "accumulator was empty"
+.set acc_empty_len, . - acc_empty
+code_ptr: .ascii "\n\033[1m*\033[0m code pointer index: "
+.set code_ptr_len, . - code_ptr
+data_ptr: .ascii "\n\033[1m*\033[0m data pointer index: "
+.set data_ptr_len, . - data_ptr
+direction: .ascii "\n\033[1m*\033[0m data pointer direction: "
+.set direction_len, . - direction
+positive: .ascii "positive"
+.set positive_len, . - positive
+negative: .ascii "negative"
+.set negative_len, . - negative
+current_cmd: .ascii "\n\033[1m*\033[0m current command: "
+.set current_cmd_len, . - current_cmd
+previous_cmd: .ascii "\n\033[1m*\033[0m previous command executed: "
+.set previous_cmd_len, . - previous_cmd
+current_cell: .ascii "\n\033[1m*\033[0m current cell value: "
+.set current_cell_len, . - current_cell
+left_cell: .ascii "\n\033[1m*\033[0m left cell value: "
+.set left_cell_len, . - left_cell
+none: .ascii "<none>"
+.set none_len, . - none
+no_cell: .ascii "<cell does not exist>"
+.set no_cell_len, . - no_cell
+exit_status: .ascii "exit status "
+.set exit_status_len, . - exit_status
+not_a_status: .ascii " is not between 0 and 255"
+.set not_a_status_len, . - not_a_status
+invalid_input: .ascii "invalid input"
+.set invalid_input_len, . - invalid_input
+no_memory: .ascii "could not allocate memory for the tape"
+.set no_memory_len, . - no_memory
+too_many_pages: .ascii "too many cells of the tape are in use"
+.set too_many_pages_len, . - too_many_pages
+newline: .ascii "\n"
+
+.text
+.globl _start
+_start:
+    mov rcx, [rsp]
+    lea rbx, [rsp + 16]
+    dec rcx
+1:  test rcx, rcx
+    jz 3f
+    mov rdi, [rbx]
+    lea rsi, [rip + arg_details]
+    call streq
+    or [rip + flag_details], rax
+    mov rdi, [rbx]
+    lea rsi, [rip + arg_d]
+    call streq
+    or [rip + flag_details], rax
+    mov rdi, [rbx]
+    lea rsi, [rip + arg_exit_with_acc]
+    call streq
+    or [rip + flag_exit_with_acc], rax
+    add rbx, 8
+    dec rcx
+    jmp 1b
+3:  xor r12, r12
+    mov r13, 1
+    xor r14, r14
+    xor r15, r15
+    xor ebx, ebx
+    mov qword ptr [rip + last], -1
+    jmp run
+
+# Returns 1 in rax if the NUL-terminated strings at rdi and rsi are equal, and 0 if not. Keeps rcx.
+streq:
+1:  mov al, [rdi]
+    mov dl, [rsi]
+    cmp al, dl
+    jne 2f
+    test al, al
+    jz 3f
+    inc rdi
+    inc rsi
+    jmp 1b
+2:  xor eax, eax
+    ret
+3:  mov eax, 1
+    ret
+
+# Finds the page holding the cell at index rdi. Returns the page in rax, or 0 if none of its cells were ever written,
+# the cell's offset in the page in rdx, the page number in rdi, and the table slot the page would go in in r10, which is 0 if the table is full.
+tape_find:
+    mov rdx, rdi
+    and rdx, PAGE_CELLS - 1
+    sar rdi, PAGE_BITS
+    mov rax, [rip + cache_page]
+    test rax, rax
+    jz 1f
+    cmp rdi, [rip + cache_number]
+    jne 1f
+    ret
+1:  mov rax, rdi
+    movabs rcx, 0x9E3779B97F4A7C15
+    imul rax, rcx
+    shr rax, 64 - TABLE_BITS
+    lea r8, [rip + page_table]
+    mov ecx, TABLE_SIZE
+2:  mov r10, rax
+    shl r10, 4
+    add r10, r8
+    mov r9, [r10 + 8]
+    test r9, r9
+    jz 3f
+    cmp [r10], rdi
+    je 4f
+    inc rax
+    and rax, TABLE_SIZE - 1
+    dec ecx
+    jnz 2b
+    xor r10d, r10d
+3:  xor eax, eax
+    ret
+4:  mov [rip + cache_number], rdi
+    mov [rip + cache_page], r9
+    mov rax, r9
+    ret
+
+# Returns the value of the cell at index rdi in rax, and 1 in rdx if the cell is full or 0 if it's empty.
+tape_get:
+    call tape_find
+    test rax, rax
+    jz 1f
+    bt qword ptr [rax], rdx
+    jnc 1f
+    mov rax, [rax + BITMAP_BYTES + rdx * 8]
+    mov edx, 1
+    ret
+1:  xor eax, eax
+    xor edx, edx
+    ret
+
+# Sets the cell at index rdi to rsi, allocating its page if needed.
+# Throws an OutOfBoundsError if the page table is full or the page can't be allocated.
+tape_set:
+    push rsi
+    call tape_find
+    test rax, rax
+    jnz 1f
+    test r10, r10
+    jz 3f
+    push rdx
+    push rdi
+    push r10
+    mov eax, 9
+    xor edi, edi
+    mov esi, PAGE_BYTES
+    mov edx, 3
+    mov r10d, 0x22
+    mov r8, -1
+    xor r9d, r9d
+    syscall
+    cmp rax, -4096
+    jae 2f
+    pop r10
+    pop rdi
+    pop rdx
+    mov [r10], rdi
+    mov [r10 + 8], rax
+    mov [rip + cache_number], rdi
+    mov [rip + cache_page], rax
+1:  pop rsi
+    bts qword ptr [rax], rdx
+    mov [rax + BITMAP_BYTES + rdx * 8], rsi
+    ret
+2:  mov edi, 6
+    lea rsi, [rip + no_memory]
+    mov edx, no_memory_len
+    xor r9d, r9d
+    jmp fail
+3:  mov edi, 6
+    lea rsi, [rip + too_many_pages]
+    mov edx, too_many_pages_len
+    xor r9d, r9d
+    jmp fail
+
+# Finds the nearest full cell from index rdi on (inclusive) in the direction in rsi. Returns its index in rax and 1 in rdx,
+# or 0 in rdx if there is none. The cells of a page are checked one by one, and once the page runs out or was never written,
+# the page table is searched for the nearest written page that way, so the empty pages in between are skipped.
+tape_next_full:
+    push rbx
+    push rbp
+    mov rbx, rdi
+    mov rbp, rsi
+1:  mov rdi, rbx
+    call tape_find
+    test rax, rax
+    jz 3f
+2:  bt qword ptr [rax], rdx
+    jc 6f
+    add rdx, rbp
+    cmp rdx, PAGE_CELLS
+    jae 3f
+    add rbx, rbp
+    jmp 2b
+3:  imul rdi, rbp
+    lea r8, [rip + page_table]
+    mov ecx, TABLE_SIZE
+    xor edx, edx
+4:  cmp qword ptr [r8 + 8], 0
+    je 5f
+    mov r10, [r8]
+    imul r10, rbp
+    cmp r10, rdi
+    jle 5f
+    test edx, edx
+    jz 7f
+    cmp r10, r11
+    jge 5f
+7:  mov r11, r10
+    mov edx, 1
+5:  add r8, 16
+    dec ecx
+    jnz 4b
+    test edx, edx
+    jz 8f
+    imul r11, rbp
+    shl r11, PAGE_BITS
+    test rbp, rbp
+    jns 9f
+    or r11, PAGE_CELLS - 1
+9:  mov rbx, r11
+    jmp 1b
+6:  mov rax, rbx
+    mov edx, 1
+8:  pop rbp
+    pop rbx
+    ret
+
+# Empties the cell at index rdi.
+tape_clear:
+    call tape_find
+    test rax, rax
+    jz 1f
+    btr qword ptr [rax], rdx
+1:  ret
+
+# Returns the cells to the left of and at the data pointer in rax and rdx, and 1 in rcx if both are full or 0 if not.
+operands:
+    mov rdi, r12
+    sub rdi, 1
+    jo 1f
+    call tape_get
+    test edx, edx
+    jz 1f
+    push rax
+    mov rdi, r12
+    call tape_get
+    mov ecx, edx
+    mov rdx, rax
+    pop rax
+    ret
+1:  xor ecx, ecx
+    ret
+
+# Appends the rdx bytes at rsi to the output.
+write_out:
+    mov rax, [rip + out_len]
+    lea rcx, [rax + rdx]
+    cmp rcx, BUFFER_BYTES
+    jbe 1f
+    push rsi
+    push rdx
+    call flush
+    pop rdx
+    pop rsi
+    xor eax, eax
+1:  lea rdi, [rip + out_buffer]
+    add rdi, rax
+    mov rcx, rdx
+    rep movsb
+    add [rip + out_len], rdx
+    ret
+
+# Writes out everything in the output buffer.
+flush:
+    lea rsi, [rip + out_buffer]
+    mov rdx, [rip + out_len]
+1:  test rdx, rdx
+    jz 2f
+    mov eax, 1
+    mov edi, 1
+    syscall
+    test rax, rax
+    jle 2f
+    add rsi, rax
+    sub rdx, rax
+    jmp 1b
+2:  mov qword ptr [rip + out_len], 0
+    ret
+
+# Writes the rdx bytes at rsi to standard error.
+write_err:
+1:  test rdx, rdx
+    jz 2f
+    mov eax, 1
+    mov edi, 2
+    syscall
+    test rax, rax
+    jle 2f
+    add rsi, rax
+    sub rdx, rax
+    jmp 1b
+2:  ret
+
+# Writes rax in decimal to rdi, and advances rdi past it.
+format_int:
+    mov r8, rax
+    test rax, rax
+    jns 1f
+    mov byte ptr [rdi], '-'
+    inc rdi
+    neg r8
+1:  lea r9, [rip + number + 32]
+    mov rax, r8
+    mov r10, 10
+2:  xor edx, edx
+    div r10
+    add dl, '0'
+    dec r9
+    mov [r9], dl
+    test rax, rax
+    jnz 2b
+    lea rcx, [rip + number + 32]
+    sub rcx, r9
+    mov rsi, r9
+    rep movsb
+    ret
+
+# Prints rax in decimal.
+write_int:
+    lea rdi, [rip + err_buffer]
+    call format_int
+    lea rsi, [rip + err_buffer]
+    mov rdx, rdi
+    sub rdx, rsi
+    jmp write_out
+
+# Prints the character with the code point in rax. Returns 0 in rax without printing anything if there is no such character, and 1 if there is.
+write_char:
+    cmp rax, 0x10FFFF
+    ja 5f
+    mov rdx, rax
+    and rdx, -0x800
+    cmp rdx, 0xD800
+    je 5f
+    lea rdi, [rip + number]
+    cmp rax, 0x80
+    jae 1f
+    mov [rdi], al
+    mov edx, 1
+    jmp 4f
+1:  mov rcx, rax
+    and cl, 0x3F
+    or cl, 0x80
+    cmp rax, 0x800
+    jae 2f
+    mov [rdi + 1], cl
+    shr rax, 6
+    or al, 0xC0
+    mov [rdi], al
+    mov edx, 2
+    jmp 4f
+2:  cmp rax, 0x10000
+    jae 3f
+    mov [rdi + 2], cl
+    mov rcx, rax
+    shr rcx, 6
+    and cl, 0x3F
+    or cl, 0x80
+    mov [rdi + 1], cl
+    shr rax, 12
+    or al, 0xE0
+    mov [rdi], al
+    mov edx, 3
+    jmp 4f
+3:  mov [rdi + 3], cl
+    mov rcx, rax
+    shr rcx, 6
+    and cl, 0x3F
+    or cl, 0x80
+    mov [rdi + 2], cl
+    mov rcx, rax
+    shr rcx, 12
+    and cl, 0x3F
+    or cl, 0x80
+    mov [rdi + 1], cl
+    shr rax, 18
+    or al, 0xF0
+    mov [rdi], al
+    mov edx, 4
+4:  mov rsi, rdi
+    call write_out
+    mov eax, 1
+    ret
+5:  xor eax, eax
+    ret
+
+# Reads a line, including its newline if it has one, and decodes it into line_chars. Returns the number of characters in rax.
+# Throws an InputError if the line can't be read or isn't valid UTF-8.
+read_line:
+    call flush
+    xor r8d, r8d
+1:  mov rax, [rip + in_pos]
+    cmp rax, [rip + in_len]
+    jb 2f
+    push r8
+    xor eax, eax
+    xor edi, edi
+    lea rsi, [rip + in_buffer]
+    mov edx, BUFFER_BYTES
+    syscall
+    pop r8
+    test rax, rax
+    js 9f
+    jz 3f
+    mov [rip + in_len], rax
+    xor eax, eax
+2:  lea rcx, [rip + in_buffer]
+    movzx edx, byte ptr [rcx + rax]
+    inc rax
+    mov [rip + in_pos], rax
+    cmp r8, LINE_BYTES
+    jae 9f
+    lea rcx, [rip + line_bytes]
+    mov [rcx + r8], dl
+    inc r8
+    cmp dl, 10
+    jne 1b
+3:  lea rsi, [rip + line_bytes]
+    lea r10, [rsi + r8]
+    lea rdi, [rip + line_chars]
+    xor r9d, r9d
+4:  cmp rsi, r10
+    jae 8f
+    movzx eax, byte ptr [rsi]
+    inc rsi
+    cmp eax, 0x80
+    jb 7f
+    cmp eax, 0xC2
+    jb 9f
+    cmp eax, 0xE0
+    jb 5f
+    cmp eax, 0xF0
+    jb 6f
+    cmp eax, 0xF5
+    jae 9f
+    and eax, 0x07
+    mov ecx, 3
+    mov r11d, 0x10000
+    jmp 10f
+5:  and eax, 0x1F
+    mov ecx, 1
+    mov r11d, 0x80
+    jmp 10f
+6:  and eax, 0x0F
+    mov ecx, 2
+    mov r11d, 0x800
+10: cmp rsi, r10
+    jae 9f
+    movzx edx, byte ptr [rsi]
+    inc rsi
+    mov r8d, edx
+    and r8d, 0xC0
+    cmp r8d, 0x80
+    jne 9f
+    shl eax, 6
+    and edx, 0x3F
+    or eax, edx
+    dec ecx
+    jnz 10b
+    cmp eax, r11d
+    jb 9f
+    cmp eax, 0x10FFFF
+    ja 9f
+    mov edx, eax
+    and edx, -0x800
+    cmp edx, 0xD800
+    je 9f
+7:  mov [rdi + r9 * 8], rax
+    inc r9
+    jmp 4b
+8:  mov rax, r9
+    ret
+9:  mov edi, 5
+    lea rsi, [rip + invalid_input]
+    mov edx, invalid_input_len
+    xor r9d, r9d
+    jmp fail
+
+# Parses the first rcx characters of the line like Rust parses an isize. Returns the number in rax, and 1 in rdx if it is one or 0 if not.
+parse_int:
+    lea rsi, [rip + line_chars]
+    xor eax, eax
+    xor r8d, r8d
+    test rcx, rcx
+    jz 5f
+    mov rdx, [rsi]
+    cmp rdx, '-'
+    jne 1f
+    mov r8d, 1
+    jmp 2f
+1:  cmp rdx, '+'
+    jne 3f
+2:  add rsi, 8
+    dec rcx
+    jz 5f
+3:  mov rdx, [rsi]
+    sub rdx, '0'
+    cmp rdx, 9
+    ja 5f
+    imul rax, rax, 10
+    jo 5f
+    test r8d, r8d
+    jnz 4f
+    add rax, rdx
+    jo 5f
+    jmp 6f
+4:  sub rax, rdx
+    jo 5f
+6:  add rsi, 8
+    dec rcx
+    jnz 3b
+    mov edx, 1
+    ret
+5:  xor edx, edx
+    ret
+
+# Writes the first rax characters of the line into the tape from the data pointer on.
+# Returns 1 in rdx, or 0 if the line went beyond the end of the tape.
+store_line:
+    push rbx
+    push rbp
+    push r12
+    mov rbp, rax
+    xor ebx, ebx
+1:  cmp rbx, rbp
+    jae 2f
+    mov rdi, [rsp]
+    lea rax, [rip + line_chars]
+    mov rsi, [rax + rbx * 8]
+    call tape_set
+    movabs rax, 0x7FFFFFFFFFFFFFFF
+    cmp [rsp], rax
+    je 3f
+    inc qword ptr [rsp]
+    inc rbx
+    jmp 1b
+2:  mov edx, 1
+    jmp 4f
+3:  xor edx, edx
+4:  pop rax
+    pop rbp
+    pop rbx
+    ret
+
+# Ends the program with the status in rdi after `.` at the command in rsi, printing the details first if asked to.
+stop:
+    push rdi
+    push rsi
+    cmp qword ptr [rip + flag_details], 0
+    je 1f
+    lea rsi, [rip + newline]
+    mov edx, 1
+    call write_out
+    call flush
+    mov rcx, [rsp]
+    mov r8, rcx
+    call details
+1:  call flush
+    pop rsi
+    pop rdi
+    mov eax, 60
+    syscall
+
+# Throws an OverflowError for `.` exiting with the accumulator's value when it isn't a valid exit status.
+fail_exit_status:
+    push rcx
+    push r8
+    lea rdi, [rip + err_buffer + 2048]
+    APPEND exit_status
+    mov rax, r14
+    call format_int
+    APPEND not_a_status
+    lea rsi, [rip + err_buffer + 2048]
+    mov rdx, rdi
+    sub rdx, rsi
+    pop r8
+    pop rcx
+    mov edi, 7
+    mov r9d, 1
+    jmp fail
+
+# Throws the error of kind rdi with the rdx bytes at rsi as its message, then, unless r9 is 0,
+# prints the details with rcx as the code pointer and the command at r8 as the current command. Exits with the error's status.
+fail:
+    mov [rip + fail_kind], rdi
+    mov [rip + fail_msg], rsi
+    mov [rip + fail_len], rdx
+    mov [rip + fail_pc], rcx
+    mov [rip + fail_cur], r8
+    mov [rip + fail_details], r9
+    call flush
+    lea rdi, [rip + err_buffer]
+    APPEND red
+    mov rax, [rip + fail_kind]
+    shl rax, 4
+    lea rdx, [rip + error_names]
+    mov rsi, [rdx + rax]
+    mov rcx, [rdx + rax + 8]
+    rep movsb
+    APPEND colon
+    mov rsi, [rip + fail_msg]
+    mov rcx, [rip + fail_len]
+    rep movsb
+    mov byte ptr [rdi], 10
+    inc rdi
+    lea rsi, [rip + err_buffer]
+    mov rdx, rdi
+    sub rdx, rsi
+    call write_err
+    cmp qword ptr [rip + fail_details], 0
+    je 1f
+    mov rcx, [rip + fail_pc]
+    mov r8, [rip + fail_cur]
+    call details
+1:  mov rdi, [rip + fail_kind]
+    add edi, 10
+    mov eax, 60
+    syscall
+
+# Prints the details like the interpreter does, with rcx as the code pointer and the command at r8 as the current command.
+details:
+    mov [rip + details_pc], rcx
+    mov [rip + details_cur], r8
+    lea rdi, [rip + err_buffer]
+    APPEND details_head
+    test r15, r15
+    jz 1f
+    APPEND acc_value
+    mov rax, r14
+    call format_int
+    jmp 2f
+1:  APPEND acc_empty
+2:  APPEND code_ptr
+    mov rax, [rip + details_pc]
+    call format_int
+    APPEND data_ptr
+    mov rax, r12
+    call format_int
+    APPEND direction
+    cmp r13, 1
+    jne 3f
+    APPEND positive
+    jmp 4f
+3:  APPEND negative
+4:  APPEND current_cmd
+    mov rax, [rip + details_cur]
+    call append_symbol
+    APPEND previous_cmd
+    mov rax, [rip + last]
+    cmp rax, -1
+    jne 5f
+    APPEND none
+    jmp 6f
+5:  call append_symbol
+6:  APPEND current_cell
+    push rdi
+    mov rdi, r12
+    call tape_get
+    pop rdi
+    call append_cell
+    APPEND left_cell
+    movabs rax, 0x8000000000000000
+    cmp r12, rax
+    jne 7f
+    APPEND no_cell
+    jmp 8f
+7:  push rdi
+    lea rdi, [r12 - 1]
+    call tape_get
+    pop rdi
+    call append_cell
+8:  mov byte ptr [rdi], 10
+    inc rdi
+    lea rsi, [rip + err_buffer]
+    mov rdx, rdi
+    sub rdx, rsi
+    jmp write_err
+
+# Appends the value rax of a cell to rdi if rdx is 1, or `<none>` if it's 0.
+append_cell:
+    test edx, edx
+    jz 1f
+    jmp format_int
+1:  APPEND none
+    ret
+
+# Appends the symbol of the command at rax to rdi.
+append_symbol:
+    shl rax, 4
+    lea rdx, [rip + symbols]
+    mov rsi, [rdx + rax]
+    mov rcx, [rdx + rax + 8]
+    rep movsb
+    ret
+"#;
+
+    const ERRORS: [Error; 8] = [
+        Error::FileError,
+        Error::UnknownSymbolError,
+        Error::OpError,
+        Error::AccumulatorError,
+        Error::SyntaxError,
+        Error::InputError,
+        Error::OutOfBoundsError,
+        Error::OverflowError,
+    ];
+
+    /// Where the code pointer shown in an error's details comes from.
+    #[derive(Clone, Copy)]
+    enum Pc {
+        /// A command known when compiling.
+        At(usize),
+        /// A command computed by `J` or `j`, which is in rax.
+        Computed,
+    }
+
+    struct Compiler {
+        len: usize,
+        text: String,
+        stubs: String,
+        strings: HashMap<String, String>,
+        data: String,
+    }
+
+    impl Compiler {
+        fn line(&mut self, instruction: &str) {
+            self.text.push_str("    ");
+            self.text.push_str(instruction);
+            self.text.push('\n');
+        }
+
+        /// Returns the label of a string in the read-only data, whose length is `<label>_len`.
+        fn string(&mut self, text: &str) -> String {
+            if let Some(label) = self.strings.get(text) {
+                return label.clone()
+            }
+            let label = format!("str_{}", self.strings.len());
+            let escaped: String = text.chars().map(|c| match c {
+                '"' => "\\\"".to_string(),
+                '\\' => "\\\\".to_string(),
+                c if c.is_ascii_graphic() || c == ' ' => c.to_string(),
+                c => c.to_string().bytes().map(|b| format!("\\{b:03o}")).collect(),
+            }).collect();
+            self.data.push_str(&format!("{label}: .ascii \"{escaped}\"\n.set {label}_len, . - {label}\n"));
+            self.strings.insert(text.to_string(), label.clone());
+            label
+        }
+
+        /// Returns the label of code that throws an error while running the command at `current`.
+        fn error(&mut self, error: Error, msg: &str, pc: Pc, current: usize) -> String {
+            let msg = self.string(msg);
+            let label = format!(".Lerror_{}", self.stubs.matches(':').count());
+            let pc = match pc {
+                Pc::At(index) => format!("mov ecx, {index}"),
+                Pc::Computed => "mov rcx, rax".to_string(),
+            };
+            self.stubs.push_str(&format!("{label}:
+    mov edi, {}
+    lea rsi, [rip + {msg}]
+    mov edx, OFFSET {msg}_len
+    {pc}
+    mov r8d, {current}
+    mov r9d, 1
+    jmp fail
+", error.exit_code() - 10));
+            label
+        }
+
+        /// Moves the data pointer and goes on with the command after `target`, where the command at `index` left the code pointer.
+        fn step(&mut self, index: usize, target: usize, moving: bool) {
+            if target + 1 >= self.len {
+                let error = self.error(Error::OutOfBoundsError, "code pointer went out of bounds", Pc::At(target), index);
+                self.line(&format!("jmp {error}"));
+                return
+            }
+            if moving {
+                let error = self.error(Error::OutOfBoundsError, "data pointer went out of bounds", Pc::At(target), index);
+                self.line("mov rax, r12");
+                self.line("add rax, r13");
+                self.line(&format!("jo {error}"));
+                self.line("mov r12, rax");
+            }
+            self.line(&format!("mov qword ptr [rip + last], {index}"));
+            if target != index {
+                self.line(&format!("jmp cmd_{}", target + 1));
+            }
+        }
+
+        /// Goes on with the command after the one in rax, which `J` or `j` at `index` jumped to.
+        fn computed_step(&mut self, index: usize) {
+            let code = self.error(Error::OutOfBoundsError, "code pointer went out of bounds", Pc::Computed, index);
+            let data = self.error(Error::OutOfBoundsError, "data pointer went out of bounds", Pc::Computed, index);
+            self.line("lea rdx, [rax + 1]");
+            self.line(&format!("cmp rdx, {}", self.len));
+            self.line(&format!("jae {code}"));
+            self.line("mov rcx, r12");
+            self.line("add rcx, r13");
+            self.line(&format!("jo {data}"));
+            self.line("mov r12, rcx");
+            self.line(&format!("mov qword ptr [rip + last], {index}"));
+            self.line("lea rcx, [rip + jump_table]");
+            self.line("jmp [rcx + rdx * 8]");
+        }
+
+        /// Emits the conditional `symbol` at `index`, where `condition` branches to `.Ltaken_<index>` when the conditional jumps.
+        fn conditional(&mut self, index: usize, symbol: char, target: Option<usize>, condition: &[&str], clears_acc: bool) {
+            for instruction in condition {
+                self.line(instruction);
+            }
+            if clears_acc {
+                self.line("xor r15d, r15d");
+            }
+            self.step(index, index, true);
+            self.line(&format!("jmp cmd_{}", index + 1));
+            self.text.push_str(&format!(".Ltaken_{index}:\n"));
+            match target {
+                Some(target) => {
+                    if clears_acc {
+                        self.line("xor r15d, r15d");
+                    }
+                    self.step(index, target, true);
+                }
+                None => {
+                    let error = self.error(Error::SyntaxError, &format!("conditional `{symbol}` does not have a corresponding `@`"), Pc::At(index), index);
+                    self.line(&format!("jmp {error}"));
+                }
+            }
+        }
+    }
+
+    /// Compiles a program into GNU assembler source for x86-64 Linux that behaves like the interpreter running it,
+    /// down to the errors it throws and their messages. It doesn't use libc, so it can be built with `as` and `ld` alone.
+    /// The compiled program accepts `--details` and `--exit-with-acc` like `guryvsr` does.
+    ///
+    /// Every command gets a label, and `J` and `j` jump through a table of them indexed by command position.
+    /// Lines longer than 4 MiB can't be read.
+    pub fn compile_x86_64_linux(tokens: &[Token]) -> String {
+        let matches = match_conditionals(tokens);
+        let mut c = Compiler { len: tokens.len(), text: "".to_string(), stubs: "".to_string(), strings: HashMap::new(), data: "".to_string() };
+        c.text.push_str("run:\n");
+        if tokens.is_empty() {
+            c.line("xor edi, edi");
+            c.line("mov eax, 60");
+            c.line("syscall");
+        }
+        for (index, token) in tokens.iter().enumerate() {
+            let symbol = token_to_symbol(token).chars().next().unwrap();
+            c.text.push_str(&format!("cmd_{index}: # {symbol}\n"));
+            if !matches!(token, Token::CreatingNumber | Token::Digit(_)) {
+                c.line("xor ebx, ebx");
+            }
+            let went_wrong = format!("execution of `{symbol}` went wrong");
+            let mut moving = true;
+            match *token {
+                Token::NoOp | Token::DestinationIfTrue | Token::Nothing => {}
+                Token::Stop => {
+                    c.line("xor edi, edi");
+                    c.line("cmp qword ptr [rip + flag_exit_with_acc], 0");
+                    c.line("je 1f");
+                    c.line("test r15, r15");
+                    c.line("jz 1f");
+                    c.line("cmp r14, 255");
+                    c.line(&format!("ja .Lexit_status_{index}"));
+                    c.line("mov rdi, r14");
+                    c.text.push_str("1:\n");
+                    c.line(&format!("mov esi, {index}"));
+                    c.line("jmp stop");
+                    c.stubs.push_str(&format!(".Lexit_status_{index}:\n    mov ecx, {index}\n    mov r8d, {index}\n    jmp fail_exit_status\n"));
+                    continue
+                }
+                Token::CreatingNumber => {
+                    let creating = c.error(Error::SyntaxError, "already creating number", Pc::At(index), index);
+                    let acc = c.error(Error::AccumulatorError, &went_wrong, Pc::At(index), index);
+                    c.line("test ebx, ebx");
+                    c.line(&format!("jnz {creating}"));
+                    c.line("test r15, r15");
+                    c.line(&format!("jnz {acc}"));
+                    c.line("mov ebx, 1");
+                    moving = false;
+                }
+                Token::Digit(n) => {
+                    let syntax = c.error(Error::SyntaxError, &went_wrong, Pc::At(index), index);
+                    let overflow = c.error(Error::OverflowError, &format!("digit {n} caused overflow"), Pc::At(index), index);
+                    c.line("test ebx, ebx");
+                    c.line(&format!("jz {syntax}"));
+                    c.line(&format!("mov eax, {n}"));
+                    c.line("test r15, r15");
+                    c.line("jz 1f");
+                    c.line("imul rax, r14, 10");
+                    c.line(&format!("jo {overflow}"));
+                    c.line(&format!("add rax, {n}"));
+                    c.line(&format!("jo {overflow}"));
+                    c.text.push_str("1:\n");
+                    c.line("mov r14, rax");
+                    c.line("mov r15d, 1");
+                    moving = false;
+                }
+                Token::Unload | Token::Distribute => {
+                    let acc = c.error(Error::AccumulatorError, &went_wrong, Pc::At(index), index);
+                    c.line("test r15, r15");
+                    c.line(&format!("jz {acc}"));
+                    c.line("mov rdi, r12");
+                    c.line("mov rsi, r14");
+                    c.line("call tape_set");
+                    if *token == Token::Unload {
+                        c.line("xor r15d, r15d");
+                    }
+                }
+                Token::Recall | Token::Copy => {
+                    let op = c.error(Error::OpError, &went_wrong, Pc::At(index), index);
+                    c.line("mov rdi, r12");
+                    c.line("call tape_get");
+                    c.line("test edx, edx");
+                    c.line(&format!("jz {op}"));
+                    c.line("mov r14, rax");
+                    c.line("mov r15d, 1");
+                    if *token == Token::Recall {
+                        c.line("mov rdi, r12");
+                        c.line("call tape_clear");
+                    }
+                }
+                Token::ClearAcc => c.line("xor r15d, r15d"),
+                Token::ClearCurrCell => {
+                    c.line("mov rdi, r12");
+                    c.line("call tape_clear");
+                }
+                Token::ZeroOrEmpty | Token::NotZeroOrEmpty => {
+                    let taken = format!(".Ltaken_{index}");
+                    let condition = if *token == Token::ZeroOrEmpty {
+                        vec!["mov rdi, r12".to_string(), "call tape_get".to_string(), "test edx, edx".to_string(), format!("jz {taken}"), "test rax, rax".to_string(), format!("jz {taken}")]
+                    } else {
+                        vec!["mov rdi, r12".to_string(), "call tape_get".to_string(), "test edx, edx".to_string(), "jz 1f".to_string(), "test rax, rax".to_string(), format!("jnz {taken}"), "1:".to_string()]
+                    };
+                    let condition: Vec<&str> = condition.iter().map(String::as_str).collect();
+                    c.conditional(index, symbol, matches[index], &condition, false);
+                    continue
+                }
+                Token::TgtZeroOrEmpty | Token::TgtNotZeroOrEmpty => {
+                    let acc = c.error(Error::AccumulatorError, &went_wrong, Pc::At(index), index);
+                    c.line("test r15, r15");
+                    c.line(&format!("jz {acc}"));
+                    let taken = format!(".Ltaken_{index}");
+                    let condition = if *token == Token::TgtZeroOrEmpty {
+                        vec!["mov rdi, r14".to_string(), "call tape_get".to_string(), "test edx, edx".to_string(), format!("jz {taken}"), "test rax, rax".to_string(), format!("jz {taken}")]
+                    } else {
+                        vec!["mov rdi, r14".to_string(), "call tape_get".to_string(), "test edx, edx".to_string(), "jz 1f".to_string(), "test rax, rax".to_string(), format!("jnz {taken}"), "1:".to_string()]
+                    };
+                    let condition: Vec<&str> = condition.iter().map(String::as_str).collect();
+                    c.conditional(index, symbol, matches[index], &condition, true);
+                    continue
+                }
+                Token::AccZeroOrEmpty | Token::AccNotZeroOrEmpty => {
+                    let taken = format!(".Ltaken_{index}");
+                    let condition = if *token == Token::AccZeroOrEmpty {
+                        vec!["test r15, r15".to_string(), format!("jz {taken}"), "test r14, r14".to_string(), format!("jz {taken}")]
+                    } else {
+                        vec!["test r15, r15".to_string(), "jz 1f".to_string(), "test r14, r14".to_string(), format!("jnz {taken}"), "1:".to_string()]
+                    };
+                    let condition: Vec<&str> = condition.iter().map(String::as_str).collect();
+                    c.conditional(index, symbol, matches[index], &condition, false);
+                    continue
+                }
+                Token::JumpCellsC => {
+                    let op = c.error(Error::OpError, &went_wrong, Pc::At(index), index);
+                    let overflow = c.error(Error::OverflowError, "code pointer went out of bounds when executing `J`", Pc::At(index), index);
+                    c.line("test r15, r15");
+                    c.line(&format!("jz {op}"));
+                    c.line("xor r15d, r15d");
+                    c.line(&format!("lea rax, [r14 + {index}]"));
+                    c.line(&format!("cmp rax, {}", tokens.len()));
+                    c.line(&format!("jae {overflow}"));
+                    c.computed_step(index);
+                    continue
+                }
+                Token::JumpToCellC => {
+                    let op = c.error(Error::OpError, &went_wrong, Pc::At(index), index);
+                    let overflow = c.error(Error::OverflowError, "code pointer went out of bounds when executing `j`", Pc::At(index), index);
+                    c.line("test r15, r15");
+                    c.line(&format!("jz {op}"));
+                    c.line(&format!("cmp r14, {}", tokens.len()));
+                    c.line(&format!("jae {overflow}"));
+                    c.line("xor r15d, r15d");
+                    c.line("mov rax, r14");
+                    c.computed_step(index);
+                    continue
+                }
+                Token::JumpCellsD => {
+                    let op = c.error(Error::OpError, &went_wrong, Pc::At(index), index);
+                    let overflow = c.error(Error::OverflowError, "data pointer went out of bounds when executing `K`", Pc::At(index), index);
+                    c.line("test r15, r15");
+                    c.line(&format!("jz {op}"));
+                    c.line("xor r15d, r15d");
+                    c.line("mov rax, r12");
+                    c.line("add rax, r14");
+                    c.line(&format!("jo {overflow}"));
+                    c.line("mov r12, rax");
+                    moving = false;
+                }
+                Token::JumpToCellD => {
+                    let op = c.error(Error::OpError, &went_wrong, Pc::At(index), index);
+                    c.line("test r15, r15");
+                    c.line(&format!("jz {op}"));
+                    c.line("xor r15d, r15d");
+                    c.line("mov r12, r14");
+                    moving = false;
+                }
+                Token::MoveDUntilEmpty => {
+                    let overflow = c.error(Error::OverflowError, "data pointer went out of bounds when executing `M`", Pc::At(index), index);
+                    c.text.push_str("1:\n");
+                    c.line("mov rdi, r12");
+                    c.line("call tape_get");
+                    c.line("test edx, edx");
+                    c.line("jz 2f");
+                    c.line("mov rax, r12");
+                    c.line("add rax, r13");
+                    c.line(&format!("jo {overflow}"));
+                    c.line("mov r12, rax");
+                    c.line("jmp 1b");
+                    c.text.push_str("2:\n");
+                    moving = false;
+                }
+                Token::MoveDUntilFull => {
+                    let overflow = c.error(Error::OverflowError, "data pointer went out of bounds when executing `m`", Pc::At(index), index);
+                    c.line("mov rdi, r12");
+                    c.line("mov rsi, r13");
+                    c.line("call tape_next_full");
+                    c.line("test edx, edx");
+                    c.line("jnz 1f");
+                    // there is no full cell that way, so the data pointer would have gone to the end of the tape
+                    c.line("movabs r12, 0x7FFFFFFFFFFFFFFF");
+                    c.line("test r13, r13");
+                    c.line(&format!("jns {overflow}"));
+                    c.line("not r12");
+                    c.line(&format!("jmp {overflow}"));
+                    c.text.push_str("1:\n");
+                    c.line("mov r12, rax");
+                    moving = false;
+                }
+                Token::FlipD(_) => c.line("neg r13"),
+                Token::Neg => {
+                    let acc = c.error(Error::AccumulatorError, &went_wrong, Pc::At(index), index);
+                    c.line("test r15, r15");
+                    let overflow = c.error(Error::OverflowError, "command `-` caused overflow", Pc::At(index), index);
+                    c.line(&format!("jz {acc}"));
+                    c.line("neg r14");
+                    c.line(&format!("jo {overflow}"));
+                }
+                Token::BitNot | Token::OutputInt | Token::OutputChar => {
+                    let msg = match *token {
+                        Token::BitNot => went_wrong.clone(),
+                        _ => format!("something went wrong while executing `{symbol}`"),
+                    };
+                    let op = c.error(Error::OpError, &msg, Pc::At(index), index);
+                    c.line("mov rdi, r12");
+                    c.line("call tape_get");
+                    c.line("test edx, edx");
+                    c.line(&format!("jz {op}"));
+                    match *token {
+                        Token::BitNot => {
+                            c.line("not rax");
+                            c.line("mov r14, rax");
+                            c.line("mov r15d, 1");
+                        }
+                        Token::OutputInt => c.line("call write_int"),
+                        _ => {
+                            let invalid = c.error(Error::OpError, "the current cell's value cannot be represented as a valid UTF-8 character", Pc::At(index), index);
+                            c.line("call write_char");
+                            c.line("test eax, eax");
+                            c.line(&format!("jz {invalid}"));
+                        }
+                    }
+                }
+                Token::InputInt => {
+                    let input = c.string("invalid input");
+                    c.line("call read_line");
+                    c.line("test rax, rax");
+                    c.line("jz 1f");
+                    c.line("dec rax");
+                    c.text.push_str("1:\n");
+                    c.line("mov rcx, rax");
+                    c.line("call parse_int");
+                    c.line("test edx, edx");
+                    c.line("jnz 2f");
+                    c.line("mov edi, 5");
+                    c.line(&format!("lea rsi, [rip + {input}]"));
+                    c.line(&format!("mov edx, OFFSET {input}_len"));
+                    c.line("xor r9d, r9d");
+                    c.line("jmp fail");
+                    c.text.push_str("2:\n");
+                    c.line("mov r14, rax");
+                    c.line("mov r15d, 1");
+                    moving = false;
+                }
+                Token::InputStr => {
+                    let input = c.error(Error::InputError, "input too long; went beyond tape boundaries", Pc::At(index), index);
+                    c.line("call read_line");
+                    c.line("test rax, rax");
+                    c.line("jz 1f");
+                    c.line("dec rax");
+                    c.text.push_str("1:\n");
+                    c.line("call store_line");
+                    c.line("test edx, edx");
+                    c.line(&format!("jz {input}"));
+                }
+                _ => {
+                    // the commands that combine the left and current cells into the accumulator
+                    let op = c.error(Error::OpError, &went_wrong, Pc::At(index), index);
+                    let acc = c.error(Error::AccumulatorError, &went_wrong, Pc::At(index), index);
+                    let divides = matches!(*token, Token::Div | Token::Mod);
+                    c.line("call operands");
+                    c.line("test ecx, ecx");
+                    c.line(&format!("jz {op}"));
+                    if divides {
+                        let zero = c.error(Error::OpError, &format!("division by zero caused by `{symbol}`"), Pc::At(index), index);
+                        c.line("test rdx, rdx");
+                        c.line(&format!("jz {zero}"));
+                    }
+                    c.line("test r15, r15");
+                    c.line(&format!("jnz {acc}"));
+                    let overflow = format!("command `{symbol}` caused overflow");
+                    match *token {
+                        Token::Add | Token::Mul => {
+                            let overflow = c.error(Error::OverflowError, &overflow, Pc::At(index), index);
+                            c.line(if *token == Token::Add { "add rax, rdx" } else { "imul rax, rdx" });
+                            c.line(&format!("jo {overflow}"));
+                        }
+                        Token::Div | Token::Mod => {
+                            let overflow = c.error(Error::OverflowError, &overflow, Pc::At(index), index);
+                            c.line("mov rcx, rdx");
+                            c.line("cmp rcx, -1");
+                            c.line("jne 1f");
+                            c.line("movabs r8, 0x8000000000000000");
+                            c.line("cmp rax, r8");
+                            c.line(&format!("je {overflow}"));
+                            c.text.push_str("1:\n");
+                            c.line("cqo");
+                            c.line("idiv rcx");
+                            if *token == Token::Mod {
+                                c.line("mov rax, rdx");
+                            } else {
+                                // round towards negative infinity when dividing by a positive number, and towards positive infinity otherwise
+                                c.line("test rdx, rdx");
+                                c.line("jns 2f");
+                                c.line("test rcx, rcx");
+                                c.line("js 3f");
+                                c.line("dec rax");
+                                c.line("jmp 2f");
+                                c.text.push_str("3:\n");
+                                c.line("inc rax");
+                                c.text.push_str("2:\n");
+                            }
+                        }
+                        Token::BitAnd => c.line("and rax, rdx"),
+                        Token::BitOr => c.line("or rax, rdx"),
+                        Token::BitXor => c.line("xor rax, rdx"),
+                        _ => {
+                            let set = match *token {
+                                Token::Eq => "sete",
+                                Token::NotEq(_) => "setne",
+                                Token::Gt => "setg",
+                                Token::GE(_) => "setge",
+                                Token::Lt => "setl",
+                                _ => "setle",
+                            };
+                            c.line("cmp rax, rdx");
+                            c.line(&format!("{set} al"));
+                            c.line("movzx eax, al");
+                        }
+                    }
+                    c.line("mov r14, rax");
+                    c.line("mov r15d, 1");
+                }
+            }
+            c.step(index, index, moving);
+        }
+
+        let names: Vec<String> = ERRORS.iter().map(|error| c.string(&format!("{error:?}"))).collect();
+        let symbols: Vec<String> = tokens.iter().map(|token| c.string(token_to_symbol(token))).collect();
+        let mut out = RUNTIME.to_string();
+        out.push_str(&c.text);
+        out.push_str(&c.stubs);
+        out.push_str("\n.section .rodata\n.balign 8\nerror_names:\n");
+        for name in names {
+            out.push_str(&format!("    .quad {name}, {name}_len\n"));
+        }
+        out.push_str("symbols:\n");
+        for symbol in symbols {
+            out.push_str(&format!("    .quad {symbol}, {symbol}_len\n"));
+        }
+        out.push_str("jump_table:\n");
+        for index in 0..tokens.len() {
+            out.push_str(&format!("    .quad cmd_{index}\n"));
+        }
+        out.push_str(&c.data);
+        out
+    }
+}
@@ -0,0 +1,155 @@
+pub mod mnemonic {
+    use crate::formatter::fmt::format_source;
+    use crate::lexer::lex::*;
+
+    /// The mnemonic of every command, in the order of [`Token`]. Aliases like `F` and `f` share a mnemonic and are told apart by their symbol.
+    const MNEMONICS: [(&str, Token); 43] = [
+        ("NOP", Token::NoOp),
+        ("STOP", Token::Stop),
+        ("CREATE_INT", Token::CreatingNumber),
+        ("DIGIT", Token::Digit(0)),
+        ("UNLOAD", Token::Unload),
+        ("DISTRIBUTE", Token::Distribute),
+        ("RECALL", Token::Recall),
+        ("COPY", Token::Copy),
+        ("CLEAR_ACC", Token::ClearAcc),
+        ("CLEAR_CELL", Token::ClearCurrCell),
+        ("IF_ZERO", Token::ZeroOrEmpty),
+        ("IF_NOT_ZERO", Token::NotZeroOrEmpty),
+        ("IF_TGT_ZERO", Token::TgtZeroOrEmpty),
+        ("IF_TGT_NOT_ZERO", Token::TgtNotZeroOrEmpty),
+        ("IF_ACC_ZERO", Token::AccZeroOrEmpty),
+        ("IF_ACC_NOT_ZERO", Token::AccNotZeroOrEmpty),
+        ("END_IF", Token::DestinationIfTrue),
+        ("JUMP_CMDS", Token::JumpCellsC),
+        ("JUMP_TO_CMD", Token::JumpToCellC),
+        ("JUMP_CELLS", Token::JumpCellsD),
+        ("JUMP_TO_CELL", Token::JumpToCellD),
+        ("FLIP", Token::FlipD(true)),
+        ("MOVE_TO_EMPTY", Token::MoveDUntilEmpty),
+        ("MOVE_TO_FULL", Token::MoveDUntilFull),
+        ("ADD", Token::Add),
+        ("NEG", Token::Neg),
+        ("MUL", Token::Mul),
+        ("DIV", Token::Div),
+        ("MOD", Token::Mod),
+        ("EQ", Token::Eq),
+        ("NOT_EQ", Token::NotEq(true)),
+        ("GT", Token::Gt),
+        ("GE", Token::GE(true)),
+        ("LT", Token::Lt),
+        ("LE", Token::LE(true)),
+        ("BIT_AND", Token::BitAnd),
+        ("BIT_OR", Token::BitOr),
+        ("BIT_NOT", Token::BitNot),
+        ("BIT_XOR", Token::BitXor),
+        ("OUT_INT", Token::OutputInt),
+        ("OUT_CHAR", Token::OutputChar),
+        ("IN_INT", Token::InputInt),
+        ("IN_STR", Token::InputStr),
+    ];
+
+    /// Returns the mnemonic of a command.
+    pub fn token_to_mnemonic(token: &Token) -> &'static str {
+        let same_kind = |(_, other): &&(&str, Token)| std::mem::discriminant(other) == std::mem::discriminant(token);
+        MNEMONICS.iter().find(same_kind).map_or("NOTHING", |(mnemonic, _)| mnemonic)
+    }
+
+    /// Returns the nesting depth of every command: the number of matched conditionals it is between.
+    /// A conditional and its matching `@` are at the depth of the commands around them.
+    fn depths(tokens: &[Token], targets: &[Option<usize>]) -> Vec<usize> {
+        let mut depth = 0;
+        tokens.iter().zip(targets).map(|(token, target)| {
+            if *token == Token::DestinationIfTrue && target.is_some() {
+                depth -= 1;
+            }
+            let current = depth;
+            if token.is_conditional() && target.is_some() {
+                depth += 1;
+            }
+            current
+        }).collect()
+    }
+
+    /// Lists a program with a line for each command: its index, its mnemonic indented by its depth, its symbol,
+    /// and a comment with its depth and, for a conditional or an `@`, the index of the command it is paired with.
+    pub fn disassemble(tokens: &[Token], targets: &[Option<usize>]) -> String {
+        let width = tokens.len().saturating_sub(1).to_string().len();
+        let mut out = "".to_string();
+        for ((index, token), depth) in tokens.iter().enumerate().zip(depths(tokens, targets)) {
+            let mnemonic = format!("{}{}", "  ".repeat(depth), token_to_mnemonic(token));
+            let line = format!("{index:>width$}  {mnemonic:<24} {:<3} ; depth {depth}", token_to_symbol(token));
+            out.push_str(&line);
+            if let Some(target) = targets[index] {
+                out.push_str(&format!(", matches {target}"));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Turns a listing in the format [`disassemble`] prints back into source.
+    ///
+    /// Each line holds at most one command, written as its mnemonic and optionally its symbol, which tells aliases apart and is
+    /// required after `DIGIT`. A number at the start of a line, like the index `disassemble` prints, is ignored, and so is everything after a `;`.
+    /// A line can also be a label like `loop:`, which becomes `{label loop}`, or `TO loop` or `BY loop`, which become the number literal that
+    /// makes a `JUMP_TO_CMD` or `JUMP_CMDS` right after it continue at the label (see [`expand_source`](crate::preprocessor::macros::expand_source)).
+    /// Returns a message with the line number if a line isn't valid.
+    pub fn assemble(src: &str) -> Result<String, String> {
+        let mut symbols = "".to_string();
+        let mut pieces: Vec<String> = vec![];
+        for (number, line) in src.lines().enumerate() {
+            let fail = |msg: String| Err(format!("line {}: {msg}", number + 1));
+            let code = line.split(';').next().unwrap();
+            let mut words = code.split_whitespace().skip_while(|word| word.chars().all(|c| c.is_ascii_digit()));
+            let Some(word) = words.next() else { continue };
+            let operand = words.next();
+            if let Some(extra) = words.next() {
+                return fail(format!("unexpected `{extra}`"))
+            }
+            if let Some(label) = word.strip_suffix(':') {
+                if operand.is_some() || !is_name(label) {
+                    return fail(format!("`{word}` is not a valid label"))
+                }
+                pieces.push(std::mem::take(&mut symbols));
+                pieces.push(format!("{{label {label}}}"));
+                continue
+            }
+            if word == "TO" || word == "BY" {
+                let Some(label) = operand.filter(|label| is_name(label)) else {
+                    return fail(format!("expected the name of a label after `{word}`"))
+                };
+                pieces.push(std::mem::take(&mut symbols));
+                pieces.push(format!("{{{} {label}}}", word.to_lowercase()));
+                continue
+            }
+            let Some((_, default)) = MNEMONICS.iter().find(|(mnemonic, _)| *mnemonic == word) else {
+                return fail(format!("unknown mnemonic `{word}`"))
+            };
+            let token = match operand {
+                None if *default == Token::Digit(0) => return fail("`DIGIT` needs the digit after it".to_string()),
+                None => *default,
+                Some(symbol) => {
+                    let mut chars = symbol.chars();
+                    match (chars.next().and_then(symbol_to_token), chars.next()) {
+                        (Some(token), None) if token_to_mnemonic(&token) == word => token,
+                        _ => return fail(format!("`{symbol}` is not the symbol of `{word}`"))
+                    }
+                }
+            };
+            symbols.push_str(token_to_symbol(&token));
+        }
+        if pieces.is_empty() {
+            // without labels, the source can be laid out like `fmt` does
+            return Ok(format_source(&symbols, 80).unwrap_or(symbols))
+        }
+        pieces.push(symbols);
+        let mut out = pieces.into_iter().filter(|piece| !piece.is_empty()).collect::<Vec<_>>().join(" ");
+        out.push('\n');
+        Ok(out)
+    }
+
+    fn is_name(text: &str) -> bool {
+        !text.is_empty() && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+}
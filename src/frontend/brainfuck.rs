@@ -0,0 +1,396 @@
+pub mod bf {
+    //! Translates Brainfuck into gur yvsr.
+    //!
+    //! Brainfuck cell `i` lives in gur yvsr cell `4i`, and the three cells around it (`4i - 1`, `4i + 1` and `4i + 2`) are its scratch space.
+    //! Since the data pointer moves after almost every command, the translator tracks where it is relative to the current Brainfuck cell
+    //! and steers it with `_` and `F`, so that every translated Brainfuck command starts and ends on the current cell, facing the positive direction.
+    //! Both ends of a conditional always leave the data pointer in the same place, so the tracking stays correct across conditionals and loops.
+    //!
+    //! Cells are kept between 0 and 255 with `%`, and an empty cell is read as 0.
+    //! `,` reads a line into a buffer far in the negative direction and hands it out one character at a time, followed by a newline.
+    //! An empty line or the end of input reads as 0. Because the buffer can only be reached through an absolute jump, programs using `,`
+    //! also keep the index of the current cell in a fixed cell so that they can find their way back.
+
+    /// The frame of reference that a tracked data pointer position is relative to.
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    enum Frame {
+        /// The current Brainfuck cell.
+        Cell,
+        /// Cell 0 of the tape.
+        Absolute,
+        /// The next unread character of the input buffer.
+        Cursor,
+    }
+
+    // fixed cells used when the program has `,`
+    const POINTER: isize = -8;         // index of the current Brainfuck cell; -9 is its scratch cell
+    const CURSOR: isize = -12;         // index of the next unread character; -13 always holds 1
+    const BUFFER: isize = -(1 << 20);  // where the input buffer starts
+    const VALUE: isize = BUFFER - 1;   // the character being read, which is turned into bits at VALUE - 6 and below
+
+    struct Emitter {
+        lines: Vec<String>,
+        line: String,
+        commands: usize,
+        pos: Option<(Frame, isize)>,
+        dir: isize,
+        tracks_pointer: bool,
+    }
+
+    impl Emitter {
+        /// Emits a command that the data pointer moves after.
+        fn cmd(&mut self, symbol: char) {
+            if symbol == 'F' {
+                self.dir = -self.dir;
+            }
+            self.line.push(symbol);
+            self.commands += 1;
+            if let Some((frame, pos)) = self.pos {
+                self.pos = Some((frame, pos + self.dir));
+            }
+        }
+
+        /// Emits a command that halts the data pointer.
+        fn halting(&mut self, symbol: char) {
+            self.line.push(symbol);
+            self.commands += 1;
+        }
+
+        fn literal(&mut self, n: isize) {
+            self.halting('#');
+            for digit in n.to_string().chars() {
+                self.halting(digit);
+            }
+        }
+
+        fn pos_in(&self, frame: Frame) -> isize {
+            match self.pos {
+                Some((f, pos)) if f == frame => pos,
+                pos => panic!("expected the data pointer to be tracked relative to {frame:?}, but it is at {pos:?}")
+            }
+        }
+
+        /// Steers the data pointer so that the next command runs on `target`, facing `dir` if provided.
+        fn move_to(&mut self, frame: Frame, target: isize, dir: Option<isize>) {
+            let d = self.dir;
+            let ahead = (target - self.pos_in(frame)) * d;
+            let same = if ahead >= 0 { ahead } else { 2 - ahead };
+            let flipped = if ahead >= 0 { ahead + 2 } else { -ahead };
+            let flip = match dir {
+                Some(dir) => dir != d,
+                None => flipped < same
+            };
+            match (flip, ahead >= 0) {
+                (false, true) => (0..ahead).for_each(|_| self.cmd('_')),
+                (false, false) => {
+                    self.cmd('F');
+                    (0..-ahead).for_each(|_| self.cmd('_'));
+                    self.cmd('F');
+                }
+                (true, true) => {
+                    (0..=ahead).for_each(|_| self.cmd('_'));
+                    self.cmd('F');
+                }
+                (true, false) => {
+                    self.cmd('F');
+                    (1..-ahead).for_each(|_| self.cmd('_'));
+                }
+            }
+        }
+
+        fn goto_absolute(&mut self, index: isize) {
+            self.literal(index.abs());
+            if index < 0 {
+                self.cmd('-');
+            }
+            self.halting('k');
+            self.pos = Some((Frame::Absolute, index));
+        }
+
+        /// Jumps to the cell whose index is stored in the provided cell, which is at `offset` in `frame`.
+        fn goto_stored(&mut self, cell: isize, frame: Frame, offset: isize) {
+            self.goto_absolute(cell);
+            self.cmd('r');
+            self.halting('k');
+            self.pos = Some((frame, offset));
+        }
+
+        /// Emits a conditional whose body is emitted by `body`. Whether or not the conditional jumps,
+        /// the data pointer ends up where the conditional left it, facing the same direction.
+        fn conditional(&mut self, symbol: char, body: impl FnOnce(&mut Emitter)) {
+            let (frame, start) = self.pos.expect("the data pointer must be tracked at a conditional");
+            let dir = self.dir;
+            self.cmd(symbol);
+            body(self);
+            self.move_to(frame, start, Some(dir));
+            self.cmd('@');
+        }
+
+        /// Makes the current cell 0 if it is empty, so that it can be used in arithmetic.
+        fn fill_cell(&mut self) {
+            self.conditional('!', |e| {
+                e.move_to(Frame::Cell, 0, None);
+                e.literal(0);
+                e.cmd('U');
+            });
+            self.move_to(Frame::Cell, 0, Some(1));
+        }
+
+        /// Adds `n` to the current cell, which must be full, wrapping around at 256.
+        fn add(&mut self, n: isize) {
+            self.move_to(Frame::Cell, -1, None);
+            self.literal(n);
+            self.cmd('U');
+            self.move_to(Frame::Cell, 0, None);
+            self.cmd('+');
+            self.move_to(Frame::Cell, 1, None);
+            self.cmd('U');
+            self.move_to(Frame::Cell, 2, None);
+            self.literal(256);
+            self.cmd('U');
+            self.move_to(Frame::Cell, 2, None);
+            self.cmd('%');
+            self.move_to(Frame::Cell, 0, None);
+            self.cmd('U');
+            self.move_to(Frame::Cell, 0, Some(1));
+        }
+
+        fn shift(&mut self, cells: isize) {
+            let offset = cells * 4;
+            if self.tracks_pointer {
+                self.goto_absolute(POINTER - 1);
+                self.literal(offset.abs());
+                if offset < 0 {
+                    self.cmd('-');
+                }
+                self.move_to(Frame::Absolute, POINTER - 1, None);
+                self.cmd('U');
+                self.move_to(Frame::Absolute, POINTER, None);
+                self.cmd('+');
+                self.move_to(Frame::Absolute, POINTER, None);
+                self.cmd('u');
+                self.halting('k');
+            } else if offset > 0 {
+                self.literal(offset);
+                self.halting('K');
+            } else {
+                // `-` moves the data pointer before `K` runs
+                self.literal(1 - offset);
+                self.cmd('-');
+                self.halting('K');
+            }
+            self.pos = Some((Frame::Cell, 0));
+            self.move_to(Frame::Cell, 0, Some(1));
+        }
+
+        fn output(&mut self) {
+            self.cmd('s');
+            self.move_to(Frame::Cell, 0, Some(1));
+        }
+
+        fn input(&mut self) {
+            // refill the buffer if every character in it has been read
+            self.goto_stored(CURSOR, Frame::Cursor, 0);
+            self.conditional('!', |e| {
+                e.move_to(Frame::Cursor, 0, None);
+                e.cmd('S');
+                e.move_to(Frame::Cursor, 0, None);
+                e.conditional('?', |e| {
+                    e.move_to(Frame::Cursor, 0, Some(1));
+                    e.halting('M');
+                    e.pos = None;
+                    e.literal(10);
+                    e.cmd('U');
+                    e.goto_stored(CURSOR, Frame::Cursor, 0);
+                });
+            });
+
+            // take the next character to VALUE, which is the first empty cell before the characters that have been read
+            self.move_to(Frame::Cursor, 0, None);
+            self.conditional('?', |e| {
+                e.move_to(Frame::Cursor, 0, None);
+                e.cmd('r');
+                e.move_to(Frame::Cursor, 0, Some(-1));
+                e.halting('M');
+                e.pos = Some((Frame::Absolute, VALUE));
+                e.cmd('U');
+                e.goto_absolute(CURSOR);
+                e.cmd('+');
+                e.move_to(Frame::Absolute, CURSOR, None);
+                e.cmd('U');
+                e.goto_stored(CURSOR, Frame::Cursor, 1);
+            });
+
+            // split the character into bits, reading an empty VALUE (the end of input) as 0
+            self.goto_absolute(VALUE);
+            self.conditional('!', |e| {
+                e.move_to(Frame::Absolute, VALUE, None);
+                e.literal(0);
+                e.cmd('U');
+            });
+            for bit in 0..8 {
+                self.move_to(Frame::Absolute, VALUE, None);
+                self.cmd('r');
+                self.move_to(Frame::Absolute, VALUE - 3, None);
+                self.cmd('U');
+                self.move_to(Frame::Absolute, VALUE - 2, None);
+                self.literal(1 << bit);
+                self.cmd('U');
+                self.move_to(Frame::Absolute, VALUE - 2, None);
+                self.cmd('/');
+                self.move_to(Frame::Absolute, VALUE - 5, None);
+                self.cmd('U');
+                self.move_to(Frame::Absolute, VALUE - 4, None);
+                self.literal(2);
+                self.cmd('U');
+                self.move_to(Frame::Absolute, VALUE - 4, None);
+                self.cmd('%');
+                self.move_to(Frame::Absolute, VALUE - 6 - bit, None);
+                self.cmd('U');
+            }
+            self.move_to(Frame::Absolute, VALUE, None);
+            self.cmd('c');
+
+            // rebuild the character in the current cell from its bits
+            self.goto_stored(POINTER, Frame::Cell, 0);
+            self.literal(0);
+            self.cmd('U');
+            for bit in 0..8 {
+                self.move_to(Frame::Cell, 0, Some(1));
+                self.literal(6 + bit - VALUE);
+                self.cmd('-');
+                self.conditional('T', |e| e.add(1 << bit));
+            }
+            self.move_to(Frame::Cell, 0, Some(1));
+        }
+
+        fn prologue(&mut self) {
+            self.goto_absolute(POINTER);
+            self.literal(0);
+            self.cmd('U');
+            self.goto_absolute(CURSOR - 1);
+            self.literal(1);
+            self.cmd('U');
+            self.goto_absolute(CURSOR);
+            self.literal(-BUFFER);
+            self.cmd('-');
+            self.move_to(Frame::Absolute, CURSOR, None);
+            self.cmd('U');
+            self.goto_absolute(0);
+            self.pos = Some((Frame::Cell, 0));
+            self.move_to(Frame::Cell, 0, Some(1));
+        }
+
+        fn end_line(&mut self, source: &str, depth: usize) {
+            if !self.line.is_empty() {
+                self.lines.push(format!("{}`{source}` {}", "    ".repeat(depth), self.line));
+                self.line.clear();
+            }
+        }
+    }
+
+    /// Translates a Brainfuck program into a gur yvsr program that prints the same output.
+    /// Cells wrap around at 256, and characters above 127 are printed as the Unicode character with that code point.
+    ///
+    /// If the brackets don't match, an [`Err`] containing a description of the problem is returned.
+    pub fn transpile(src: &str) -> Result<String, String> {
+        let code: Vec<char> = src.chars().filter(|c| "+-<>[].,".contains(*c)).collect();
+        let mut e = Emitter {
+            lines: vec![],
+            line: "".to_string(),
+            commands: 0,
+            pos: Some((Frame::Cell, 0)),
+            dir: 1,
+            tracks_pointer: code.contains(&','),
+        };
+        if e.tracks_pointer {
+            e.prologue();
+            e.end_line("setup", 0);
+        }
+
+        // the index of the `?` of each open loop
+        let mut loops: Vec<usize> = vec![];
+        // whether the current cell is known to be full
+        let mut full = false;
+        let mut i = 0;
+        while i < code.len() {
+            let start = i;
+            let depth = loops.len();
+            match code[i] {
+                '+' | '-' => {
+                    let mut n = 0;
+                    while i < code.len() && (code[i] == '+' || code[i] == '-') {
+                        n += if code[i] == '+' { 1 } else { 255 };
+                        i += 1;
+                    }
+                    if n % 256 != 0 {
+                        if !full {
+                            e.fill_cell();
+                        }
+                        e.add(n % 256);
+                        full = true;
+                    }
+                    i -= 1;
+                }
+                '<' | '>' => {
+                    let mut n = 0;
+                    while i < code.len() && (code[i] == '<' || code[i] == '>') {
+                        n += if code[i] == '>' { 1 } else { -1 };
+                        i += 1;
+                    }
+                    if n != 0 {
+                        e.shift(n);
+                        full = false;
+                    }
+                    i -= 1;
+                }
+                '.' => {
+                    if !full {
+                        e.fill_cell();
+                    }
+                    e.output();
+                    full = true;
+                }
+                ',' => {
+                    e.input();
+                    full = true;
+                }
+                '[' => {
+                    if e.commands == 0 {
+                        // the loop jumps back to the command before its `?`, so there has to be one
+                        e.cmd('F');
+                        e.cmd('F');
+                    }
+                    loops.push(e.commands);
+                    e.cmd('?');
+                    e.move_to(Frame::Cell, 0, Some(1));
+                    full = true;
+                }
+                ']' => {
+                    let Some(head) = loops.pop() else {
+                        return Err(format!("`]` at index {start} does not have a matching `[`"))
+                    };
+                    e.move_to(Frame::Cell, -1, Some(1));
+                    e.literal(head as isize - 1);
+                    e.cmd('j');
+                    e.cmd('@');
+                    // only the jump from the `?` gets past the `@`
+                    e.pos = Some((Frame::Cell, 1));
+                    e.move_to(Frame::Cell, 0, Some(1));
+                    full = false;
+                }
+                _ => unreachable!()
+            }
+            let source: String = code[start..=i].iter().collect();
+            // a `]` is written at the depth of its `[`
+            e.end_line(&source, depth.min(loops.len()));
+            i += 1;
+        }
+        if !loops.is_empty() {
+            return Err(format!("{} `[` do not have a matching `]`", loops.len()))
+        }
+        e.halting('.');
+        e.end_line("end", 0);
+        Ok(e.lines.join("\n") + "\n")
+    }
+}
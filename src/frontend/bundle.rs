@@ -0,0 +1,45 @@
+pub mod standalone {
+    use std::fs::File;
+    use std::io::{Read, Seek, SeekFrom};
+
+    /// The last bytes of a bundled executable.
+    const MAGIC: &[u8; 8] = b"GURBUNDL";
+    /// The length of the trailer: the length of the embedded program as a little-endian `u64`, then the magic.
+    const TRAILER_LEN: usize = 16;
+
+    /// Appends a program's bytecode to an executable, followed by a trailer that [`embedded_program`] looks for when the executable starts.
+    /// If the executable already has a program, it is replaced.
+    pub fn bundle(executable: &[u8], bytecode: &[u8]) -> Vec<u8> {
+        let embedded = executable.len().checked_sub(TRAILER_LEN)
+            .and_then(|start| embedded_len(&executable[start..]))
+            .map_or(0, |len| len.saturating_add(TRAILER_LEN))
+            .min(executable.len());
+        let mut bytes = executable[..executable.len() - embedded].to_vec();
+        bytes.extend(bytecode);
+        bytes.extend((bytecode.len() as u64).to_le_bytes());
+        bytes.extend(MAGIC);
+        bytes
+    }
+
+    /// Returns the bytecode embedded in the running executable by [`bundle`], or [`None`] if it doesn't have any.
+    /// This runs every time the executable starts, so only the trailer is read unless it describes a program.
+    pub fn embedded_program() -> Option<Vec<u8>> {
+        let mut file = File::open(std::env::current_exe().ok()?).ok()?;
+        let end = file.seek(SeekFrom::End(-(TRAILER_LEN as i64))).ok()? + TRAILER_LEN as u64;
+        let mut trailer = [0; TRAILER_LEN];
+        file.read_exact(&mut trailer).ok()?;
+        let len = embedded_len(&trailer)?;
+        file.seek(SeekFrom::Start(end.checked_sub((len as u64).checked_add(TRAILER_LEN as u64)?)?)).ok()?;
+        let mut bytecode = vec![0; len];
+        file.read_exact(&mut bytecode).ok()?;
+        Some(bytecode)
+    }
+
+    /// Returns the length of the embedded program a trailer describes, or [`None`] if it isn't a trailer.
+    fn embedded_len(trailer: &[u8]) -> Option<usize> {
+        if &trailer[8..] != MAGIC {
+            return None
+        }
+        usize::try_from(u64::from_le_bytes(trailer[..8].try_into().unwrap())).ok()
+    }
+}
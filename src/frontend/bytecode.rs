@@ -0,0 +1,138 @@
+pub mod gurc {
+    use crate::lexer::lex::*;
+
+    /// The first bytes of every bytecode file.
+    const MAGIC: &[u8; 4] = b"GURC";
+    /// The version of the format written by [`encode`]. Files with any other version are rejected.
+    pub const VERSION: u16 = 1;
+    /// Set in the flags when the file has a span table.
+    const HAS_SPANS: u16 = 1;
+    /// The length of the header: the magic, the version, the flags and the number of commands.
+    const HEADER_LEN: usize = 12;
+
+    /// A program loaded from bytecode.
+    pub struct Bytecode {
+        pub tokens: Vec<Token>,
+        /// The matching `@` of each conditional, as returned by [`match_conditionals`].
+        pub targets: Vec<Option<usize>>,
+        /// The byte offset of each command in the source it was built from, if the file has a span table.
+        /// A command that comes from a macro or an include has the offset of the call or the include.
+        pub spans: Option<Vec<usize>>,
+    }
+
+    /// Encodes a program as bytecode. The layout, with every integer in little-endian order, is:
+    ///
+    /// - the magic `GURC`, a `u16` version and `u16` flags, of which only bit 0 (the span table is present) is used;
+    /// - the number of commands as a `u32`, followed by the symbol of each command as a byte;
+    /// - for each conditional in order, the distance to its matching `@`, or 0 if it has none, as an unsigned LEB128 integer;
+    /// - if there is a span table, the distance in bytes from the start of each command in the source to the start of the next, as an unsigned LEB128 integer,
+    ///   starting from the start of the source;
+    /// - the CRC-32 of everything before it as a `u32`.
+    pub fn encode(tokens: &[Token], spans: Option<&[usize]>) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(VERSION.to_le_bytes());
+        bytes.extend((if spans.is_some() { HAS_SPANS } else { 0 }).to_le_bytes());
+        bytes.extend((tokens.len() as u32).to_le_bytes());
+        bytes.extend(tokens.iter().map(|token| token_to_symbol(token).as_bytes()[0]));
+        for (index, target) in match_conditionals(tokens).into_iter().enumerate() {
+            if tokens[index].is_conditional() {
+                write_varint(&mut bytes, target.map_or(0, |target| target - index));
+            }
+        }
+        if let Some(spans) = spans {
+            let mut previous = 0;
+            for &start in spans {
+                write_varint(&mut bytes, start - previous);
+                previous = start;
+            }
+        }
+        bytes.extend(crc32(&bytes).to_le_bytes());
+        bytes
+    }
+
+    /// Decodes bytecode written by [`encode`], checking that it is intact and describes a program that [`tokenize`] could have produced.
+    ///
+    /// If it doesn't, an [`Err`] describing the problem is returned.
+    pub fn decode(bytes: &[u8]) -> Result<Bytecode, String> {
+        if bytes.len() < HEADER_LEN + 4 || &bytes[..4] != MAGIC {
+            return Err("missing bytecode header".to_string())
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != VERSION {
+            return Err(format!("unsupported bytecode version {version} (expected {VERSION})"))
+        }
+        let (body, checksum) = bytes.split_at(bytes.len() - 4);
+        if crc32(body) != u32::from_le_bytes(checksum.try_into().unwrap()) {
+            return Err("checksum mismatch; the file is corrupted".to_string())
+        }
+        let flags = u16::from_le_bytes([bytes[6], bytes[7]]);
+        if flags & !HAS_SPANS != 0 {
+            return Err(format!("unknown flags {flags:#06x}"))
+        }
+        let len = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+        let symbols = body.get(HEADER_LEN..HEADER_LEN + len).ok_or("the file is truncated")?;
+        let tokens = symbols.iter().enumerate().map(|(index, &byte)| {
+            symbol_to_token(byte as char).ok_or(format!("unrecognized symbol byte {byte:#04x} at index {index}"))
+        }).collect::<Result<Vec<Token>, String>>()?;
+
+        let mut rest = &body[HEADER_LEN + len..];
+        let targets = match_conditionals(&tokens);
+        for (index, token) in tokens.iter().enumerate() {
+            if token.is_conditional() && read_varint(&mut rest)? != targets[index].map_or(0, |target| target - index) {
+                return Err(format!("the conditional at index {index} does not point at its matching `@`"))
+            }
+        }
+        let spans = if flags & HAS_SPANS != 0 {
+            let mut spans = Vec::with_capacity(len);
+            let mut start = 0usize;
+            for _ in 0..len {
+                start = start.checked_add(read_varint(&mut rest)?).ok_or("span table overflows")?;
+                spans.push(start);
+            }
+            Some(spans)
+        } else {
+            None
+        };
+        if !rest.is_empty() {
+            return Err("unexpected bytes after the program".to_string())
+        }
+        Ok(Bytecode { tokens, targets, spans })
+    }
+
+    fn write_varint(bytes: &mut Vec<u8>, mut value: usize) {
+        while value >= 0x80 {
+            bytes.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        bytes.push(value as u8);
+    }
+
+    fn read_varint(bytes: &mut &[u8]) -> Result<usize, String> {
+        let mut value = 0usize;
+        let mut shift = 0;
+        loop {
+            let (&byte, rest) = bytes.split_first().ok_or("the file is truncated")?;
+            *bytes = rest;
+            if shift >= usize::BITS || (byte & 0x7f) as usize > usize::MAX >> shift {
+                return Err("integer too large".to_string())
+            }
+            value |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value)
+            }
+            shift += 7;
+        }
+    }
+
+    /// The CRC-32 used by zlib and PNG.
+    fn crc32(bytes: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for &byte in bytes {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            }
+        }
+        !crc
+    }
+}
@@ -0,0 +1,120 @@
+pub mod check {
+    use std::ops::Range;
+    use crate::analyzer::deep::*;
+    use crate::errors::err::Error;
+    use crate::lexer::lex::*;
+
+    /// A problem found in a program without running it.
+    pub struct Diagnostic {
+        /// The error the problem causes if the program runs into it, or [`None`] if the problem is only worth a warning.
+        pub error: Option<Error>,
+        /// The byte range of the offending symbol in the source.
+        pub span: Range<usize>,
+        pub message: String,
+    }
+
+    /// Looks for the problems that can be found without running the program:
+    /// unrecognized symbols, conditionals without a matching `@`, `@`s without a matching conditional,
+    /// digits that don't belong to a number literal, and `#`s in the middle of a number literal.
+    /// The diagnostics are sorted by their position in the source.
+    pub fn check_source(src: &str) -> Vec<Diagnostic> {
+        let mut diagnostics: Vec<Diagnostic> = vec![];
+        let mut commands: Vec<(Token, Range<usize>)> = vec![];
+        for lexeme in scan_all(src) {
+            match lexeme.kind {
+                LexemeKind::Command(token) => commands.push((token, lexeme.span)),
+                LexemeKind::Comment | LexemeKind::Directive => {}
+                LexemeKind::Unknown(c) => diagnostics.push(Diagnostic {
+                    error: Some(Error::UnknownSymbolError),
+                    span: lexeme.span,
+                    message: format!("unrecognized symbol {c} found at index {}", commands.len()),
+                }),
+            }
+        }
+
+        let tokens: Vec<Token> = commands.iter().map(|(token, _)| *token).collect();
+        let matches = match_conditionals(&tokens);
+        for (index, (token, span)) in commands.into_iter().enumerate() {
+            let symbol = token_to_symbol(&token);
+            let in_literal = index > 0 && matches!(tokens[index - 1], Token::CreatingNumber | Token::Digit(_));
+            let (error, message) = match token {
+                _ if token.is_conditional() && matches[index].is_none() => {
+                    (Some(Error::SyntaxError), format!("conditional `{symbol}` at index {index} does not have a corresponding `@`"))
+                }
+                Token::DestinationIfTrue if matches[index].is_none() => {
+                    (None, format!("`@` at index {index} does not have a corresponding conditional, so it does nothing"))
+                }
+                Token::Digit(_) if !in_literal => {
+                    (Some(Error::SyntaxError), format!("digit `{symbol}` at index {index} is not part of a number literal; number literals start with `#`"))
+                }
+                Token::CreatingNumber if in_literal => {
+                    (Some(Error::SyntaxError), format!("`#` at index {index} comes right after a number literal, which is still being created"))
+                }
+                _ => continue
+            };
+            diagnostics.push(Diagnostic { error, span, message });
+        }
+        diagnostics.sort_by_key(|d| d.span.start);
+        diagnostics
+    }
+
+    /// Looks for the `OpError`s and `AccumulatorError`s the program is certain or likely to throw, as found by [`predict_errors`].
+    /// They are only warnings, since the commands might never run. The diagnostics are sorted by their position in the source.
+    /// Nothing is found in a source with unrecognized symbols, which [`check_source`] already reports.
+    pub fn check_deep(src: &str) -> Vec<Diagnostic> {
+        let Ok(tokens) = try_tokenize(src) else {
+            return vec![]
+        };
+        let spans: Vec<Range<usize>> = scan_all(src).into_iter()
+            .filter(|lexeme| matches!(lexeme.kind, LexemeKind::Command(_)))
+            .map(|lexeme| lexeme.span)
+            .collect();
+        predict_errors(&tokens).into_iter()
+            .map(|finding| Diagnostic { error: None, span: spans[finding.index].clone(), message: finding.message })
+            .collect()
+    }
+
+    /// Looks for `j`s and `J`s right after a number literal written by hand whose command to continue at isn't right after a label,
+    /// which is what happens when a command is added before its target and the literal isn't updated. `labels` are the indices of the commands
+    /// right after the labels, and `references` the commands of the literals that `{to name}` and `{by name}` expanded into, which are always right.
+    /// Programs without labels count every jump by hand, so nothing is reported for them. The diagnostics are warnings, sorted by their position in the source.
+    pub fn check_jumps(src: &str, labels: &[usize], references: &[Range<usize>]) -> Vec<Diagnostic> {
+        if labels.is_empty() {
+            return vec![]
+        }
+        let (tokens, spans): (Vec<Token>, Vec<Range<usize>>) = scan_all(src).into_iter()
+            .filter_map(|lexeme| match lexeme.kind {
+                LexemeKind::Command(token) => Some((token, lexeme.span)),
+                _ => None
+            })
+            .unzip();
+        let mut diagnostics = vec![];
+        for (index, token) in tokens.iter().enumerate() {
+            let (Token::JumpCellsC | Token::JumpToCellC) = token else { continue };
+            let Some(value) = static_jump(&tokens, index) else { continue };
+            if references.iter().any(|reference| reference.contains(&(index - 1))) {
+                continue
+            }
+            let (target, reference) = match token {
+                Token::JumpCellsC => (index.checked_add_signed(value), "{by name}"),
+                _ => (usize::try_from(value).ok(), "{to name}")
+            };
+            let Some(next) = target.map(|target| target + 1) else { continue };
+            if !labels.contains(&next) {
+                let symbol = token_to_symbol(token);
+                diagnostics.push(Diagnostic {
+                    error: None,
+                    span: spans[index].clone(),
+                    message: format!("`{symbol}` at index {index} continues at index {next}, which is not right after a label; write its literal as `{reference}` to jump to a label"),
+                });
+            }
+        }
+        diagnostics
+    }
+
+    /// Returns the line and column (both starting at 1) of the character at the provided byte offset.
+    pub fn line_column(src: &str, offset: usize) -> (usize, usize) {
+        let line_start = src[..offset].rfind('\n').map_or(0, |i| i + 1);
+        (src[..line_start].matches('\n').count() + 1, src[line_start..offset].chars().count() + 1)
+    }
+}
@@ -0,0 +1,1067 @@
+pub mod debug {
+    use std::cell::RefCell;
+    use std::collections::{BTreeSet, VecDeque};
+    use std::fmt;
+    use std::io::{self, Write};
+    use std::ops::RangeInclusive;
+    use std::process::exit;
+    use std::rc::Rc;
+    use crate::errors::err::*;
+    use crate::exec::exec::{print_details, Halt};
+    use crate::exec::prelude::prelude::*;
+    use crate::lexer::lex::*;
+
+    /// How many steps apart checkpoints are taken at first. The interval doubles whenever checkpoints are thinned out.
+    const CHECKPOINT_INTERVAL: usize = 1024;
+    /// The most checkpoints kept at once. When there are more, every other one is dropped.
+    const MAX_CHECKPOINTS: usize = 64;
+    /// The most changes kept in the undo log. When there are more, the oldest steps are dropped,
+    /// and stepping back past them restores a checkpoint and runs forward from it instead.
+    const MAX_LOGGED_CHANGES: usize = 1 << 20;
+
+    /// A change made by a step, holding what it replaced so that it can be undone.
+    #[derive(Clone, Copy)]
+    enum Change {
+        Cell(isize, Option<isize>),
+        Acc(Option<isize>),
+        DataPtr(isize),
+        FlipDir,
+        CodePtr(usize),
+        CreatingNumber(bool),
+        LastExecuted(Token),
+        LinesRead(usize),
+    }
+
+    /// Everything a step can change.
+    #[derive(Clone)]
+    pub struct State {
+        pub tape: Tape,
+        pub acc: Acc,
+        pub data_ptr_index: isize,
+        pub data_ptr_dir: isize,
+        pub code_ptr_index: usize,
+        pub creating_number: bool,
+        pub last_executed: Token,
+        /// How many lines of input have been read.
+        pub lines_read: usize,
+    }
+
+    /// An error thrown by a step.
+    #[derive(Clone, Debug)]
+    pub struct Fault {
+        pub error: Error,
+        pub msg: String,
+        /// The code pointer's index when the error was thrown, which a jump may have moved away from the command that threw it.
+        pub code_ptr_index: usize,
+        pub command: Token,
+        /// Whether the interpreter prints the details along with this error. Only invalid input doesn't.
+        pub details: bool,
+    }
+
+    /// Why a [`Machine`] can't step any further.
+    #[derive(Clone, Debug)]
+    pub enum Outcome {
+        /// `.` was run, and the process would exit with this status.
+        Stopped(i32),
+        Failed(Fault),
+    }
+
+    /// What to report when it is touched.
+    #[derive(Clone, Default, Debug)]
+    pub struct Watch {
+        pub cells: Vec<RangeInclusive<isize>>,
+        /// Whether to report the accumulator becoming empty or full. Changes that keep it full aren't reported.
+        pub acc: bool,
+    }
+
+    impl Watch {
+        pub fn is_empty(&self) -> bool {
+            self.cells.is_empty() && !self.acc
+        }
+
+        fn watches_cell(&self, index: isize) -> bool {
+            self.cells.iter().any(|cells| cells.contains(&index))
+        }
+
+        /// Returns the index of the first watched cell found when moving from the provided index (inclusive) by `dir`, or [`None`] if there is none.
+        fn next_cell(&self, index: isize, dir: isize) -> Option<isize> {
+            let ahead = self.cells.iter().filter(|cells| !cells.is_empty());
+            if dir > 0 {
+                ahead.filter(|cells| *cells.end() >= index).map(|cells| index.max(*cells.start())).min()
+            } else {
+                ahead.filter(|cells| *cells.start() <= index).map(|cells| index.min(*cells.end())).max()
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Access {
+        Read,
+        Write,
+        Clear,
+    }
+
+    #[derive(Clone, Copy, Debug)]
+    pub enum Watched {
+        /// For a read, `old` and `new` are both the value read.
+        Cell { index: isize, access: Access, old: Option<isize>, new: Option<isize> },
+        Acc { old: Option<isize>, new: Option<isize> },
+    }
+
+    /// A watched cell or the accumulator being touched by a command.
+    #[derive(Clone, Copy, Debug)]
+    pub struct WatchHit {
+        pub code_ptr_index: usize,
+        pub command: Token,
+        pub watched: Watched,
+    }
+
+    impl fmt::Display for WatchHit {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let value = |value: Option<isize>| value.map_or("<none>".to_string(), |value| value.to_string());
+            write!(f, "command {} `{}` ", self.code_ptr_index, token_to_symbol(&self.command))?;
+            match self.watched {
+                Watched::Cell { index, access: Access::Read, old, .. } => write!(f, "read cell {index}: {}", value(old)),
+                Watched::Cell { index, access, old, new } => {
+                    write!(f, "{} cell {index}: {} -> {}", if access == Access::Write { "wrote" } else { "cleared" }, value(old), value(new))
+                }
+                Watched::Acc { old, new } => {
+                    write!(f, "{} the accumulator: {} -> {}", if new.is_some() { "filled" } else { "emptied" }, value(old), value(new))
+                }
+            }
+        }
+    }
+
+    /// Reads a line of input into a string, like [`Stdin::read_line`](io::Stdin::read_line).
+    type ReadLine = Box<dyn FnMut(&mut String) -> io::Result<usize>>;
+
+    /// A command that isn't built in, run by a [`Machine`] at the indices its symbol was found at, as given to [`set_extensions`](Machine::set_extensions).
+    ///
+    /// Stepping back undoes what the command did to the state, but not to the extension itself, and the steps re-run after
+    /// stepping back past the undo log run the command again. Extensions that must replay exactly should only depend on the state.
+    pub trait CommandExtension {
+        /// The symbol of the command, which must not be the symbol of a built-in command.
+        fn symbol(&self) -> char;
+
+        /// Whether the data pointer stays where the command leaves it, like it does after `#`, `K` or `I`, instead of moving on.
+        fn halts_data_pointer(&self) -> bool {
+            false
+        }
+
+        /// Runs the command. An [`Err`] is thrown like an error thrown by a built-in command, keeping the changes made before it.
+        fn execute(&mut self, state: &mut StateView) -> Result<(), (Error, String)>;
+    }
+
+    /// The extensions a [`Machine`] runs, which can be shared by several machines.
+    pub type Extensions = Rc<RefCell<Vec<Box<dyn CommandExtension>>>>;
+
+    /// What a [`CommandExtension`] can read and change while it runs. Changes are recorded like those of built-in commands,
+    /// so they can be stepped back over and are reported to watchpoints.
+    #[allow(dead_code)] // only the library's interpreter registers extensions
+    pub struct StateView<'a> {
+        machine: &'a mut Machine,
+    }
+
+    #[allow(dead_code)]
+    impl StateView<'_> {
+        pub fn cell(&mut self, index: isize) -> Option<isize> {
+            self.machine.read_cell(index)
+        }
+
+        /// Sets the value of a cell, or clears it if `value` is [`None`].
+        pub fn set_cell(&mut self, index: isize, value: Option<isize>) {
+            self.machine.set_cell(index, value)
+        }
+
+        pub fn acc(&self) -> Option<isize> {
+            self.machine.state.acc.get_value().ok()
+        }
+
+        /// Sets the accumulator's value, or empties it if `value` is [`None`].
+        pub fn set_acc(&mut self, value: Option<isize>) {
+            self.machine.set_acc(value)
+        }
+
+        pub fn data_ptr(&self) -> isize {
+            self.machine.state.data_ptr_index
+        }
+
+        /// Moves the data pointer. Unless the command halts the data pointer, it then moves on from there.
+        pub fn set_data_ptr(&mut self, index: isize) {
+            self.machine.set_data_ptr(index)
+        }
+
+        /// Returns the data pointer's direction, which is 1 or -1.
+        pub fn dir(&self) -> isize {
+            self.machine.state.data_ptr_dir
+        }
+
+        pub fn flip_dir(&mut self) {
+            self.machine.record(Change::FlipDir);
+            self.machine.state.data_ptr_dir *= -1;
+        }
+
+        /// Returns the index of the command being run.
+        pub fn code_ptr(&self) -> usize {
+            self.machine.state.code_ptr_index
+        }
+
+        pub fn write(&mut self, output: &str) {
+            self.machine.write(output)
+        }
+    }
+
+    /// Runs a program one command at a time, keeping enough history to step backwards.
+    ///
+    /// Every change a step makes is recorded in an undo log, and a full copy of the state is checkpointed every so often.
+    /// The log is bounded, so stepping back past its start restores the closest earlier checkpoint and runs forward from it,
+    /// reading the same input as the first time. Re-running a step never writes its output again.
+    ///
+    /// Without history, it is the interpreter's engine: see [`discard_history`](Machine::discard_history).
+    pub struct Machine {
+        tokens: Vec<Token>,
+        targets: Vec<Option<usize>>,
+        exit_with_acc: bool,
+        state: State,
+        steps: usize,
+        /// The most steps ever taken. Steps before this have already written their output.
+        furthest: usize,
+        outcome: Option<Outcome>,
+        /// The changes made by each of the latest steps, oldest first.
+        log: VecDeque<Vec<Change>>,
+        logged_changes: usize,
+        /// Copies of the state, with the number of steps taken when each was made. The first is always the initial state.
+        checkpoints: Vec<(usize, State)>,
+        checkpoint_interval: usize,
+        /// Whether the log, checkpoints and transcript are kept, so that the machine can step back.
+        history: bool,
+        /// Every line read so far, so that a re-run step reads the same line.
+        transcript: Vec<String>,
+        output: String,
+        watch: Watch,
+        /// The watched things touched since [`take_hits`](Machine::take_hits) was last called.
+        hits: Vec<WatchHit>,
+        /// The index of the command being run, for reporting hits.
+        command_index: usize,
+        input: ReadLine,
+        extensions: Extensions,
+        /// The symbol of the extension run at each index, for the commands that aren't built in. The tokens hold [`Token::Nothing`] there.
+        extension_symbols: Vec<Option<char>>,
+    }
+
+    impl Machine {
+        /// `targets` holds the matching `@` of each conditional, as returned by [`match_conditionals`].
+        pub fn new(tokens: Vec<Token>, targets: Vec<Option<usize>>, exit_with_acc: bool) -> Machine {
+            let state = State {
+                tape: Tape::new(),
+                acc: Acc::new(),
+                data_ptr_index: 0,
+                data_ptr_dir: 1,
+                code_ptr_index: 0,
+                creating_number: false,
+                last_executed: Token::Nothing,
+                lines_read: 0,
+            };
+            Machine {
+                tokens,
+                targets,
+                exit_with_acc,
+                checkpoints: vec![(0, state.clone())],
+                state,
+                steps: 0,
+                furthest: 0,
+                outcome: None,
+                log: VecDeque::new(),
+                logged_changes: 0,
+                checkpoint_interval: CHECKPOINT_INTERVAL,
+                history: true,
+                transcript: vec![],
+                output: "".to_string(),
+                watch: Watch::default(),
+                hits: vec![],
+                command_index: 0,
+                input: Box::new(|line| io::stdin().read_line(line)),
+                extensions: Extensions::default(),
+                extension_symbols: vec![],
+            }
+        }
+
+        /// Sets the extensions that run the commands that aren't built in, and the symbol of the one to run at each index.
+        /// The tokens must hold [`Token::Nothing`] at those indices. Without an extension for a symbol, running it throws an
+        /// [`UnknownSymbolError`](Error::UnknownSymbolError).
+        #[allow(dead_code)]
+        pub fn set_extensions(&mut self, extensions: Extensions, symbols: Vec<Option<char>>) {
+            self.extensions = extensions;
+            self.extension_symbols = symbols;
+        }
+
+        /// Stops keeping the history needed to step back, so that steps are faster and memory doesn't grow with the number of steps taken.
+        /// Stepping back then always returns false.
+        pub fn discard_history(&mut self) {
+            self.history = false;
+            self.log.clear();
+            self.logged_changes = 0;
+            self.checkpoints.clear();
+            self.transcript.clear();
+        }
+
+        /// Replaces how lines of input are read, which is [`Stdin::read_line`](io::Stdin::read_line) by default.
+        pub fn set_input(&mut self, read_line: impl FnMut(&mut String) -> io::Result<usize> + 'static) {
+            self.input = Box::new(read_line);
+        }
+
+        pub fn watch(&self) -> &Watch {
+            &self.watch
+        }
+
+        pub fn watch_mut(&mut self) -> &mut Watch {
+            &mut self.watch
+        }
+
+        /// Returns the watched things touched since the last call, in the order they were touched.
+        ///
+        /// Stepping back reports the writes, clears and accumulator changes it undoes, as they were made, but not reads.
+        pub fn take_hits(&mut self) -> Vec<WatchHit> {
+            std::mem::take(&mut self.hits)
+        }
+
+        pub fn state(&self) -> &State {
+            &self.state
+        }
+
+        pub fn tokens(&self) -> &[Token] {
+            &self.tokens
+        }
+
+        /// Returns how many steps have been taken, not counting the ones stepped back over.
+        pub fn steps(&self) -> usize {
+            self.steps
+        }
+
+        /// Returns why the program can't step any further, or [`None`] if it can.
+        pub fn outcome(&self) -> Option<&Outcome> {
+            self.outcome.as_ref()
+        }
+
+        /// Returns the output written since the last call.
+        pub fn take_output(&mut self) -> String {
+            std::mem::take(&mut self.output)
+        }
+
+        /// Runs the command at the code pointer. If the program stops or throws an error, the returned [`Outcome`] says which,
+        /// and the machine stays where it is until it steps back.
+        ///
+        /// A step that throws an error keeps the changes it made before throwing, like the interpreter does, so that they can be inspected.
+        pub fn step(&mut self) -> Option<&Outcome> {
+            if self.outcome.is_some() {
+                return self.outcome.as_ref()
+            }
+            if self.tokens.is_empty() {
+                self.outcome = Some(Outcome::Stopped(0));
+                return self.outcome.as_ref()
+            }
+            if self.history {
+                self.log.push_back(vec![]);
+            }
+            let result = self.execute();
+            self.steps += 1;
+            self.furthest = self.furthest.max(self.steps);
+            self.outcome = match result {
+                Ok(None) => None,
+                Ok(Some(status)) => Some(Outcome::Stopped(status)),
+                Err(fault) => Some(Outcome::Failed(fault)),
+            };
+            if !self.history {
+                return self.outcome.as_ref()
+            }
+            if self.outcome.is_none() && self.steps.is_multiple_of(self.checkpoint_interval) && self.checkpoints.last().is_some_and(|(steps, _)| *steps < self.steps) {
+                self.checkpoints.push((self.steps, self.state.clone()));
+                if self.checkpoints.len() > MAX_CHECKPOINTS {
+                    let mut index = 0;
+                    self.checkpoints.retain(|_| { index += 1; index % 2 == 1 });
+                    self.checkpoint_interval *= 2;
+                }
+            }
+            while self.logged_changes > MAX_LOGGED_CHANGES && self.log.len() > 1 {
+                self.logged_changes -= self.log.pop_front().unwrap().len();
+            }
+            self.outcome.as_ref()
+        }
+
+        /// Undoes the last step. Returns false if no steps have been taken, or if the history was discarded.
+        pub fn step_back(&mut self) -> bool {
+            if self.steps == 0 || !self.history {
+                return false
+            }
+            if self.log.is_empty() {
+                self.rewind(self.steps);
+            }
+            let changes = self.log.pop_back().unwrap();
+            self.logged_changes -= changes.len();
+            let mut hits = vec![];
+            for change in changes.into_iter().rev() {
+                match change {
+                    Change::Cell(index, old) => {
+                        let new = self.state.tape.get(index);
+                        if self.watch.watches_cell(index) {
+                            let access = if new.is_some() { Access::Write } else { Access::Clear };
+                            hits.push(Watched::Cell { index, access, old, new });
+                        }
+                        match old {
+                            Some(value) => self.state.tape.set(index, value),
+                            None => { self.state.tape.clear(index); }
+                        }
+                    }
+                    Change::Acc(old) => {
+                        let new = self.state.acc.get_value().ok();
+                        if self.watch.acc && old.is_some() != new.is_some() {
+                            hits.push(Watched::Acc { old, new });
+                        }
+                        match old {
+                            Some(value) => self.state.acc.set_value(value),
+                            None => { self.state.acc.clear(); }
+                        }
+                    }
+                    Change::DataPtr(index) => self.state.data_ptr_index = index,
+                    Change::FlipDir => self.state.data_ptr_dir *= -1,
+                    Change::CodePtr(index) => self.state.code_ptr_index = index,
+                    Change::CreatingNumber(creating_number) => self.state.creating_number = creating_number,
+                    Change::LastExecuted(token) => self.state.last_executed = token,
+                    Change::LinesRead(lines_read) => self.state.lines_read = lines_read,
+                }
+            }
+            let code_ptr_index = self.state.code_ptr_index;
+            let command = self.tokens[code_ptr_index];
+            self.hits.extend(hits.into_iter().rev().map(|watched| WatchHit { code_ptr_index, command, watched }));
+            self.steps -= 1;
+            self.outcome = None;
+            true
+        }
+
+        /// Goes back to when `steps` steps had been taken by restoring the closest checkpoint before it and running forward,
+        /// which also logs the last step so that it can be undone.
+        fn rewind(&mut self, steps: usize) {
+            let (checkpoint_steps, state) = self.checkpoints.iter().rev().find(|(checkpoint_steps, _)| *checkpoint_steps < steps).unwrap();
+            self.steps = *checkpoint_steps;
+            self.state = state.clone();
+            self.outcome = None;
+            self.log.clear();
+            self.logged_changes = 0;
+            while self.steps < steps {
+                self.step();
+            }
+            self.hits.clear();
+        }
+
+        fn record(&mut self, change: Change) {
+            if !self.history {
+                return
+            }
+            self.log.back_mut().unwrap().push(change);
+            self.logged_changes += 1;
+        }
+
+        fn hit(&mut self, watched: Watched) {
+            let code_ptr_index = self.command_index;
+            self.hits.push(WatchHit { code_ptr_index, command: self.tokens[code_ptr_index], watched });
+        }
+
+        fn read_cell(&mut self, index: isize) -> Option<isize> {
+            let value = self.state.tape.get(index);
+            if self.watch.watches_cell(index) {
+                self.hit(Watched::Cell { index, access: Access::Read, old: value, new: value });
+            }
+            value
+        }
+
+        fn set_cell(&mut self, index: isize, value: Option<isize>) {
+            let old = self.state.tape.get(index);
+            if self.watch.watches_cell(index) {
+                let access = if value.is_some() { Access::Write } else { Access::Clear };
+                self.hit(Watched::Cell { index, access, old, new: value });
+            }
+            self.record(Change::Cell(index, old));
+            match value {
+                Some(value) => self.state.tape.set(index, value),
+                None => { self.state.tape.clear(index); }
+            }
+        }
+
+        fn set_acc(&mut self, value: Option<isize>) {
+            let old = self.state.acc.get_value().ok();
+            if self.watch.acc && old.is_some() != value.is_some() {
+                self.hit(Watched::Acc { old, new: value });
+            }
+            self.record(Change::Acc(old));
+            match value {
+                Some(value) => self.state.acc.set_value(value),
+                None => { self.state.acc.clear(); }
+            }
+        }
+
+        fn set_data_ptr(&mut self, index: isize) {
+            self.record(Change::DataPtr(self.state.data_ptr_index));
+            self.state.data_ptr_index = index;
+        }
+
+        fn set_code_ptr(&mut self, index: usize) {
+            self.record(Change::CodePtr(self.state.code_ptr_index));
+            self.state.code_ptr_index = index;
+        }
+
+        fn set_creating_number(&mut self, creating_number: bool) {
+            if self.state.creating_number != creating_number {
+                self.record(Change::CreatingNumber(self.state.creating_number));
+                self.state.creating_number = creating_number;
+            }
+        }
+
+        /// Reads a line of input, or the line read the first time if the step reading it is being re-run.
+        fn read_line(&mut self, command: Token) -> Result<String, Fault> {
+            let lines_read = self.state.lines_read;
+            let mut input = match self.transcript.get(lines_read) {
+                Some(input) => input.clone(),
+                None => {
+                    let mut input = "".to_string();
+                    if (self.input)(&mut input).is_err() {
+                        return Err(Fault { details: false, ..self.fault(command, Error::InputError, "invalid input") })
+                    }
+                    if self.history {
+                        self.transcript.push(input.clone());
+                    }
+                    input
+                }
+            };
+            self.record(Change::LinesRead(lines_read));
+            self.state.lines_read += 1;
+            input.pop();
+            Ok(input)
+        }
+
+        fn write(&mut self, output: &str) {
+            // the step being run has already written its output if it was taken before
+            if self.steps >= self.furthest {
+                self.output.push_str(output);
+            }
+        }
+
+        fn fault(&self, command: Token, error: Error, msg: &str) -> Fault {
+            Fault { error, msg: msg.to_string(), code_ptr_index: self.state.code_ptr_index, command, details: true }
+        }
+
+        /// Returns the values of the cells to the left of and at the data pointer, or [`None`] if either is empty or doesn't exist.
+        fn operands(&mut self) -> Option<(isize, isize)> {
+            let index = self.state.data_ptr_index;
+            let left = index.checked_sub(1).and_then(|left| self.read_cell(left));
+            let current = self.read_cell(index);
+            Some((left?, current?))
+        }
+
+        /// Runs the command at the code pointer, returning the exit status if it is `.`.
+        fn execute(&mut self) -> Result<Option<i32>, Fault> {
+            self.command_index = self.state.code_ptr_index;
+            let current = self.tokens[self.state.code_ptr_index];
+            let went_wrong = || format!("execution of `{}` went wrong", token_to_symbol(&current));
+            let fail = |m: &Machine, error: Error, msg: &str| Err(m.fault(current, error, msg));
+            let mut moving = true;
+            if !matches!(current, Token::CreatingNumber | Token::Digit(_)) {
+                self.set_creating_number(false);
+            }
+            let index = self.state.data_ptr_index;
+            let acc = self.state.acc.get_value().ok();
+
+            match current {
+                Token::NoOp | Token::DestinationIfTrue => {}
+                Token::Nothing => if let Some(&Some(symbol)) = self.extension_symbols.get(self.state.code_ptr_index) {
+                    let extensions = Rc::clone(&self.extensions);
+                    let mut extensions = extensions.borrow_mut();
+                    let Some(extension) = extensions.iter_mut().find(|extension| extension.symbol() == symbol) else {
+                        return fail(self, Error::UnknownSymbolError, &format!("no extension runs `{symbol}`"))
+                    };
+                    if let Err((error, msg)) = extension.execute(&mut StateView { machine: self }) {
+                        return fail(self, error, &msg)
+                    }
+                    moving = !extension.halts_data_pointer();
+                }
+                Token::Stop => {
+                    let mut status = 0;
+                    if self.exit_with_acc && let Some(value) = acc {
+                        if !(0..=255).contains(&value) {
+                            return fail(self, Error::OverflowError, &format!("exit status {value} is not between 0 and 255"))
+                        }
+                        status = value as i32;
+                    }
+                    return Ok(Some(status))
+                }
+                Token::CreatingNumber => {
+                    if self.state.creating_number {
+                        return fail(self, Error::SyntaxError, "already creating number")
+                    }
+                    if acc.is_some() {
+                        return fail(self, Error::AccumulatorError, &went_wrong())
+                    }
+                    self.set_creating_number(true);
+                    moving = false;
+                }
+                Token::Digit(n) => {
+                    if !self.state.creating_number {
+                        return fail(self, Error::SyntaxError, &went_wrong())
+                    }
+                    let value = match acc {
+                        Some(value) => value.checked_mul(10).and_then(|value| value.checked_add(n)),
+                        None => Some(n)
+                    };
+                    let Some(value) = value else { return fail(self, Error::OverflowError, &format!("digit {n} caused overflow")) };
+                    self.set_acc(Some(value));
+                    moving = false;
+                }
+                Token::Unload | Token::Distribute => {
+                    let Some(value) = acc else { return fail(self, Error::AccumulatorError, &went_wrong()) };
+                    self.set_cell(index, Some(value));
+                    if current == Token::Unload {
+                        self.set_acc(None);
+                    }
+                }
+                Token::Recall | Token::Copy => {
+                    let Some(value) = self.read_cell(index) else { return fail(self, Error::OpError, &went_wrong()) };
+                    self.set_acc(Some(value));
+                    if current == Token::Recall {
+                        self.set_cell(index, None);
+                    }
+                }
+                Token::ClearAcc => self.set_acc(None),
+                Token::ClearCurrCell => self.set_cell(index, None),
+                Token::ZeroOrEmpty | Token::NotZeroOrEmpty | Token::TgtZeroOrEmpty | Token::TgtNotZeroOrEmpty | Token::AccZeroOrEmpty | Token::AccNotZeroOrEmpty => {
+                    let jumps = match current {
+                        Token::ZeroOrEmpty => self.read_cell(index).is_none_or(|value| value == 0),
+                        Token::NotZeroOrEmpty => self.read_cell(index).is_some_and(|value| value != 0),
+                        Token::AccZeroOrEmpty => acc.is_none_or(|value| value == 0),
+                        Token::AccNotZeroOrEmpty => acc.is_some_and(|value| value != 0),
+                        _ => {
+                            let Some(target) = acc else { return fail(self, Error::AccumulatorError, &went_wrong()) };
+                            self.read_cell(target).is_none_or(|value| value == 0) == (current == Token::TgtZeroOrEmpty)
+                        }
+                    };
+                    if jumps {
+                        match self.targets[self.state.code_ptr_index] {
+                            Some(target) => self.set_code_ptr(target),
+                            None => return fail(self, Error::SyntaxError, &format!("conditional `{}` does not have a corresponding `@`", token_to_symbol(&current)))
+                        }
+                    }
+                    if matches!(current, Token::TgtZeroOrEmpty | Token::TgtNotZeroOrEmpty) {
+                        self.set_acc(None);
+                    }
+                }
+                Token::JumpCellsC => {
+                    let Some(value) = acc else { return fail(self, Error::OpError, &went_wrong()) };
+                    self.set_acc(None);
+                    let target = self.state.code_ptr_index.checked_add_signed(value).filter(|&target| target < self.tokens.len());
+                    let Some(target) = target else { return fail(self, Error::OverflowError, "code pointer went out of bounds when executing `J`") };
+                    self.set_code_ptr(target);
+                }
+                Token::JumpToCellC => {
+                    let Some(value) = acc else { return fail(self, Error::OpError, &went_wrong()) };
+                    if value >= self.tokens.len() as isize || value < 0 {
+                        return fail(self, Error::OverflowError, "code pointer went out of bounds when executing `j`")
+                    }
+                    self.set_acc(None);
+                    self.set_code_ptr(value as usize);
+                }
+                Token::JumpCellsD => {
+                    let Some(value) = acc else { return fail(self, Error::OpError, &went_wrong()) };
+                    self.set_acc(None);
+                    let Some(index) = self.state.data_ptr_index.checked_add(value) else {
+                        return fail(self, Error::OverflowError, "data pointer went out of bounds when executing `K`")
+                    };
+                    self.set_data_ptr(index);
+                    moving = false;
+                }
+                Token::JumpToCellD => {
+                    let Some(value) = acc else { return fail(self, Error::OpError, &went_wrong()) };
+                    self.set_acc(None);
+                    self.set_data_ptr(value);
+                    moving = false;
+                }
+                Token::MoveDUntilEmpty => {
+                    let mut index = index;
+                    while self.read_cell(index).is_some() {
+                        let Some(next) = index.checked_add(self.state.data_ptr_dir) else {
+                            self.set_data_ptr(index);
+                            return fail(self, Error::OverflowError, "data pointer went out of bounds when executing `M`")
+                        };
+                        index = next;
+                    }
+                    self.set_data_ptr(index);
+                    moving = false;
+                }
+                Token::MoveDUntilFull => {
+                    let dir = self.state.data_ptr_dir;
+                    let full = self.state.tape.next_full(index, dir);
+                    // the empty cells passed on the way are only visited if they are watched
+                    let mut watched = self.watch.next_cell(index, dir);
+                    while let Some(cell) = watched && full.is_none_or(|full| if dir > 0 { cell < full } else { cell > full }) {
+                        self.read_cell(cell);
+                        watched = cell.checked_add(dir).and_then(|next| self.watch.next_cell(next, dir));
+                    }
+                    let Some(full) = full else {
+                        self.set_data_ptr(if dir > 0 { isize::MAX } else { isize::MIN });
+                        return fail(self, Error::OverflowError, "data pointer went out of bounds when executing `m`")
+                    };
+                    self.read_cell(full);
+                    self.set_data_ptr(full);
+                    moving = false;
+                }
+                Token::FlipD(_) => {
+                    self.record(Change::FlipDir);
+                    self.state.data_ptr_dir *= -1;
+                }
+                Token::Neg => {
+                    let Some(value) = acc else { return fail(self, Error::AccumulatorError, &went_wrong()) };
+                    let Some(value) = value.checked_neg() else { return fail(self, Error::OverflowError, "command `-` caused overflow") };
+                    self.set_acc(Some(value));
+                }
+                Token::BitNot => {
+                    let Some(value) = self.read_cell(index) else { return fail(self, Error::OpError, &went_wrong()) };
+                    self.set_acc(Some(!value));
+                }
+                Token::Add | Token::Mul | Token::Div | Token::Mod | Token::Eq | Token::NotEq(_) | Token::Gt | Token::GE(_)
+                | Token::Lt | Token::LE(_) | Token::BitAnd | Token::BitOr | Token::BitXor => {
+                    let Some((left, right)) = self.operands() else { return fail(self, Error::OpError, &went_wrong()) };
+                    if matches!(current, Token::Div | Token::Mod) && right == 0 {
+                        return fail(self, Error::OpError, &format!("division by zero caused by `{}`", token_to_symbol(&current)))
+                    }
+                    if acc.is_some() {
+                        return fail(self, Error::AccumulatorError, &went_wrong())
+                    }
+                    let Some(value) = combine(current, left, right) else {
+                        return fail(self, Error::OverflowError, &format!("command `{}` caused overflow", token_to_symbol(&current)))
+                    };
+                    self.set_acc(Some(value));
+                }
+                Token::OutputInt => {
+                    let Some(value) = self.read_cell(index) else { return fail(self, Error::OpError, "something went wrong while executing `i`") };
+                    self.write(&value.to_string());
+                }
+                Token::OutputChar => {
+                    let Some(value) = self.read_cell(index) else { return fail(self, Error::OpError, "something went wrong while executing `s`") };
+                    let Some(chr) = u32::try_from(value).ok().and_then(char::from_u32) else {
+                        return fail(self, Error::OpError, "the current cell's value cannot be represented as a valid UTF-8 character")
+                    };
+                    self.write(&chr.to_string());
+                }
+                Token::InputInt => {
+                    let Ok(value) = self.read_line(current)?.parse::<isize>() else {
+                        return Err(Fault { details: false, ..self.fault(current, Error::InputError, "invalid input") })
+                    };
+                    self.set_acc(Some(value));
+                    moving = false;
+                }
+                Token::InputStr => {
+                    let mut index = index;
+                    for chr in self.read_line(current)?.chars() {
+                        self.set_cell(index, Some(chr as isize));
+                        let Some(next) = index.checked_add(1) else {
+                            return fail(self, Error::InputError, "input too long; went beyond tape boundaries")
+                        };
+                        index = next;
+                    }
+                }
+            }
+
+            if self.state.code_ptr_index + 1 >= self.tokens.len() {
+                return fail(self, Error::OutOfBoundsError, "code pointer went out of bounds")
+            }
+            let next = if moving { self.state.data_ptr_index.checked_add(self.state.data_ptr_dir) } else { Some(self.state.data_ptr_index) };
+            let Some(next) = next else { return fail(self, Error::OutOfBoundsError, "data pointer went out of bounds") };
+            self.record(Change::LastExecuted(self.state.last_executed));
+            self.state.last_executed = current;
+            self.set_code_ptr(self.state.code_ptr_index + 1);
+            if moving {
+                self.set_data_ptr(next);
+            }
+            Ok(None)
+        }
+    }
+
+    /// Combines the cells to the left of and at the data pointer like a binary command does, or returns [`None`] on overflow.
+    fn combine(command: Token, left: isize, right: isize) -> Option<isize> {
+        match command {
+            Token::Add => left.checked_add(right),
+            Token::Mul => left.checked_mul(right),
+            Token::Div => left.checked_div_euclid(right),
+            Token::Mod => left.checked_rem(right),
+            Token::Eq => Some(isize::from(left == right)),
+            Token::NotEq(_) => Some(isize::from(left != right)),
+            Token::Gt => Some(isize::from(left > right)),
+            Token::GE(_) => Some(isize::from(left >= right)),
+            Token::Lt => Some(isize::from(left < right)),
+            Token::LE(_) => Some(isize::from(left <= right)),
+            Token::BitAnd => Some(left & right),
+            Token::BitOr => Some(left | right),
+            Token::BitXor => Some(left ^ right),
+            _ => unreachable!()
+        }
+    }
+
+    const HELP: &str = "\
+step [n], s [n]        run the next n commands (1 by default)
+back [n], b [n]        undo the last n commands (1 by default)
+continue, c            run until a breakpoint, a watchpoint, the end of the program, or an error
+reverse-continue, rc   undo commands until a breakpoint, a watchpoint, or the start of the program
+break <index>          stop before running the command at index
+delete <index>         remove the breakpoint at index
+watch <cells>          stop when a cell in a range like 12, 10..20 or 10..=19 is read, written or cleared
+watch acc              stop when the accumulator becomes empty or full
+watch                  list what is being watched
+unwatch <cells>|acc    stop watching a range of cells or the accumulator
+print, p               print the state of the interpreter
+cell <index>           print the value of a cell
+quit, q                exit the debugger";
+
+    /// Parses a range of cells to watch: a single index like `12`, a half-open range like `10..20`, or a closed range like `10..=19`.
+    pub fn parse_cells(cells: &str) -> Result<RangeInclusive<isize>, String> {
+        let index = |index: &str| index.trim().parse::<isize>().map_err(|_| format!("`{index}` is not a cell index"));
+        let range = if let Some((start, end)) = cells.split_once("..=") {
+            index(start)?..=index(end)?
+        } else if let Some((start, end)) = cells.split_once("..") {
+            let end = index(end)?;
+            index(start)?..=end.checked_sub(1).ok_or(format!("`{cells}` is empty"))?
+        } else {
+            index(cells)?..=index(cells)?
+        };
+        if range.is_empty() {
+            return Err(format!("`{cells}` is empty"))
+        }
+        Ok(range)
+    }
+
+    fn print_hit(hit: &WatchHit) {
+        eprintln!("\x1b[36;1mwatch:\x1b[0m {hit}");
+    }
+
+    /// Prints the outcome of a program the way the interpreter reports it, without exiting.
+    fn report(m: &Machine, outcome: &Outcome) {
+        let state = m.state();
+        match outcome {
+            Outcome::Stopped(status) => eprintln!("the program stopped with status {status}"),
+            Outcome::Failed(fault) => {
+                fault.error.throw(&fault.msg, false);
+                if fault.details {
+                    print_details(&state.acc, &state.tape, state.data_ptr_index, state.data_ptr_dir, fault.code_ptr_index, &fault.command, &state.last_executed);
+                }
+            }
+        }
+    }
+
+    /// Writes out the output taken since the last call, then prints where the program is.
+    fn show(m: &mut Machine) {
+        print!("{}", m.take_output());
+        io::stdout().flush().unwrap();
+        let state = m.state();
+        match m.tokens().get(state.code_ptr_index) {
+            _ if m.outcome().is_some() => eprintln!("[step {}] the program has ended; step back to keep debugging", m.steps()),
+            Some(command) => eprintln!("[step {}] command {}: `{}`", m.steps(), state.code_ptr_index, token_to_symbol(command)),
+            None => eprintln!("[step {}] the program is empty", m.steps())
+        }
+    }
+
+    /// Steps forwards or backwards `count` times, stopping early when a watched thing is touched, the program ends,
+    /// or it reaches the start. Without a count, it keeps going until one of those happens or it reaches a breakpoint.
+    fn travel(m: &mut Machine, forward: bool, count: Option<usize>, breakpoints: &BTreeSet<usize>) {
+        for _ in 0..count.unwrap_or(usize::MAX) {
+            let outcome = if forward {
+                m.step().cloned()
+            } else if m.step_back() {
+                None
+            } else {
+                eprintln!("already at the start of the program");
+                return
+            };
+            let hits = m.take_hits();
+            hits.iter().for_each(print_hit);
+            if let Some(outcome) = outcome {
+                report(m, &outcome);
+                return
+            }
+            if !hits.is_empty() || count.is_none() && breakpoints.contains(&m.state().code_ptr_index) {
+                return
+            }
+        }
+    }
+
+    /// Runs a program under an interactive debugger, reading commands from standard input and printing to standard error.
+    /// The program's own output goes to standard output, and its input is read from standard input between commands.
+    pub fn debug_program(tokens: Vec<Token>, targets: Vec<Option<usize>>, exit_with_acc: bool, watch: Watch) -> ! {
+        let mut m = Machine::new(tokens, targets, exit_with_acc);
+        *m.watch_mut() = watch;
+        let mut breakpoints = BTreeSet::new();
+        eprintln!("type `help` for a list of commands");
+        show(&mut m);
+        loop {
+            eprint!("(gur) ");
+            let mut line = "".to_string();
+            if io::stdin().read_line(&mut line).is_err() || line.is_empty() {
+                eprintln!();
+                break
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let count = || words.get(1).map_or(Some(1), |count| count.parse::<usize>().ok());
+            let index = || words.get(1).and_then(|index| index.parse::<usize>().ok()).filter(|&index| index < m.tokens().len());
+            match words.first().copied() {
+                None => continue,
+                Some(command @ ("step" | "s" | "back" | "b")) => {
+                    let Some(count) = count() else { eprintln!("expected a number of commands"); continue };
+                    travel(&mut m, matches!(command, "step" | "s"), Some(count), &breakpoints);
+                }
+                Some("continue" | "c") => travel(&mut m, true, None, &breakpoints),
+                Some("reverse-continue" | "rc") => travel(&mut m, false, None, &breakpoints),
+                Some("break") => match index() {
+                    Some(index) => { breakpoints.insert(index); }
+                    None => eprintln!("expected the index of a command")
+                },
+                Some("delete") => match index() {
+                    Some(index) => { breakpoints.remove(&index); }
+                    None => eprintln!("expected the index of a command")
+                },
+                Some(command @ ("watch" | "unwatch")) => {
+                    let watching = command == "watch";
+                    match words.get(1).copied() {
+                        None if watching => {
+                            let watch = m.watch();
+                            watch.cells.iter().for_each(|cells| eprintln!("cells {}..={}", cells.start(), cells.end()));
+                            if watch.acc {
+                                eprintln!("the accumulator")
+                            }
+                        }
+                        Some("acc") => m.watch_mut().acc = watching,
+                        Some(cells) => match parse_cells(cells) {
+                            Ok(cells) if watching => m.watch_mut().cells.push(cells),
+                            Ok(cells) => m.watch_mut().cells.retain(|watched| *watched != cells),
+                            Err(msg) => eprintln!("{msg}")
+                        },
+                        None => eprintln!("expected a range of cells or `acc`")
+                    }
+                    continue
+                }
+                Some("print" | "p") => {
+                    let state = m.state();
+                    let current = m.tokens().get(state.code_ptr_index).copied().unwrap_or(Token::Nothing);
+                    print_details(&state.acc, &state.tape, state.data_ptr_index, state.data_ptr_dir, state.code_ptr_index, &current, &state.last_executed);
+                    continue
+                }
+                Some("cell") => {
+                    match words.get(1).and_then(|index| index.parse::<isize>().ok()) {
+                        Some(index) => eprintln!("cell {index}: {}", m.state().tape.get(index).map_or("<none>".to_string(), |value| value.to_string())),
+                        None => eprintln!("expected the index of a cell")
+                    }
+                    continue
+                }
+                Some("quit" | "q") => break,
+                Some("help" | "h") => {
+                    eprintln!("{HELP}");
+                    continue
+                }
+                Some(command) => {
+                    eprintln!("unknown command `{command}`; type `help` for a list of commands");
+                    continue
+                }
+            }
+            show(&mut m);
+        }
+        exit(0)
+    }
+
+    /// Runs a program like [`execute`](crate::exec::exec::execute), reporting every time something in `watch` is touched
+    /// on standard error as it happens, and returns how it ended.
+    pub fn execute_watched(tokens: Vec<Token>, targets: Vec<Option<usize>>, details: bool, exit_with_acc: bool, watch: Watch) -> Halt {
+        let mut m = Machine::new(tokens, targets, exit_with_acc);
+        m.discard_history();
+        *m.watch_mut() = watch;
+        loop {
+            let outcome = m.step().cloned();
+            let output = m.take_output();
+            if !output.is_empty() {
+                print!("{output}");
+                io::stdout().flush().unwrap();
+            }
+            m.take_hits().iter().for_each(print_hit);
+            match outcome {
+                None => {}
+                Some(Outcome::Stopped(status)) => {
+                    let state = m.state();
+                    if details && let Some(current) = m.tokens().get(state.code_ptr_index) {
+                        println!();
+                        print_details(&state.acc, &state.tape, state.data_ptr_index, state.data_ptr_dir, state.code_ptr_index, current, &state.last_executed);
+                    }
+                    return Halt::new(status, state, state.code_ptr_index)
+                }
+                Some(Outcome::Failed(fault)) => {
+                    report(&m, &Outcome::Failed(fault.clone()));
+                    return Halt::new(fault.error.exit_code(), m.state(), fault.code_ptr_index)
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// Runs a program with one line of input on a machine that keeps no history, returning how it ended.
+        fn run(program: &str, input: &str) -> Outcome {
+            let tokens = try_tokenize(program).unwrap();
+            let targets = match_conditionals(&tokens);
+            let mut machine = Machine::new(tokens, targets, false);
+            machine.discard_history();
+            let mut input = Some(input.to_string());
+            machine.set_input(move |line| {
+                let read = input.take().unwrap_or_default();
+                line.push_str(&read);
+                Ok(read.len())
+            });
+            for _ in 0..1000 {
+                if let Some(outcome) = machine.step() {
+                    return outcome.clone()
+                }
+            }
+            panic!("{program} didn't halt")
+        }
+
+        fn error(program: &str, input: &str) -> (Error, String) {
+            match run(program, input) {
+                Outcome::Failed(fault) => (fault.error, fault.msg),
+                Outcome::Stopped(status) => panic!("{program} stopped with status {status}")
+            }
+        }
+
+        // the inputs below are the crashes the `execute` fuzz target found
+
+        #[test]
+        fn negating_the_smallest_number_overflows() {
+            assert_eq!(error("I - .", "-9223372036854775808\n"), (Error::OverflowError, "command `-` caused overflow".to_string()));
+        }
+
+        #[test]
+        fn jumping_back_by_the_smallest_number_overflows() {
+            assert_eq!(error("I J .", "-9223372036854775808\n"), (Error::OverflowError, "code pointer went out of bounds when executing `J`".to_string()));
+        }
+
+        #[test]
+        fn moving_until_full_stops_at_the_end_of_the_tape() {
+            for program in ["#0 m .", "#0 F m ."] {
+                assert_eq!(error(program, ""), (Error::OverflowError, "data pointer went out of bounds when executing `m`".to_string()), "{program}");
+            }
+        }
+
+        #[test]
+        fn machines_without_history_cannot_step_back() {
+            let tokens = try_tokenize("#1U .").unwrap();
+            let targets = match_conditionals(&tokens);
+            let mut machine = Machine::new(tokens, targets, false);
+            machine.discard_history();
+            machine.step();
+            assert!(!machine.step_back());
+            assert!(matches!(run("#1U .", ""), Outcome::Stopped(0)));
+        }
+    }
+}
@@ -0,0 +1,332 @@
+pub mod pseudo {
+    use std::collections::BTreeSet;
+    use std::ops::Range;
+    use crate::lexer::lex::*;
+    use crate::parser::ast::*;
+
+    /// A line of pseudocode, with the indices of the commands it stands for.
+    struct Line {
+        depth: usize,
+        text: String,
+        commands: Option<Range<usize>>,
+    }
+
+    /// A number literal that hasn't been written yet, in case the `-` or jump after it can be written along with it.
+    struct Literal {
+        commands: Range<usize>,
+        value: Option<isize>,
+        negated: bool,
+    }
+
+    struct Decompiler<'a> {
+        tokens: &'a [Token],
+        /// The indices of the commands that a `J` or `j` with a constant target continues at, which get a label.
+        labels: BTreeSet<usize>,
+        /// The value each `J` and `j` always jumps with, as found by [`static_jumps`].
+        jumps: Vec<Option<isize>>,
+        /// Whether moves of the data pointer can be put off and added together, which isn't possible if a computed jump
+        /// could land between two of them.
+        merge: bool,
+        lines: Vec<Line>,
+        depth: usize,
+        literal: Option<Literal>,
+        /// The number of moves of the data pointer that haven't been written yet, and the commands they come from.
+        steps: Option<(usize, Range<usize>)>,
+    }
+
+    impl Decompiler<'_> {
+        fn emit(&mut self, text: String, commands: Range<usize>) {
+            self.lines.push(Line { depth: self.depth, text, commands: Some(commands) });
+        }
+
+        /// Puts off the move of the data pointer after the command at `index`.
+        fn step(&mut self, index: usize) {
+            self.steps = Some(self.steps.take().map_or((1, index..index + 1), |(count, commands)| (count + 1, commands.start..index + 1)));
+            if !self.merge {
+                self.flush()
+            }
+        }
+
+        /// Writes the moves of the data pointer that were put off.
+        fn flush(&mut self) {
+            let Some((count, commands)) = self.steps.take() else { return };
+            let text = if count == 1 { "p += dir".to_string() } else { format!("p += {count}*dir") };
+            self.emit(text, commands);
+        }
+
+        /// Returns the position of the current cell, given the moves that were put off, plus `offset` more.
+        fn position(&self, offset: isize) -> String {
+            match self.steps.as_ref().map_or(0, |(count, _)| *count as isize) + offset {
+                0 => "p".to_string(),
+                1 => "p + dir".to_string(),
+                -1 => "p - dir".to_string(),
+                n if n < 0 => format!("p - {}*dir", -n),
+                n => format!("p + {n}*dir")
+            }
+        }
+
+        fn cell(&self) -> String {
+            format!("tape[{}]", self.position(0))
+        }
+
+        fn left(&self) -> String {
+            format!("tape[{} - 1]", self.position(0))
+        }
+
+        /// Writes the literal being held back.
+        fn release(&mut self) {
+            let Some(literal) = self.literal.take() else { return };
+            let value = match literal.value {
+                Some(value) if literal.negated => format!("-{value}"),
+                Some(value) => value.to_string(),
+                None => "<too large>".to_string()
+            };
+            self.emit(format!("acc = {value}"), literal.commands.clone());
+            if literal.negated {
+                self.step(literal.commands.end - 1);
+            }
+        }
+
+        /// Called before each node: a label starts a new line of control, so nothing can be held back across it.
+        fn start(&mut self, node: &Node) {
+            if self.labels.contains(&node.index()) {
+                self.release();
+                self.flush();
+                self.lines.push(Line { depth: self.depth, text: format!("L{}:", node.index()), commands: None });
+            }
+        }
+
+        /// Returns the text of a jump that continues at `next`.
+        fn goto(&self, next: Option<usize>) -> String {
+            match next {
+                Some(next) if self.labels.contains(&next) => format!("goto L{next}"),
+                Some(next) if next < self.tokens.len() => format!("goto command {next}"),
+                _ => "error \"jump out of the program\"".to_string()
+            }
+        }
+    }
+
+    /// Returns the condition under which a conditional jumps to its matching `@`, where `cell` is the cell it tests.
+    fn jump_condition(test: Token, cell: &str) -> String {
+        match test {
+            Token::ZeroOrEmpty => format!("empty_or_zero({cell})"),
+            Token::NotZeroOrEmpty => format!("not empty_or_zero({cell})"),
+            Token::TgtZeroOrEmpty => "empty_or_zero(tape[take(acc)])".to_string(),
+            Token::TgtNotZeroOrEmpty => "not empty_or_zero(tape[take(acc)])".to_string(),
+            Token::AccZeroOrEmpty => "empty_or_zero(acc)".to_string(),
+            _ => "not empty_or_zero(acc)".to_string()
+        }
+    }
+
+    fn negate(condition: &str) -> String {
+        match condition.strip_prefix("not ") {
+            Some(condition) => condition.to_string(),
+            None => format!("not {condition}")
+        }
+    }
+
+    impl Visitor for Decompiler<'_> {
+        fn visit_literal(&mut self, node: &Node, value: Option<isize>) {
+            self.start(node);
+            self.release();
+            self.literal = Some(Literal { commands: node.commands.clone(), value, negated: false });
+        }
+
+        fn visit_command(&mut self, node: &Node, token: Token) {
+            self.start(node);
+            let index = node.index();
+            if let Some(mut literal) = self.literal.take() {
+                match token {
+                    Token::Neg if !literal.negated => {
+                        literal.negated = true;
+                        literal.commands.end = index + 1;
+                        self.literal = Some(literal);
+                        return
+                    }
+                    Token::JumpCellsC | Token::JumpToCellC if let Some(value) = self.jumps[index] => {
+                        let commands = literal.commands.start..index + 1;
+                        if literal.negated {
+                            self.step(literal.commands.end - 1);
+                        }
+                        let next = match token {
+                            Token::JumpCellsC => index.checked_add_signed(value),
+                            _ => usize::try_from(value).ok()
+                        }.and_then(|target| target.checked_add(1));
+                        self.step(index);
+                        self.flush();
+                        // the literal goes into the accumulator and the jump takes it out, so the accumulator is left as it was
+                        let text = self.goto(next);
+                        self.emit(text, commands);
+                        return
+                    }
+                    _ => {
+                        self.literal = Some(literal);
+                        self.release()
+                    }
+                }
+            }
+
+            let (cell, left) = (self.cell(), self.left());
+            let text = match token {
+                Token::NoOp | Token::DestinationIfTrue | Token::Nothing => {
+                    self.step(index);
+                    return
+                }
+                Token::Stop => {
+                    self.flush();
+                    self.emit("stop".to_string(), node.commands.clone());
+                    return
+                }
+                Token::CreatingNumber => "start a number in acc".to_string(),
+                Token::Digit(n) => format!("acc = acc*10 + {n}"),
+                Token::Unload => format!("{cell} = acc; acc = empty"),
+                Token::Distribute => format!("{cell} = acc"),
+                Token::Recall => format!("acc = {cell}; {cell} = empty"),
+                Token::Copy => format!("acc = {cell}"),
+                Token::ClearAcc => "acc = empty".to_string(),
+                Token::ClearCurrCell => format!("{cell} = empty"),
+                _ if token.is_conditional() => format!("if {} {{ error \"no matching @\" }}", jump_condition(token, &cell)),
+                Token::JumpCellsC | Token::JumpToCellC => {
+                    self.step(index);
+                    self.flush();
+                    let target = if token == Token::JumpCellsC { format!("{index} + take(acc)") } else { "take(acc)".to_string() };
+                    self.emit(format!("goto command {target} + 1"), node.commands.clone());
+                    return
+                }
+                Token::JumpCellsD | Token::JumpToCellD | Token::MoveDUntilEmpty | Token::MoveDUntilFull => {
+                    self.flush();
+                    let text = match token {
+                        Token::JumpCellsD => "p += take(acc)",
+                        Token::JumpToCellD => "p = take(acc)",
+                        Token::MoveDUntilEmpty => "p = next_empty(p, dir)",
+                        _ => "p = next_full(p, dir)"
+                    };
+                    self.emit(text.to_string(), node.commands.clone());
+                    return
+                }
+                Token::FlipD(_) => {
+                    self.flush();
+                    self.emit("dir = -dir".to_string(), node.commands.clone());
+                    self.step(index);
+                    return
+                }
+                Token::Add => format!("acc = {left} + {cell}"),
+                Token::Neg => "acc = -acc".to_string(),
+                Token::Mul => format!("acc = {left} * {cell}"),
+                Token::Div => format!("acc = floor({left} / {cell})"),
+                Token::Mod => format!("acc = {left} % {cell}"),
+                Token::Eq => format!("acc = {left} == {cell}"),
+                Token::NotEq(_) => format!("acc = {left} != {cell}"),
+                Token::Gt => format!("acc = {left} > {cell}"),
+                Token::GE(_) => format!("acc = {left} >= {cell}"),
+                Token::Lt => format!("acc = {left} < {cell}"),
+                Token::LE(_) => format!("acc = {left} <= {cell}"),
+                Token::BitAnd => format!("acc = {left} & {cell}"),
+                Token::BitOr => format!("acc = {left} | {cell}"),
+                Token::BitNot => format!("acc = ~{cell}"),
+                Token::BitXor => format!("acc = {left} ^ {cell}"),
+                Token::OutputInt => format!("print_int({cell})"),
+                Token::OutputChar => format!("print_char({cell})"),
+                Token::InputInt => {
+                    self.emit("acc = read_int()".to_string(), node.commands.clone());
+                    return
+                }
+                Token::InputStr => format!("tape[{}..] = read_chars()", self.position(0)),
+                _ => unreachable!()
+            };
+            self.emit(text, node.commands.clone());
+            if !matches!(token, Token::CreatingNumber | Token::Digit(_)) {
+                self.step(index);
+            }
+        }
+
+        fn enter_conditional(&mut self, node: &Node, test: Token) {
+            self.start(node);
+            self.release();
+            // the data pointer moves after the conditional whether it jumps or not, so the move is written before the `if`,
+            // which then tests the cell one move back
+            self.step(node.index());
+            self.flush();
+            let condition = negate(&jump_condition(test, &format!("tape[{}]", self.position(-1))));
+            self.emit(format!("if {condition} {{"), node.index()..node.index() + 1);
+            self.depth += 1;
+        }
+
+        fn leave_conditional(&mut self, node: &Node, _test: Token) {
+            let end = node.commands.end - 1;
+            self.release();
+            // the `@` runs as a no-op when the body falls through to it, so only that way moves the data pointer
+            self.step(end);
+            self.flush();
+            self.depth -= 1;
+            self.emit("}".to_string(), end..end + 1);
+        }
+    }
+
+    /// Collects the index of every node's first command, which are the commands a label can go before.
+    struct Starts(BTreeSet<usize>);
+
+    impl Visitor for Starts {
+        fn visit_command(&mut self, node: &Node, _token: Token) {
+            self.0.insert(node.index());
+        }
+
+        fn visit_literal(&mut self, node: &Node, _value: Option<isize>) {
+            self.0.insert(node.index());
+        }
+
+        fn enter_conditional(&mut self, node: &Node, _test: Token) {
+            self.0.insert(node.index());
+        }
+    }
+
+    /// Turns a program into pseudocode, with each line followed by the indices of the commands it stands for.
+    ///
+    /// `acc` is the accumulator, `tape[p]` the current cell, and `dir` the direction of the data pointer, which is 1 or -1.
+    /// `take(acc)` is the accumulator's value, leaving it empty. The data pointer's move after each command is written as `p += dir`,
+    /// and moves are added together until something needs the data pointer to be where it is, unless the program has a jump to a computed target.
+    /// Conditionals become `if` blocks that run when the conditional doesn't jump, and a `J` or `j` right after a number literal that nothing jumps into becomes a `goto`
+    /// to a label named after the index of the command it continues at. Errors are only written where they are certain, like a conditional without a matching `@`.
+    pub fn decompile(tokens: &[Token]) -> String {
+        let program = parse_tokens(tokens);
+        let mut starts = Starts(BTreeSet::new());
+        program.walk(&mut starts);
+
+        let jumps = static_jumps(tokens);
+        let mut labels = BTreeSet::new();
+        let mut merge = true;
+        for (index, token) in tokens.iter().enumerate() {
+            if !matches!(token, Token::JumpCellsC | Token::JumpToCellC) {
+                continue
+            }
+            let Some(value) = jumps[index] else {
+                merge = false;
+                continue
+            };
+            let target = match token {
+                Token::JumpCellsC => index.checked_add_signed(value),
+                _ => usize::try_from(value).ok()
+            };
+            if let Some(next) = target.and_then(|target| target.checked_add(1)) && starts.0.contains(&next) {
+                labels.insert(next);
+            }
+        }
+
+        let mut decompiler = Decompiler { tokens, labels, jumps, merge, lines: vec![], depth: 0, literal: None, steps: None };
+        program.walk(&mut decompiler);
+        decompiler.release();
+        decompiler.flush();
+
+        let width = decompiler.lines.iter().map(|line| line.depth * 4 + line.text.chars().count()).max().unwrap_or(0);
+        let mut out = "".to_string();
+        for line in decompiler.lines {
+            let code = format!("{}{}", "    ".repeat(line.depth), line.text);
+            match line.commands {
+                Some(commands) if commands.len() > 1 => out.push_str(&format!("{code:<width$}  // {}-{}\n", commands.start, commands.end - 1)),
+                Some(commands) => out.push_str(&format!("{code:<width$}  // {}\n", commands.start)),
+                None => out.push_str(&format!("{code}\n"))
+            }
+        }
+        out
+    }
+}
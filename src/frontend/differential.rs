@@ -0,0 +1,451 @@
+pub mod diff {
+    use std::collections::BTreeMap;
+    use std::fmt;
+    use std::fs;
+    use std::io::{self, Read, Write};
+    use std::path::{Path, PathBuf};
+    use std::process::{Command, ExitStatus, Stdio};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread::{self, JoinHandle};
+    use std::time::{Duration, Instant};
+    use serde_json::Value;
+    use crate::errors::err::Error;
+    use crate::lexer::lex::*;
+
+    /// A way of running a program.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Backend {
+        /// The interpreter with `--engine reference`.
+        Reference,
+        /// The interpreter with `--engine threaded`.
+        Threaded,
+        /// The program built into bytecode with `build`, then run by the interpreter.
+        Bytecode,
+        /// The program compiled with `compile --target rust`, then built with `rustc`.
+        Rust,
+        /// The program compiled with `compile --target x86_64-linux`, then built with `as` and `ld`.
+        X86_64Linux,
+    }
+
+    impl Backend {
+        /// The backends that only need the interpreter to run.
+        pub const INTERPRETED: [Backend; 3] = [Backend::Reference, Backend::Threaded, Backend::Bytecode];
+
+        /// Whether the backend can write out the whole tape. Compiled programs only print the current and left cells with the details.
+        fn whole_tape(self) -> bool {
+            !matches!(self, Backend::Rust | Backend::X86_64Linux)
+        }
+    }
+
+    /// How a run ended.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Ending {
+        /// `.` was run.
+        Stopped,
+        Threw(Error),
+        /// The process exited with a status that no error has, like 101 after a panic, or was killed by a signal ([`None`]).
+        Crashed(Option<i32>),
+        /// The process was killed for running longer than the harness's timeout.
+        TimedOut,
+    }
+
+    /// The state of the interpreter when a run ended, as printed with the details.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct FinalState {
+        pub acc: Option<isize>,
+        pub code_ptr_index: usize,
+        pub data_ptr_index: isize,
+        pub data_ptr_dir: isize,
+        /// The values of the full cells by index: all of them if `whole_tape` is set, and otherwise the current and left cells.
+        pub tape: BTreeMap<isize, isize>,
+        pub whole_tape: bool,
+    }
+
+    impl FinalState {
+        /// Returns the left and current cells, which every backend reports.
+        fn near_cells(&self) -> [Option<isize>; 2] {
+            let left = self.data_ptr_index.checked_sub(1).and_then(|index| self.tape.get(&index).copied());
+            [left, self.tape.get(&self.data_ptr_index).copied()]
+        }
+    }
+
+    /// What a backend did with a program.
+    #[derive(Clone, Debug)]
+    pub struct Observation {
+        pub stdout: String,
+        pub ending: Ending,
+        /// [`None`] if the details weren't printed, which happens when the input is invalid or the run crashed or timed out.
+        pub state: Option<FinalState>,
+    }
+
+    /// Something a backend did differently from the first backend, or a crash.
+    #[derive(Clone, Debug)]
+    pub struct Mismatch {
+        pub backend: Backend,
+        /// What differs: `stdout`, `ending`, `details`, `accumulator`, `pointers` or `tape`.
+        pub aspect: &'static str,
+        pub expected: String,
+        pub actual: String,
+    }
+
+    impl fmt::Display for Mismatch {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}: {} was {} instead of {}", self.backend, self.aspect, self.actual, self.expected)
+        }
+    }
+
+    /// Gives every harness its own directory, even when several run in the same process.
+    static HARNESSES: AtomicUsize = AtomicUsize::new(0);
+
+    /// Runs programs on several backends and compares what they do: what they print, how they end,
+    /// and the accumulator, pointers and tape they end with.
+    ///
+    /// Every backend is run as a process of its own, so that a crash or a hang is observed rather than suffered.
+    pub struct Harness {
+        interpreter: PathBuf,
+        backends: Vec<Backend>,
+        timeout: Duration,
+        dir: PathBuf,
+        runs: usize,
+    }
+
+    impl Harness {
+        /// `interpreter` is the `guryvsr` executable, which runs the programs and compiles them for the other backends.
+        /// The first backend is the one the others are compared with.
+        pub fn new(interpreter: impl Into<PathBuf>, backends: &[Backend]) -> Harness {
+            let id = HARNESSES.fetch_add(1, Ordering::Relaxed);
+            Harness {
+                interpreter: interpreter.into(),
+                backends: backends.to_vec(),
+                timeout: Duration::from_secs(10),
+                dir: std::env::temp_dir().join(format!("guryvsr-diff-{}-{id}", std::process::id())),
+                runs: 0,
+            }
+        }
+
+        /// Replaces how long a run may take before it is killed, which is 10 seconds by default.
+        pub fn set_timeout(&mut self, timeout: Duration) {
+            self.timeout = timeout;
+        }
+
+        /// Runs a program on every backend with the provided input, and returns how the others differ from the first, along with any crashes.
+        /// Fails if a program can't be built for a backend, like when `rustc` isn't installed.
+        pub fn compare(&mut self, tokens: &[Token], input: &[u8]) -> io::Result<Vec<Mismatch>> {
+            let mut observations = vec![];
+            for backend in self.backends.clone() {
+                observations.push((backend, self.observe(backend, tokens, input)?));
+            }
+            let mut mismatches = vec![];
+            for (backend, observation) in &observations {
+                if let Ending::Crashed(status) = observation.ending {
+                    let actual = status.map_or("a crash".to_string(), |status| format!("a crash with status {status}"));
+                    mismatches.push(Mismatch { backend: *backend, aspect: "ending", expected: "a halt or an error".to_string(), actual });
+                }
+            }
+            if let Some(((_, expected), others)) = observations.split_first() {
+                for (backend, actual) in others {
+                    mismatches.extend(differences(*backend, expected, actual));
+                }
+            }
+            Ok(mismatches)
+        }
+
+        /// Runs a program on a backend with the provided input.
+        pub fn observe(&mut self, backend: Backend, tokens: &[Token], input: &[u8]) -> io::Result<Observation> {
+            fs::create_dir_all(&self.dir)?;
+            self.runs += 1;
+            let base = self.dir.join(format!("run{}", self.runs));
+            let source = base.with_extension("gur");
+            fs::write(&source, tokens.iter().map(token_to_symbol).collect::<String>())?;
+
+            let mut command = match backend {
+                Backend::Reference | Backend::Threaded => {
+                    let engine = format!("{backend:?}").to_lowercase();
+                    let mut command = Command::new(&self.interpreter);
+                    command.arg(&source).args(["--engine", &engine]);
+                    command
+                }
+                Backend::Bytecode => {
+                    let bytecode = base.with_extension("gurc");
+                    build(Command::new(&self.interpreter).args(["build", "-o"]).args([&bytecode, &source]))?;
+                    let mut command = Command::new(&self.interpreter);
+                    command.arg(&bytecode);
+                    command
+                }
+                Backend::Rust => {
+                    let rust = base.with_extension("rs");
+                    build(Command::new(&self.interpreter).args(["compile", "--target", "rust", "-o"]).args([&rust, &source]))?;
+                    build(Command::new("rustc").args(["-O", "-o"]).args([&base, &rust]))?;
+                    Command::new(&base)
+                }
+                Backend::X86_64Linux => {
+                    let assembly = base.with_extension("s");
+                    let object = base.with_extension("o");
+                    build(Command::new(&self.interpreter).args(["compile", "--target", "x86_64-linux", "-o"]).args([&assembly, &source]))?;
+                    build(Command::new("as").arg("-o").args([&object, &assembly]))?;
+                    build(Command::new("ld").arg("-o").args([&base, &object]))?;
+                    Command::new(&base)
+                }
+            };
+            command.arg("--details");
+            let state_path = base.with_extension("json");
+            if backend.whole_tape() {
+                command.arg("--dump-state").arg(&state_path);
+            }
+
+            let (status, stdout, stderr) = run_with_timeout(&mut command, input, self.timeout)?;
+            let ending = match status.map(|status| status.code()) {
+                None => Ending::TimedOut,
+                Some(Some(0)) => Ending::Stopped,
+                Some(Some(code)) => match error_with_status(code) {
+                    Some(error) => Ending::Threw(error),
+                    None => Ending::Crashed(Some(code))
+                },
+                Some(None) => Ending::Crashed(None),
+            };
+            let state = match ending {
+                Ending::Crashed(_) | Ending::TimedOut => None,
+                // the state is only written out in full, but like the others it only counts when the details were printed
+                _ if backend.whole_tape() => parse_details(&String::from_utf8_lossy(&stderr)).and_then(|_| read_state(&state_path)),
+                _ => parse_details(&String::from_utf8_lossy(&stderr)),
+            };
+            Ok(Observation { stdout: String::from_utf8_lossy(&stdout).into_owned(), ending, state })
+        }
+    }
+
+    /// Removes the programs the harness wrote and built.
+    impl Drop for Harness {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    /// Returns the error that exits the process with the provided status, if any.
+    fn error_with_status(status: i32) -> Option<Error> {
+        [Error::FileError, Error::UnknownSymbolError, Error::OpError, Error::AccumulatorError, Error::SyntaxError,
+         Error::InputError, Error::OutOfBoundsError, Error::OverflowError, Error::InfiniteLoopError]
+            .into_iter()
+            .find(|error| error.exit_code() == status)
+    }
+
+    /// Runs a step of building a program for a backend, failing if it does.
+    fn build(command: &mut Command) -> io::Result<()> {
+        let output = command.output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!("{command:?} failed: {}", String::from_utf8_lossy(&output.stderr))))
+        }
+        Ok(())
+    }
+
+    fn read_in_background(mut pipe: impl Read + Send + 'static) -> JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut bytes = vec![];
+            let _ = pipe.read_to_end(&mut bytes);
+            bytes
+        })
+    }
+
+    /// Runs a command with the provided input, killing it if it takes longer than `timeout`.
+    /// Returns its exit status, or [`None`] if it was killed, along with what it wrote to standard output and standard error.
+    /// Input is written and output is read on threads of their own, so a program that prints a lot never blocks on a full pipe.
+    pub fn run_with_timeout(command: &mut Command, input: &[u8], timeout: Duration) -> io::Result<(Option<ExitStatus>, Vec<u8>, Vec<u8>)> {
+        let mut child = command.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+        let mut stdin = child.stdin.take().unwrap();
+        let input = input.to_vec();
+        // the program may exit without reading all of its input
+        let writer = thread::spawn(move || { let _ = stdin.write_all(&input); });
+        let stdout = read_in_background(child.stdout.take().unwrap());
+        let stderr = read_in_background(child.stderr.take().unwrap());
+        let start = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break Some(status)
+            }
+            if start.elapsed() > timeout {
+                child.kill()?;
+                child.wait()?;
+                break None
+            }
+            thread::sleep(Duration::from_millis(2));
+        };
+        let _ = writer.join();
+        Ok((status, stdout.join().unwrap(), stderr.join().unwrap()))
+    }
+
+    /// Reads the state written by `--dump-state`, if it was written.
+    fn read_state(path: &Path) -> Option<FinalState> {
+        let state: Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+        let tape = state["tape"].as_array()?.iter()
+            .map(|cell| Some((cell[0].as_i64()? as isize, cell[1].as_i64()? as isize)))
+            .collect::<Option<BTreeMap<isize, isize>>>()?;
+        Some(FinalState {
+            acc: state["acc"].as_i64().map(|acc| acc as isize),
+            code_ptr_index: state["code_ptr_index"].as_u64()? as usize,
+            data_ptr_index: state["data_ptr_index"].as_i64()? as isize,
+            data_ptr_dir: state["data_ptr_dir"].as_i64()? as isize,
+            tape,
+            whole_tape: true,
+        })
+    }
+
+    /// Reads the state from the details printed on standard error, which only show the current and left cells of the tape.
+    fn parse_details(stderr: &str) -> Option<FinalState> {
+        let details = &stderr[stderr.find("[Details]")?..];
+        let field = |name: &str| details.lines().find_map(|line| line.split_once(&format!("{name}: ")).map(|(_, value)| value));
+        let cell = |name: &str| field(name).and_then(|value| value.parse::<isize>().ok());
+        let data_ptr_index = field("data pointer index")?.parse().ok()?;
+        let mut tape = BTreeMap::new();
+        if let Some(value) = cell("current cell value") {
+            tape.insert(data_ptr_index, value);
+        }
+        if let Some(value) = cell("left cell value") {
+            tape.insert(data_ptr_index - 1, value);
+        }
+        Some(FinalState {
+            acc: cell("accumulator value"),
+            code_ptr_index: field("code pointer index")?.parse().ok()?,
+            data_ptr_index,
+            data_ptr_dir: if field("data pointer direction")? == "positive" { 1 } else { -1 },
+            tape,
+            whole_tape: false,
+        })
+    }
+
+    /// Returns how `actual`, from `backend`, differs from `expected`.
+    fn differences(backend: Backend, expected: &Observation, actual: &Observation) -> Vec<Mismatch> {
+        let mut mismatches = vec![];
+        let mut differ = |aspect: &'static str, expected: String, actual: String| {
+            if expected != actual {
+                mismatches.push(Mismatch { backend, aspect, expected, actual });
+            }
+        };
+        differ("stdout", format!("{:?}", expected.stdout), format!("{:?}", actual.stdout));
+        differ("ending", format!("{:?}", expected.ending), format!("{:?}", actual.ending));
+        match (&expected.state, &actual.state) {
+            (Some(expected), Some(actual)) => {
+                differ("accumulator", format!("{:?}", expected.acc), format!("{:?}", actual.acc));
+                let pointers = |state: &FinalState| format!("code {}, data {}, direction {}", state.code_ptr_index, state.data_ptr_index, state.data_ptr_dir);
+                differ("pointers", pointers(expected), pointers(actual));
+                if expected.whole_tape && actual.whole_tape {
+                    differ("tape", format!("{:?}", expected.tape), format!("{:?}", actual.tape));
+                } else {
+                    differ("tape", format!("{:?}", expected.near_cells()), format!("{:?}", actual.near_cells()));
+                }
+            }
+            (expected, actual) => {
+                let printed = |state: &Option<FinalState>| if state.is_some() { "printed" } else { "not printed" }.to_string();
+                differ("details", printed(expected), printed(actual));
+            }
+        }
+        mismatches
+    }
+
+    /// The commands the generator uses anywhere, as opposed to literals, conditionals, `@`, jumps and `.`, which it places with care.
+    const PLAIN: &str = "_UuRrCcFfKkMm+-*/%=Nn>Gg<Ll&|~^isIS";
+
+    /// Makes random programs that are well-formed: every conditional has a matching `@`, number literals are `#` followed by digits
+    /// (never followed by another `#`), and the program ends with `.`. Jumps are always forward and go to a number literal's value,
+    /// so every program halts. The same seed always makes the same programs.
+    pub struct Generator {
+        state: u64,
+        /// The most commands a program has before its conditionals are closed and `.` is added.
+        pub max_len: usize,
+    }
+
+    impl Generator {
+        pub fn new(seed: u64) -> Generator {
+            Generator { state: seed, max_len: 40 }
+        }
+
+        /// Returns the next number of the sequence (SplitMix64).
+        fn next(&mut self) -> u64 {
+            self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+            let mut n = self.state;
+            n = (n ^ (n >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            n = (n ^ (n >> 27)).wrapping_mul(0x94d049bb133111eb);
+            n ^ (n >> 31)
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+
+        pub fn program(&mut self) -> Vec<Token> {
+            let len = 1 + self.below(self.max_len);
+            let mut tokens = vec![];
+            let mut open = 0;
+            while tokens.len() < len {
+                match self.below(20) {
+                    0..=3 => {
+                        let value = if self.below(10) == 0 { self.next().to_string() } else { self.below(1000).to_string() };
+                        literal(&mut tokens, &value);
+                        // most literals are stored right away, so that the accumulator is free for the next command that needs it
+                        if self.below(3) > 0 {
+                            tokens.push(Token::Unload);
+                        }
+                    }
+                    4..=5 => {
+                        tokens.push(symbol_to_token("?!TtAa".chars().nth(self.below(6)).unwrap()).unwrap());
+                        open += 1;
+                    }
+                    6..=7 if open > 0 => {
+                        tokens.push(Token::DestinationIfTrue);
+                        open -= 1;
+                    }
+                    8 => self.jump(&mut tokens),
+                    _ => tokens.push(symbol_to_token(PLAIN.chars().nth(self.below(PLAIN.len())).unwrap()).unwrap()),
+                }
+            }
+            tokens.extend(std::iter::repeat_n(Token::DestinationIfTrue, open));
+            tokens.push(Token::Stop);
+            tokens
+        }
+
+        /// Adds a jump forward by a few commands, either relative with `J` or absolute with `j`.
+        fn jump(&mut self, tokens: &mut Vec<Token>) {
+            let skip = 1 + self.below(4);
+            if self.below(2) == 0 {
+                literal(tokens, &skip.to_string());
+                tokens.push(Token::JumpCellsC);
+                return
+            }
+            if matches!(tokens.last(), Some(Token::Digit(_))) {
+                tokens.push(Token::NoOp);
+            }
+            // the `j` comes after the digits of its target, so the target depends on how many digits it has
+            let mut digits = 1;
+            let target = loop {
+                let target = tokens.len() + 1 + digits + skip;
+                if target.to_string().len() == digits {
+                    break target
+                }
+                digits += 1;
+            };
+            literal(tokens, &target.to_string());
+            tokens.push(Token::JumpToCellC);
+        }
+
+        /// Returns a few lines of input, mostly numbers that `I` can read, and sometimes words or numbers at the edge of what fits.
+        pub fn input(&mut self) -> Vec<u8> {
+            let mut input = "".to_string();
+            for _ in 0..self.below(4) {
+                match self.below(4) {
+                    0 => input.extend((0..self.below(6)).map(|_| (b'a' + self.below(26) as u8) as char)),
+                    1 => input.push_str(["-9223372036854775808", "9223372036854775807", "-1", "0"][self.below(4)]),
+                    _ => input.push_str(&(self.below(1050) as isize - 50).to_string()),
+                }
+                input.push('\n');
+            }
+            input.into_bytes()
+        }
+    }
+
+    /// Adds a number literal, separating it from a literal right before it, which it would otherwise continue.
+    fn literal(tokens: &mut Vec<Token>, digits: &str) {
+        if matches!(tokens.last(), Some(Token::Digit(_))) {
+            tokens.push(Token::NoOp);
+        }
+        tokens.push(Token::CreatingNumber);
+        tokens.extend(digits.chars().map(|digit| Token::Digit(digit.to_digit(10).unwrap() as isize)));
+    }
+}
@@ -4,22 +4,46 @@ pub mod err {
     use std::process::exit;
 
     /// Error that can be thrown when something goes wrong.
-    #[derive(Debug)]
+    /// Each error exits the process with its own status, which is noted next to it and returned by [`Error::exit_code`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum Error {
-        FileError,
-        UnknownSymbolError,
-        OpError,
-        AccumulatorError,
-        SyntaxError,
-        InputError,
-        OutOfBoundsError,
-        OverflowError,
+        FileError,          // 10
+        UnknownSymbolError, // 11
+        OpError,            // 12
+        AccumulatorError,   // 13
+        SyntaxError,        // 14
+        InputError,         // 15
+        OutOfBoundsError,   // 16
+        OverflowError,      // 17
+        InfiniteLoopError,  // 18
     }
 
     impl Error {
         pub(crate) fn throw(&self, msg: &str, terminate: bool) {
             eprintln!("\x1b[31;1m{:?}:\x1b[0m {msg}", self);
-            if terminate { exit(1) }
+            if terminate { exit(self.exit_code()) }
         }
+
+        /// Returns the status the process exits with when this error is thrown.
+        /// The statuses start at 10 so that they can't be confused with 1, which is used for failures that aren't errors
+        /// (like `fmt --check` finding an unformatted file), or with 2, which is used for invalid arguments.
+        pub fn exit_code(&self) -> i32 {
+            match self {
+                Error::FileError => 10,
+                Error::UnknownSymbolError => 11,
+                Error::OpError => 12,
+                Error::AccumulatorError => 13,
+                Error::SyntaxError => 14,
+                Error::InputError => 15,
+                Error::OutOfBoundsError => 16,
+                Error::OverflowError => 17,
+                Error::InfiniteLoopError => 18,
+            }
+        }
+    }
+
+    /// Prints a warning about something that isn't an error but might not do what the user expects.
+    pub(crate) fn warn(msg: &str) {
+        eprintln!("\x1b[33;1mwarning:\x1b[0m {msg}");
     }
 }
\ No newline at end of file
@@ -1,26 +1,71 @@
 #[path="prelude.rs"]
-mod prelude;
+pub(crate) mod prelude;
 pub mod exec {
-    use std::process::exit;
     use std::io;
     use std::io::{stdout, Write};
+    use std::sync::OnceLock;
     use crate::errors::err::*;
     use crate::lexer::lex::*;
     use crate::exec::prelude::prelude::*;
-    pub fn execute(tokens: Vec<Token>, details: bool) {
+    use crate::debugger::debug::State;
+    /// How a program ended: the status the process exits with, and the state of the interpreter when it did.
+    pub struct Halt {
+        pub status: i32,
+        pub acc: Acc,
+        pub tape: Tape,
+        /// The code pointer's index, which is the index of `.` or of the command that threw an error, unless a jump moved it away.
+        pub code_ptr_index: usize,
+        pub data_ptr_index: isize,
+        pub data_ptr_dir: isize,
+    }
+
+    impl Halt {
+        pub(crate) fn new(status: i32, state: &State, code_ptr_index: usize) -> Halt {
+            Halt {
+                status,
+                acc: state.acc.clone(),
+                tape: state.tape.clone(),
+                code_ptr_index,
+                data_ptr_index: state.data_ptr_index,
+                data_ptr_dir: state.data_ptr_dir,
+            }
+        }
+    }
+
+    /// Runs a program until it stops or throws an error, printing its output as it is written and its error and details on standard error,
+    /// and returns how it ended.
+    ///
+    /// `.` ends with status 0, unless `exit_with_acc` is set, in which case it ends with the accumulator's value (or 0 if the accumulator is empty).
+    /// An error ends with the error's [`exit_code`](Error::exit_code).
+    /// `targets` holds the matching `@` of each conditional, as returned by [`match_conditionals`].
+    /// If `detect_loops` is set, the program throws an [`InfiniteLoopError`](Error::InfiniteLoopError) when it returns to
+    /// a state it was in before without reading input in between, as found by a [`LoopDetector`].
+    pub fn execute(tokens: Vec<Token>, targets: &[Option<usize>], details: bool, exit_with_acc: bool, detect_loops: bool) -> Halt {
         let stdin = io::stdin();
         let mut data_ptr_index: isize = 0;
         let mut code_ptr_index: usize = 0;
         let mut data_ptr_dir: isize = 1;
         let mut moving: isize;
-        let mut tape = Tape::new();
+        let mut tape = if detect_loops { Tape::hashed() } else { Tape::new() };
         let mut acc = Acc::new();
         let mut creating_number = false;
+        let mut loops = detect_loops.then(LoopDetector::new);
 
         let mut last_executed: &Token = &Token::Nothing;
         while code_ptr_index < tokens.len() {
             moving = 1;
             let current = tokens.get(code_ptr_index).unwrap();
+            if let Some(loops) = &mut loops {
+                if (current.is_conditional() || matches!(current, Token::JumpCellsC | Token::JumpToCellC))
+                    && let Some(commands) = loops.visit(&tape, &acc, data_ptr_index, data_ptr_dir, code_ptr_index, creating_number) {
+                    Error::InfiniteLoopError.throw(&format!("the program returned to the state it was in at command {code_ptr_index} without reading input, so commands {} to {} repeat forever", commands.start(), commands.end()), false);
+                    return stopped_by(Error::InfiniteLoopError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
+                }
+                if matches!(current, Token::InputInt | Token::InputStr) {
+                    loops.reset();
+                }
+                loops.run(code_ptr_index);
+            }
             creating_number = match *current {
                 Token::CreatingNumber | Token::Digit(_) => creating_number,
                 _ => false
@@ -29,17 +74,29 @@ pub mod exec {
             match *current {
                 Token::NoOp | Token::DestinationIfTrue | Token::Nothing => {}
                 Token::Stop => {
-                    details_success(details, data_ptr_index, code_ptr_index, data_ptr_dir, &mut tape, &mut acc, last_executed, current);
-                    exit(0);
+                    let mut status = 0;
+                    if exit_with_acc && acc.is_not_empty() {
+                        let value = acc.get_value().unwrap();
+                        if !(0..=255).contains(&value) {
+                            Error::OverflowError.throw(&format!("exit status {value} is not between 0 and 255"), false);
+                            return stopped_by(Error::OverflowError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
+                        }
+                        status = value as i32;
+                    }
+                    if details {
+                        println!();
+                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
+                    }
+                    return Halt { status, acc, tape, code_ptr_index, data_ptr_index, data_ptr_dir }
                 }
                 Token::CreatingNumber => {
                     if creating_number {
                         Error::SyntaxError.throw("already creating number", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::SyntaxError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if acc.is_not_empty() {
                         Error::AccumulatorError.throw("execution of `#` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     creating_number = true;
                     moving = 0;
@@ -47,37 +104,38 @@ pub mod exec {
                 Token::Digit(n) => {
                     if !creating_number {
                         Error::SyntaxError.throw(&format!("execution of `{n}` went wrong"), false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::SyntaxError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
+                    }
+                    if acc.append(n).is_err() {
+                        return stopped_by(Error::OverflowError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
-                    let success = acc.append(n);
-                    if success.is_err() { print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true); }
                     moving = 0;
                 }
                 Token::Unload => {
                     if acc.is_empty() {
                         Error::AccumulatorError.throw("execution of `U` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     tape.set(data_ptr_index, acc.clear().unwrap());
                 }
                 Token::Distribute => {
                     if acc.is_empty() {
                         Error::AccumulatorError.throw("execution of `u` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     tape.set(data_ptr_index, acc.get_value().unwrap())
                 }
                 Token::Recall => {
                     if tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw("execution of `R` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     acc.set_value(tape.clear(data_ptr_index).unwrap())
                 }
                 Token::Copy => {
                     if tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw("execution of `r` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     acc.set_value(tape.get(data_ptr_index).unwrap())
                 }
@@ -89,10 +147,10 @@ pub mod exec {
                 }
                 Token::ZeroOrEmpty => {
                     if tape.cell_is_empty(data_ptr_index) || tape.get(data_ptr_index).unwrap() == 0 {
-                        let result = scope_check(code_ptr_index, &tokens);
+                        let result = targets[code_ptr_index];
                         if result.is_none() {
                             Error::SyntaxError.throw("conditional `?` does not have a corresponding `@`", false);
-                            print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                            return stopped_by(Error::SyntaxError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                         } else {
                             code_ptr_index = result.unwrap();
                         }
@@ -100,10 +158,10 @@ pub mod exec {
                 }
                 Token::NotZeroOrEmpty => {
                     if tape.cell_is_full(data_ptr_index) && tape.get(data_ptr_index).unwrap() != 0 {
-                        let result = scope_check(code_ptr_index, &tokens);
+                        let result = targets[code_ptr_index];
                         if result.is_none() {
                             Error::SyntaxError.throw("conditional `!` does not have a corresponding `@`", false);
-                            print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                            return stopped_by(Error::SyntaxError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                         } else {
                             code_ptr_index = result.unwrap();
                         }
@@ -112,14 +170,14 @@ pub mod exec {
                 Token::TgtZeroOrEmpty => {
                     if acc.is_empty() {
                         Error::AccumulatorError.throw("execution of `T` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     let pos = tape.get(acc.get_value().unwrap());
                     if pos.is_none() || pos.unwrap() == 0 {
-                        let result = scope_check(code_ptr_index, &tokens);
+                        let result = targets[code_ptr_index];
                         if result.is_none() {
                             Error::SyntaxError.throw("conditional `T` does not have a corresponding `@`", false);
-                            print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                            return stopped_by(Error::SyntaxError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                         } else {
                             code_ptr_index = result.unwrap();
                         }
@@ -129,14 +187,14 @@ pub mod exec {
                 Token::TgtNotZeroOrEmpty => {
                     if acc.is_empty() {
                         Error::AccumulatorError.throw("execution of `t` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     let pos = tape.get(acc.get_value().unwrap());
                     if pos.is_some() && pos.unwrap() != 0 {
-                        let result = scope_check(code_ptr_index, &tokens);
+                        let result = targets[code_ptr_index];
                         if result.is_none() {
                             Error::SyntaxError.throw("conditional `t` does not have a corresponding `@`", false);
-                            print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                            return stopped_by(Error::SyntaxError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                         } else {
                             code_ptr_index = result.unwrap();
                         }
@@ -145,10 +203,10 @@ pub mod exec {
                 }
                 Token::AccZeroOrEmpty => {
                     if acc.is_empty() || acc.get_value().unwrap() == 0 {
-                        let result = scope_check(code_ptr_index, &tokens);
+                        let result = targets[code_ptr_index];
                         if result.is_none() {
                             Error::SyntaxError.throw("conditional `A` does not have a corresponding `@`", false);
-                            print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                            return stopped_by(Error::SyntaxError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                         } else {
                             code_ptr_index = result.unwrap();
                         }
@@ -156,10 +214,10 @@ pub mod exec {
                 }
                 Token::AccNotZeroOrEmpty => {
                     if !acc.is_empty() && acc.get_value().unwrap() != 0 {
-                        let result = scope_check(code_ptr_index, &tokens);
+                        let result = targets[code_ptr_index];
                         if result.is_none() {
                             Error::SyntaxError.throw("conditional `a` does not have a corresponding `@`", false);
-                            print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                            return stopped_by(Error::SyntaxError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                         } else {
                             code_ptr_index = result.unwrap();
                         }
@@ -168,41 +226,41 @@ pub mod exec {
                 Token::JumpCellsC => {
                     if acc.is_empty() {
                         Error::OpError.throw("execution of `J` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
 
                     let will_overflow = if acc.get_value().unwrap() < 0 {
-                        code_ptr_index.overflowing_sub((-acc.clear().unwrap()) as usize)
+                        code_ptr_index.overflowing_sub(acc.clear().unwrap().unsigned_abs())
                     } else {
                         code_ptr_index.overflowing_add(acc.clear().unwrap() as usize)
                     };
 
                     if will_overflow.1 || will_overflow.0 >= tokens.len() {
                         Error::OverflowError.throw("code pointer went out of bounds when executing `J`", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OverflowError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     code_ptr_index = will_overflow.0
                 }
                 Token::JumpToCellC => {
                     if acc.is_empty() {
                         Error::OpError.throw("execution of `j` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if acc.get_value().unwrap() >= tokens.len() as isize || acc.get_value().unwrap() < 0 {
                         Error::OverflowError.throw("code pointer went out of bounds when executing `j`", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OverflowError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     code_ptr_index = acc.clear().unwrap() as usize;
                 }
                 Token::JumpCellsD => {
                     if acc.is_empty() {
                         Error::OpError.throw("execution of `K` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     let will_overflow = data_ptr_index.overflowing_add(acc.clear().unwrap());
                     if will_overflow.1 {
                         Error::OverflowError.throw("data pointer went out of bounds when executing `K`", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OverflowError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     data_ptr_index = will_overflow.0;
                     moving = 0
@@ -210,7 +268,7 @@ pub mod exec {
                 Token::JumpToCellD => {
                     if acc.is_empty() {
                         Error::OpError.throw("execution of `k` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     data_ptr_index = acc.clear().unwrap();
                     moving = 0
@@ -220,20 +278,20 @@ pub mod exec {
                         let will_overflow = data_ptr_index.overflowing_add(data_ptr_dir);
                         if will_overflow.1 {
                             Error::OverflowError.throw("data pointer went out of bounds when executing `M`", false);
-                            print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                            return stopped_by(Error::OverflowError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                         }
                         data_ptr_index = will_overflow.0
                     }
                     moving = 0
                 }
                 Token::MoveDUntilFull => {
-                    while tape.cell_is_empty(data_ptr_index) {
-                        let will_overflow = data_ptr_index.overflowing_add(data_ptr_dir);
-                        if will_overflow.1 {
+                    match tape.next_full(data_ptr_index, data_ptr_dir) {
+                        Some(index) => data_ptr_index = index,
+                        None => {
+                            data_ptr_index = if data_ptr_dir > 0 { isize::MAX } else { isize::MIN };
                             Error::OverflowError.throw("data pointer went out of bounds when executing `m`", false);
-                            print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                            return stopped_by(Error::OverflowError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                         }
-                        data_ptr_index = will_overflow.0
                     }
                     moving = 0
                 }
@@ -243,192 +301,197 @@ pub mod exec {
                 Token::Add => {
                     if tape.left_of(data_ptr_index).is_err() || tape.left_of(data_ptr_index).unwrap().is_none() || tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw("execution of `+` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if acc.is_not_empty() {
                         Error::AccumulatorError.throw("execution of `+` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     let result = tape.get(data_ptr_index - 1).unwrap().overflowing_add(tape.get(data_ptr_index).unwrap());
                     if result.1 {
                         Error::OverflowError.throw("command `+` caused overflow", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OverflowError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     acc.set_value(result.0)
                 }
                 Token::Neg => {
                     if acc.is_empty() {
                         Error::AccumulatorError.throw("execution of `-` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
-                    acc.set_value(-acc.get_value().unwrap())
+                    let result = acc.get_value().unwrap().overflowing_neg();
+                    if result.1 {
+                        Error::OverflowError.throw("command `-` caused overflow", false);
+                        return stopped_by(Error::OverflowError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
+                    }
+                    acc.set_value(result.0)
                 }
                 Token::Mul => {
                     if tape.left_of(data_ptr_index).is_err() || tape.left_of(data_ptr_index).unwrap().is_none() || tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw("execution of `*` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if acc.is_not_empty() {
                         Error::AccumulatorError.throw("execution of `*` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     let result = tape.get(data_ptr_index - 1).unwrap().overflowing_mul(tape.get(data_ptr_index).unwrap());
                     if result.1 {
                         Error::OverflowError.throw("command `*` caused overflow", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OverflowError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     acc.set_value(result.0)
                 }
                 Token::Div => {
                     if tape.left_of(data_ptr_index).is_err() || tape.left_of(data_ptr_index).unwrap().is_none() || tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw("execution of `/` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if tape.get(data_ptr_index).unwrap() == 0 {
                         Error::OpError.throw("division by zero caused by `/`", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if acc.is_not_empty() {
                         Error::AccumulatorError.throw("execution of `/` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     let result = tape.get(data_ptr_index - 1).unwrap().overflowing_div_euclid(tape.get(data_ptr_index).unwrap());
                     if result.1 {
                         Error::OverflowError.throw("command `/` caused overflow", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OverflowError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     acc.set_value(result.0)
                 }
                 Token::Mod => {
                     if tape.left_of(data_ptr_index).is_err() || tape.left_of(data_ptr_index).unwrap().is_none() || tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw("execution of `%` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if tape.get(data_ptr_index).unwrap() == 0 {
                         Error::OpError.throw("division by zero caused by `%`", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if acc.is_not_empty() {
                         Error::AccumulatorError.throw("execution of `%` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     let result = tape.get(data_ptr_index - 1).unwrap().overflowing_rem(tape.get(data_ptr_index).unwrap());
                     if result.1 {
                         Error::OverflowError.throw("command `%` caused overflow", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OverflowError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     acc.set_value(result.0)
                 }
                 Token::Eq => {
                     if tape.left_of(data_ptr_index).is_err() || tape.left_of(data_ptr_index).unwrap().is_none() || tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw("execution of `=` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if acc.is_not_empty() {
                         Error::AccumulatorError.throw("execution of `=` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     acc.set_value(isize::from(tape.get(data_ptr_index - 1).unwrap() == tape.get(data_ptr_index).unwrap()))
                 }
                 Token::NotEq(n) => {
                     if tape.left_of(data_ptr_index).is_err() || tape.left_of(data_ptr_index).unwrap().is_none() || tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw(&format!("execution of `{}` went wrong", if n {'N'} else {'n'}), false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if acc.is_not_empty() {
                         Error::AccumulatorError.throw(&format!("execution of `{}` went wrong", if n {'N'} else {'n'}), false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     acc.set_value(isize::from(tape.get(data_ptr_index - 1).unwrap() != tape.get(data_ptr_index).unwrap()))
                 }
                 Token::Gt => {
                     if tape.left_of(data_ptr_index).is_err() || tape.left_of(data_ptr_index).unwrap().is_none() || tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw("execution of `>` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if acc.is_not_empty() {
                         Error::AccumulatorError.throw("execution of `>` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     acc.set_value(isize::from(tape.get(data_ptr_index - 1).unwrap() > tape.get(data_ptr_index).unwrap()))
                 }
                 Token::GE(n) => {
                     if tape.left_of(data_ptr_index).is_err() || tape.left_of(data_ptr_index).unwrap().is_none() || tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw(&format!("execution of `{}` went wrong", if n {'G'} else {'g'}), false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if acc.is_not_empty() {
                         Error::AccumulatorError.throw(&format!("execution of `{}` went wrong", if n {'G'} else {'g'}), false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     acc.set_value(isize::from(tape.get(data_ptr_index - 1).unwrap() >= tape.get(data_ptr_index).unwrap()))
                 }
                 Token::Lt => {
                     if tape.left_of(data_ptr_index).is_err() || tape.left_of(data_ptr_index).unwrap().is_none() || tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw("execution of `<` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if acc.is_not_empty() {
                         Error::AccumulatorError.throw("execution of `<` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     acc.set_value(isize::from(tape.get(data_ptr_index - 1).unwrap() < tape.get(data_ptr_index).unwrap()))
                 }
                 Token::LE(n) => {
                     if tape.left_of(data_ptr_index).is_err() || tape.left_of(data_ptr_index).unwrap().is_none() || tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw(&format!("execution of `{}` went wrong", if n {'L'} else {'l'}), false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if acc.is_not_empty() {
                         Error::AccumulatorError.throw(&format!("execution of `{}` went wrong", if n {'L'} else {'l'}), false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     acc.set_value(isize::from(tape.get(data_ptr_index - 1).unwrap() <= tape.get(data_ptr_index).unwrap()))
                 }
                 Token::BitAnd => {
                     if tape.left_of(data_ptr_index).is_err() || tape.left_of(data_ptr_index).unwrap().is_none() || tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw("execution of `&` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if acc.is_not_empty() {
                         Error::AccumulatorError.throw("execution of `&` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     acc.set_value(tape.get(data_ptr_index - 1).unwrap() & tape.get(data_ptr_index).unwrap())
                 }
                 Token::BitOr => {
                     if tape.left_of(data_ptr_index).is_err() || tape.left_of(data_ptr_index).unwrap().is_none() || tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw("execution of `|` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if acc.is_not_empty() {
                         Error::AccumulatorError.throw("execution of `|` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     acc.set_value(tape.get(data_ptr_index - 1).unwrap() | tape.get(data_ptr_index).unwrap())
                 }
                 Token::BitNot => {
                     if tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw("execution of `~` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     acc.set_value(!tape.get(data_ptr_index).unwrap())
                 }
                 Token::BitXor => {
                     if tape.left_of(data_ptr_index).is_err() || tape.left_of(data_ptr_index).unwrap().is_none() || tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw("execution of `^` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if acc.is_not_empty() {
                         Error::AccumulatorError.throw("execution of `^` went wrong", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::AccumulatorError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     acc.set_value(tape.get(data_ptr_index - 1).unwrap() ^ tape.get(data_ptr_index).unwrap())
                 }
                 Token::OutputInt => {
                     if tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw("something went wrong while executing `i`", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     print!("{}", tape.get(data_ptr_index).unwrap());
                     stdout().flush().unwrap();
@@ -437,17 +500,17 @@ pub mod exec {
                     // throw if the current cell is empty
                     if tape.cell_is_empty(data_ptr_index) {
                         Error::OpError.throw("something went wrong while executing `s`", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
 
                     // check if the current cell's value can be represented as a UTF-8 character
                     if u32::try_from(tape.get(data_ptr_index).unwrap()).is_err() {
                         Error::OpError.throw("the current cell's value cannot be represented as a valid UTF-8 character", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
                     if char::from_u32(tape.get(data_ptr_index).unwrap() as u32).is_none() {
                         Error::OpError.throw("the current cell's value cannot be represented as a valid UTF-8 character", false);
-                        print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                        return stopped_by(Error::OpError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                     }
 
                     // print the character out
@@ -462,14 +525,16 @@ pub mod exec {
                     // check 1: does the input have any invalid characters?
                     let input_valid_1 = stdin.read_line(&mut input);
                     if let Err(_) = input_valid_1 {
-                        Error::InputError.throw("invalid input", true);
+                        Error::InputError.throw("invalid input", false);
+                        return Halt { status: Error::InputError.exit_code(), acc, tape, code_ptr_index, data_ptr_index, data_ptr_dir }
                     }
                     input.pop();
 
                     // check 2: can the input be represented as a sized integer?
                     let input_valid_2 = input.parse::<isize>();
                     if let Err(_) = input_valid_2 {
-                        Error::InputError.throw("invalid input", true);
+                        Error::InputError.throw("invalid input", false);
+                        return Halt { status: Error::InputError.exit_code(), acc, tape, code_ptr_index, data_ptr_index, data_ptr_dir }
                     }
 
                     // set the accumulator's value to the input
@@ -483,7 +548,8 @@ pub mod exec {
                     // check 1: does the input have any invalid characters?
                     let input_valid_1 = stdin.read_line(&mut input);
                     if let Err(_) = input_valid_1 {
-                        Error::InputError.throw("invalid input", true);
+                        Error::InputError.throw("invalid input", false);
+                        return Halt { status: Error::InputError.exit_code(), acc, tape, code_ptr_index, data_ptr_index, data_ptr_dir }
                     }
                     input.pop();
 
@@ -494,7 +560,7 @@ pub mod exec {
                         tape.set(curr, chr as isize);
                         if curr.overflowing_add(1).1 {
                             Error::InputError.throw("input too long; went beyond tape boundaries", false);
-                            print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                            return stopped_by(Error::InputError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
                         }
                         curr += 1;
                     }
@@ -504,48 +570,32 @@ pub mod exec {
             let out_of_bounds_d = data_ptr_index.overflowing_add(1 * moving * data_ptr_dir).1;
             if out_of_bounds_c || code_ptr_index + 1 >= tokens.len() {
                 Error::OutOfBoundsError.throw("code pointer went out of bounds", false);
-                print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                return stopped_by(Error::OutOfBoundsError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
             }
             if out_of_bounds_d {
                 Error::OutOfBoundsError.throw("data pointer went out of bounds", false);
-                print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, true);
+                return stopped_by(Error::OutOfBoundsError, &acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
             }
             last_executed = current;
             code_ptr_index += 1;
             data_ptr_index += moving * data_ptr_dir
         }
+        // only an empty program gets here, since running off the end of any other is an error
+        Halt { status: 0, acc, tape, code_ptr_index, data_ptr_index, data_ptr_dir }
     }
 
-    fn details_success(details: bool, data_ptr_index: isize, code_ptr_index: usize, data_ptr_dir: isize, tape: &mut Tape, acc: &mut Acc, last_executed: &Token, current: &Token) {
-        if details {
-            println!();
-            print_details(&acc, &tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed, false);
-            exit(0)
-        }
+    /// Prints the details of a program that threw an error, which was already reported, and returns how it ended.
+    fn stopped_by(error: Error, acc: &Acc, tape: &Tape, data_ptr_index: isize, data_ptr_dir: isize, code_ptr_index: usize, current: &Token, last_executed: &Token) -> Halt {
+        print_details(acc, tape, data_ptr_index, data_ptr_dir, code_ptr_index, current, last_executed);
+        Halt { status: error.exit_code(), acc: acc.clone(), tape: tape.clone(), code_ptr_index, data_ptr_index, data_ptr_dir }
     }
 
-    fn scope_check(code_ptr_index: usize, tokens: &Vec<Token>) -> Option<usize> {
-        let mut destination = code_ptr_index;
-        let mut scope = 0;
-        while destination < tokens.len() {
-            scope += match *tokens.iter().nth(destination)? {
-                Token::ZeroOrEmpty | Token::NotZeroOrEmpty | Token::TgtZeroOrEmpty | Token::TgtNotZeroOrEmpty | Token::AccZeroOrEmpty | Token::AccNotZeroOrEmpty => {
-                    1
-                }
-                Token::DestinationIfTrue => {
-                    -1
-                }
-                _ => 0
-            };
-            if scope == 0 {
-                return Some(destination)
-            }
-            destination += 1;
-        }
-        None
-    }
+    /// Describes where the command at an index was written, if the program was expanded from macros or includes.
+    /// [`print_details`] adds the description as a note, since the code pointer's index alone doesn't find the command in the source.
+    pub(crate) static COMMAND_ORIGIN: OnceLock<Box<dyn Fn(usize) -> Option<String> + Send + Sync>> = OnceLock::new();
 
-    pub(crate) fn print_details(acc: &Acc, tape: &Tape, data_ptr_index: isize, data_ptr_dir: isize, code_ptr_index: usize, current_command: &Token, last_executed: &Token, terminate: bool) {
+    /// Prints the state of the interpreter.
+    pub(crate) fn print_details(acc: &Acc, tape: &Tape, data_ptr_index: isize, data_ptr_dir: isize, code_ptr_index: usize, current_command: &Token, last_executed: &Token) {
         eprintln!("\x1b[33;1m[Details]\x1b[0m
 \x1b[1m*\x1b[0m {}
 \x1b[1m*\x1b[0m code pointer index: {code_ptr_index}
@@ -574,6 +624,8 @@ pub mod exec {
                       "<cell does not exist>".to_string()
                   }
         );
-        if terminate { exit(1) }
+        if let Some(describe) = COMMAND_ORIGIN.get() && let Some(description) = describe(code_ptr_index) {
+            eprintln!("\x1b[36;1mnote:\x1b[0m {description}");
+        }
     }
 }
\ No newline at end of file
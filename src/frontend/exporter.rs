@@ -0,0 +1,187 @@
+pub mod html {
+    use crate::lexer::lex::*;
+    use crate::preprocessor::macros::Expansion;
+
+    const STYLE: &str = r#"body { font-family: sans-serif; margin: 2em; }
+.legend span { margin-right: 1em; font-family: monospace; }
+.program { position: relative; }
+.program pre { margin: 0; padding: 0.5em 0.5em 0.5em 3em; font-size: 1.1em; line-height: 1.6; background: #fafafa; border: 1px solid #ddd; }
+.program svg { position: absolute; top: 0; left: 0; width: 100%; height: 100%; pointer-events: none; }
+.program path { fill: none; stroke-width: 1.5; opacity: 0.45; }
+.program path.active { stroke-width: 2.5; opacity: 1; }
+.cmd.active { outline: 2px solid currentColor; }
+.depth-0 { stroke: #af00db; } .depth-1 { stroke: #0451a5; } .depth-2 { stroke: #098658; } .depth-3 { stroke: #e36209; }
+.literal { color: #098658; }
+.memory { color: #0451a5; }
+.arithmetic { color: #795e26; }
+.conditional { color: #af00db; font-weight: bold; }
+.jump { color: #d7263d; font-weight: bold; }
+.pointer-motion { color: #e36209; }
+.io { color: #267f99; }
+.control { color: #555555; }
+.comment { color: #6a737d; font-style: italic; }
+.directive { color: #001080; background: #eef3fb; }"#;
+
+    /// Draws a line from every conditional to its matching `@`, and highlights both ends of a pair while either is hovered.
+    const SCRIPT: &str = r#"const program = document.querySelector(".program");
+const svg = program.querySelector("svg");
+function draw() {
+    svg.replaceChildren();
+    const origin = program.getBoundingClientRect();
+    for (const open of program.querySelectorAll(".conditional[data-match]")) {
+        if (+open.dataset.match < +open.dataset.index) continue;
+        const close = program.querySelector(`[data-index="${open.dataset.match}"]`);
+        // the `@` is inside a macro, where it isn't shown
+        if (!close) continue;
+        const a = open.getBoundingClientRect(), b = close.getBoundingClientRect();
+        const x1 = a.left + a.width / 2 - origin.left, y1 = a.bottom - origin.top;
+        const x2 = b.left + b.width / 2 - origin.left, y2 = b.bottom - origin.top;
+        let d;
+        if (Math.abs(a.top - b.top) < 1) {
+            d = `M${x1},${y1} C${x1},${y1 + 8} ${x2},${y2 + 8} ${x2},${y2}`;
+        } else {
+            const gutter = 6 + 6 * +open.dataset.depth;
+            d = `M${a.left - origin.left},${a.top + a.height / 2 - origin.top} H${gutter} V${b.top + b.height / 2 - origin.top} H${b.left - origin.left}`;
+        }
+        const path = document.createElementNS("http://www.w3.org/2000/svg", "path");
+        path.setAttribute("d", d);
+        path.setAttribute("class", `depth-${open.dataset.depth % 4}`);
+        path.dataset.pair = open.dataset.index;
+        svg.appendChild(path);
+    }
+}
+function highlight(event, on) {
+    const cmd = event.target.closest("[data-match]");
+    if (!cmd) return;
+    const open = Math.min(+cmd.dataset.index, +cmd.dataset.match);
+    for (const el of program.querySelectorAll(`[data-index="${cmd.dataset.index}"], [data-index="${cmd.dataset.match}"], path[data-pair="${open}"]`)) {
+        el.classList.toggle("active", on);
+    }
+}
+program.addEventListener("mouseover", e => highlight(e, true));
+program.addEventListener("mouseout", e => highlight(e, false));
+window.addEventListener("resize", draw);
+draw();"#;
+
+    const CATEGORIES: [Category; 8] = [
+        Category::Literal,
+        Category::Memory,
+        Category::Arithmetic,
+        Category::Conditional,
+        Category::Jump,
+        Category::PointerMotion,
+        Category::Io,
+        Category::Control,
+    ];
+
+    fn class(category: Category) -> &'static str {
+        match category {
+            Category::Literal => "literal",
+            Category::Memory => "memory",
+            Category::Arithmetic => "arithmetic",
+            Category::Conditional => "conditional",
+            Category::Jump => "jump",
+            Category::PointerMotion => "pointer-motion",
+            Category::Io => "io",
+            Category::Control => "control",
+        }
+    }
+
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+    }
+
+    /// Renders a program as a standalone HTML page, keeping the layout of the source.
+    ///
+    /// Every command is coloured by its [`Category`] and shows its index on hover,
+    /// and a line is drawn from every conditional to its matching `@`. Macro directives are shown as they are written, without being expanded,
+    /// and a macro call or label reference shows the commands it expands into on hover.
+    /// The indices are those of `tokens`, the commands of the expanded program, so a command keeps the index it runs at.
+    pub fn export_html(src: &str, title: &str, tokens: &[Token], expansion: &Expansion) -> String {
+        let matches = match_conditionals(tokens);
+        let mut depths = vec![0; tokens.len()];
+        let mut depth = 0;
+        for (index, &token) in tokens.iter().enumerate() {
+            if matches[index].is_none() {
+                continue
+            }
+            if token == Token::DestinationIfTrue {
+                depth -= 1;
+            }
+            depths[index] = depth;
+            if token != Token::DestinationIfTrue {
+                depth += 1;
+            }
+        }
+        let commands = expansion.written_commands();
+        let directives = expansion.written_directives();
+        let line_starts: Vec<usize> = std::iter::once(0).chain(src.match_indices('\n').map(|(offset, _)| offset + 1)).collect();
+        let position = |offset: usize| {
+            let line = line_starts.partition_point(|&start| start <= offset);
+            (line, src[line_starts[line - 1]..offset].chars().count() + 1)
+        };
+
+        let mut body = "".to_string();
+        let mut prev_end = 0;
+        for lexeme in scan_all(src) {
+            body.push_str(&escape(&src[prev_end..lexeme.span.start]));
+            prev_end = lexeme.span.end;
+            let text = escape(&src[lexeme.span.clone()]);
+            let at = position(lexeme.span.start);
+            let (token, index) = match (lexeme.kind, commands.get(&at)) {
+                (LexemeKind::Command(token), Some(&index)) => (token, index),
+                (LexemeKind::Comment, _) => {
+                    body.push_str(&format!("<span class=\"comment\">{text}</span>"));
+                    continue
+                }
+                (LexemeKind::Directive, _) => {
+                    match directives.get(&at) {
+                        Some(commands) if commands.is_empty() => body.push_str(&format!("<span class=\"directive\" title=\"expands into no commands\">{text}</span>")),
+                        Some(commands) => body.push_str(&format!("<span class=\"directive\" title=\"expands into commands {} to {}\">{text}</span>", commands.start, commands.end - 1)),
+                        None => body.push_str(&format!("<span class=\"directive\">{text}</span>"))
+                    }
+                    continue
+                }
+                // every command written outside of the directives is in the expansion, so this is never reached
+                _ => {
+                    body.push_str(&text);
+                    continue
+                }
+            };
+            let mut attributes = format!("class=\"cmd {}\" data-index=\"{index}\"", class(token.category()));
+            let mut title = format!("command {index}");
+            if let Some(partner) = matches[index] {
+                attributes.push_str(&format!(" data-match=\"{partner}\" data-depth=\"{}\"", depths[index]));
+                title.push_str(&format!(", matches command {partner}"));
+            }
+            body.push_str(&format!("<span {attributes} title=\"{title}\">{text}</span>"));
+        }
+        body.push_str(&escape(&src[prev_end..]));
+
+        let legend: Vec<String> = CATEGORIES.iter()
+            .map(|&category| format!("<span class=\"{0}\">{1}</span>", class(category), class(category).replace('-', " ")))
+            .collect();
+        format!("<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{title}</title>
+<style>
+{STYLE}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p class=\"legend\">{} <span class=\"comment\">comment</span> <span class=\"directive\">macro</span></p>
+<div class=\"program\">
+<pre>{body}</pre>
+<svg></svg>
+</div>
+<script>
+{SCRIPT}
+</script>
+</body>
+</html>
+", legend.join(" "), title = escape(title))
+    }
+}
@@ -0,0 +1,126 @@
+pub mod fmt {
+    use crate::lexer::lex::*;
+
+    const INDENT: &str = "    ";
+
+    /// Builds the formatted output line by line.
+    struct Layout {
+        lines: Vec<String>,
+        line: String,
+        line_depth: usize,
+        depth: usize,
+        width: usize,
+        break_pending: bool,
+    }
+
+    impl Layout {
+        /// Adds a word to the current line, starting a new line first if the word would not fit.
+        /// Words are never split, so a word longer than the maximum width gets a line of its own.
+        fn word(&mut self, word: &str) {
+            if self.break_pending {
+                self.end_line();
+            }
+            if !self.line.is_empty() && self.line_depth * INDENT.len() + self.line.len() + word.len() > self.width {
+                self.end_line();
+            }
+            if self.line.is_empty() {
+                self.line_depth = self.depth;
+            }
+            self.line.push_str(word);
+        }
+
+        fn end_line(&mut self) {
+            if !self.line.is_empty() {
+                self.lines.push(INDENT.repeat(self.line_depth) + &self.line);
+                self.line.clear();
+            }
+            self.break_pending = false;
+        }
+
+        fn blank_line(&mut self) {
+            self.end_line();
+            if self.lines.last().is_some_and(|l| !l.is_empty()) {
+                self.lines.push("".to_string());
+            }
+        }
+
+        fn comment(&mut self, text: &str, trailing: bool) {
+            if trailing && !self.line.is_empty() {
+                self.line.push(' ');
+                self.line.push_str(text);
+            } else {
+                self.end_line();
+                self.lines.push(INDENT.repeat(self.depth) + text);
+            }
+            self.end_line();
+        }
+    }
+
+    /// Re-emits a program in its canonical layout.
+    ///
+    /// Commands are packed onto lines no wider than `width` (not counting comments), and number literals (`#` followed by its digits) are never split.
+    /// Every conditional ends its line and indents the lines up to its matching `@`, which gets a line of its own.
+    /// Comments are kept: a comment that trailed a command in the source stays at the end of that line, and every other comment gets a line of its own.
+    /// Blank lines are kept, but runs of them are collapsed into one.
+    /// Macro directives are kept as they are written, like words, or on lines of their own if they span several lines.
+    ///
+    /// Since whitespace is ignored, the output tokenizes to the same commands as the source.
+    /// If an unrecognized symbol is found, an [`Err`] containing the symbol and its command index is returned.
+    pub fn format_source(src: &str, width: usize) -> Result<String, (char, usize)> {
+        let lexemes = scan(src)?;
+        let mut layout = Layout {
+            lines: vec![],
+            line: "".to_string(),
+            line_depth: 0,
+            depth: 0,
+            width,
+            break_pending: false,
+        };
+
+        let mut iter = lexemes.iter().peekable();
+        let mut prev_end: Option<usize> = None;
+        while let Some(lexeme) = iter.next() {
+            let newlines = prev_end.map_or(0, |end| src[end..lexeme.span.start].matches('\n').count());
+            if newlines >= 2 {
+                layout.blank_line();
+            }
+            let mut end = lexeme.span.end;
+            match lexeme.kind {
+                LexemeKind::Comment => layout.comment(&src[lexeme.span.clone()], prev_end.is_some() && newlines == 0),
+                // a directive spanning several lines, like a long `{define}`, keeps its lines as written
+                LexemeKind::Directive if src[lexeme.span.clone()].contains('\n') => layout.comment(&src[lexeme.span.clone()], false),
+                LexemeKind::Directive => layout.word(&src[lexeme.span.clone()]),
+                LexemeKind::Command(token @ (Token::CreatingNumber | Token::Digit(_))) => {
+                    let mut literal = token_to_symbol(&token).to_string();
+                    while let Some(next) = iter.next_if(|l| matches!(l.kind, LexemeKind::Command(Token::Digit(_)))) {
+                        if let LexemeKind::Command(digit) = next.kind {
+                            literal.push_str(token_to_symbol(&digit));
+                        }
+                        end = next.span.end;
+                    }
+                    layout.word(&literal);
+                }
+                LexemeKind::Command(token) if token.is_conditional() => {
+                    layout.word(token_to_symbol(&token));
+                    layout.depth += 1;
+                    layout.break_pending = true;
+                }
+                LexemeKind::Command(Token::DestinationIfTrue) if layout.depth > 0 => {
+                    layout.end_line();
+                    layout.depth -= 1;
+                    layout.word("@");
+                    layout.break_pending = true;
+                }
+                LexemeKind::Command(token) => layout.word(token_to_symbol(&token)),
+                LexemeKind::Unknown(_) => unreachable!("scan fails on unrecognized symbols"),
+            }
+            prev_end = Some(end);
+        }
+        layout.end_line();
+
+        if layout.lines.is_empty() {
+            return Ok("".to_string())
+        }
+        Ok(layout.lines.join("\n") + "\n")
+    }
+}
@@ -0,0 +1,178 @@
+pub mod cfg {
+    use std::collections::BTreeSet;
+    use std::fmt::Write;
+    use crate::errors::err::Error;
+    use crate::lexer::lex::*;
+
+    /// A run of commands that is only entered at its first command and only left after its last.
+    pub struct Block {
+        pub start: usize,
+        /// The index after the block's last command.
+        pub end: usize,
+    }
+
+    /// Where control goes when it leaves a block.
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    pub enum Destination {
+        /// The index of a block.
+        Block(usize),
+        /// A jump whose target depends on a value only known at runtime.
+        Computed,
+        /// An error that is certain to be thrown on the way, like a jump out of the program.
+        Error(Error),
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    pub enum EdgeKind {
+        /// The block ends because the next command starts another block.
+        Next,
+        /// A conditional jumping past its matching `@`.
+        Taken,
+        /// A conditional not jumping.
+        NotTaken,
+        /// `J` or `j` with the value of the number literal right before it, which is always the value it jumps with, as found by [`static_jumps`].
+        StaticJump(isize),
+        /// `J` or `j` with a value that isn't known until runtime.
+        ComputedJump,
+    }
+
+    pub struct Edge {
+        pub from: usize,
+        pub to: Destination,
+        pub kind: EdgeKind,
+    }
+
+    pub struct Graph {
+        pub blocks: Vec<Block>,
+        pub edges: Vec<Edge>,
+    }
+
+    /// Returns the index of the next command run after a jump at `index` with `value`, or the error the jump throws.
+    /// Like the interpreter, a jump moves the code pointer to its target, and the command after the target runs next.
+    fn jump_target(tokens: &[Token], index: usize, value: isize) -> Result<usize, Error> {
+        let target = match tokens[index] {
+            Token::JumpCellsC => index.checked_add_signed(value),
+            _ => usize::try_from(value).ok()
+        };
+        match target {
+            Some(target) if target < tokens.len() => next(tokens, target),
+            _ => Err(Error::OverflowError)
+        }
+    }
+
+    /// Returns the index of the command after `index`, or an error if `index` is the last command.
+    fn next(tokens: &[Token], index: usize) -> Result<usize, Error> {
+        Some(index + 1).filter(|&next| next < tokens.len()).ok_or(Error::OutOfBoundsError)
+    }
+
+    /// Splits a program into basic blocks at conditionals, the commands run after their matching `@`s, `J`, `j` and `.`,
+    /// and connects them with the ways control can flow between them.
+    pub fn build_graph(tokens: &[Token]) -> Graph {
+        let targets = match_conditionals(tokens);
+        let jumps = static_jumps(tokens);
+        let mut leaders = BTreeSet::new();
+        if !tokens.is_empty() {
+            leaders.insert(0);
+        }
+        for (index, token) in tokens.iter().enumerate() {
+            let ends_block = token.is_conditional() || matches!(token, Token::JumpCellsC | Token::JumpToCellC | Token::Stop);
+            if ends_block && let Ok(next) = next(tokens, index) {
+                leaders.insert(next);
+            }
+            if token.is_conditional() && let Some(Ok(next)) = targets[index].map(|target| next(tokens, target)) {
+                leaders.insert(next);
+            }
+            if matches!(token, Token::JumpCellsC | Token::JumpToCellC) && let Some(Ok(next)) = jumps[index].map(|value| jump_target(tokens, index, value)) {
+                leaders.insert(next);
+            }
+        }
+
+        let starts: Vec<usize> = leaders.into_iter().collect();
+        let blocks: Vec<Block> = starts.iter().enumerate()
+            .map(|(block, &start)| Block { start, end: starts.get(block + 1).copied().unwrap_or(tokens.len()) })
+            .collect();
+        let destination = |next: Result<usize, Error>| match next {
+            Ok(next) => Destination::Block(starts.binary_search(&next).unwrap()),
+            Err(error) => Destination::Error(error)
+        };
+        let mut edges = vec![];
+        for (from, block) in blocks.iter().enumerate() {
+            let last = block.end - 1;
+            let token = tokens[last];
+            if token == Token::Stop {
+                continue
+            }
+            if token.is_conditional() {
+                let taken = targets[last].map_or(Err(Error::SyntaxError), |target| next(tokens, target));
+                edges.push(Edge { from, to: destination(taken), kind: EdgeKind::Taken });
+                edges.push(Edge { from, to: destination(next(tokens, last)), kind: EdgeKind::NotTaken });
+            } else if matches!(token, Token::JumpCellsC | Token::JumpToCellC) {
+                edges.push(match jumps[last] {
+                    Some(value) => Edge { from, to: destination(jump_target(tokens, last, value)), kind: EdgeKind::StaticJump(value) },
+                    None => Edge { from, to: Destination::Computed, kind: EdgeKind::ComputedJump }
+                });
+            } else {
+                edges.push(Edge { from, to: destination(next(tokens, last)), kind: EdgeKind::Next });
+            }
+        }
+        Graph { blocks, edges }
+    }
+
+    /// The number of symbols on each line of a block's label.
+    const LABEL_WIDTH: usize = 32;
+
+    /// Renders a program's control-flow graph in Graphviz's DOT language.
+    ///
+    /// Each block is labelled with the indices of its commands and their symbols, and blocks ending in `.` have a double border.
+    /// Taken conditionals are green, conditionals that aren't taken are red, and jumps are blue, dashed if their target is computed.
+    pub fn to_dot(tokens: &[Token]) -> String {
+        let graph = build_graph(tokens);
+        let mut dot = "digraph program {\n    node [shape=box, fontname=\"monospace\"];\n    start [shape=point];\n".to_string();
+        for (index, block) in graph.blocks.iter().enumerate() {
+            let symbols: Vec<&str> = tokens[block.start..block.end].iter().map(token_to_symbol).collect();
+            let mut label = if block.end - block.start == 1 { format!("{}", block.start) } else { format!("{}-{}", block.start, block.end - 1) };
+            for line in symbols.chunks(LABEL_WIDTH) {
+                label.push_str("\\l");
+                label.push_str(&line.concat().replace('\\', "\\\\").replace('"', "\\\""));
+            }
+            let border = if tokens[block.end - 1] == Token::Stop { ", peripheries=2" } else { "" };
+            writeln!(dot, "    b{index} [label=\"{label}\\l\"{border}];").unwrap();
+        }
+        if !graph.blocks.is_empty() {
+            dot.push_str("    start -> b0;\n");
+        }
+
+        let mut errors = BTreeSet::new();
+        let mut computed = false;
+        for edge in &graph.edges {
+            let to = match edge.to {
+                Destination::Block(block) => format!("b{block}"),
+                Destination::Computed => {
+                    computed = true;
+                    "computed".to_string()
+                }
+                Destination::Error(error) => {
+                    errors.insert(format!("{error:?}"));
+                    format!("{error:?}")
+                }
+            };
+            let symbol = token_to_symbol(&tokens[graph.blocks[edge.from].end - 1]);
+            let attributes = match edge.kind {
+                EdgeKind::Next => "".to_string(),
+                EdgeKind::Taken => format!(" [label=\"{symbol} taken\", color=\"darkgreen\", fontcolor=\"darkgreen\"]"),
+                EdgeKind::NotTaken => format!(" [label=\"{symbol} not taken\", color=\"red\", fontcolor=\"red\"]"),
+                EdgeKind::StaticJump(value) => format!(" [label=\"{symbol} {value}\", color=\"blue\", fontcolor=\"blue\"]"),
+                EdgeKind::ComputedJump => format!(" [label=\"{symbol} computed\", color=\"blue\", fontcolor=\"blue\", style=\"dashed\"]"),
+            };
+            writeln!(dot, "    b{} -> {to}{attributes};", edge.from).unwrap();
+        }
+        if computed {
+            dot.push_str("    computed [shape=diamond, style=\"dashed\", label=\"computed target\"];\n");
+        }
+        for error in errors {
+            writeln!(dot, "    {error} [shape=octagon, color=\"red\", fontcolor=\"red\"];").unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
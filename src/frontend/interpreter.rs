@@ -0,0 +1,150 @@
+pub mod interp {
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+    use crate::debugger::debug::*;
+    use crate::errors::err::Error;
+    use crate::lexer::lex::*;
+
+    /// The symbols of commands added by extensions, which are recognized on top of the built-in ones.
+    #[derive(Debug, Clone, Default)]
+    struct Registry {
+        symbols: Vec<char>,
+    }
+
+    impl Registry {
+        /// Makes a symbol a command. Returns a message if it already is one, or if it is whitespace or the backtick that starts a comment.
+        fn register(&mut self, c: char) -> Result<(), String> {
+            if symbol_to_token(c).is_some() {
+                return Err(format!("`{c}` is already the symbol of a built-in command"))
+            }
+            if c.is_whitespace() || c == '`' {
+                return Err(format!("{c:?} can't be the symbol of a command"))
+            }
+            if self.symbols.contains(&c) {
+                return Err(format!("`{c}` is already the symbol of another extension"))
+            }
+            self.symbols.push(c);
+            Ok(())
+        }
+
+        /// Like [`try_tokenize`], but also recognizes the registered symbols.
+        fn tokenize(&self, txt: &str) -> Result<Program, (char, usize)> {
+            let commands = tokenize_with(txt, |c| match symbol_to_token(c) {
+                Some(token) => Some((token, None)),
+                None => self.symbols.contains(&c).then_some((Token::Nothing, Some(c)))
+            })?;
+            let (tokens, extensions) = commands.into_iter().unzip();
+            Ok(Program { tokens, extensions })
+        }
+    }
+
+    /// A program tokenized by an [`Interpreter`].
+    #[derive(Debug, Clone)]
+    pub struct Program {
+        /// The commands, with [`Token::Nothing`] for the commands added by extensions.
+        pub tokens: Vec<Token>,
+        /// The symbol of each command added by an extension, and [`None`] for the built-in ones.
+        pub extensions: Vec<Option<char>>,
+    }
+
+    /// Builds an [`Interpreter`] that runs commands added by extensions on top of the built-in ones.
+    #[derive(Default)]
+    pub struct InterpreterBuilder {
+        registry: Registry,
+        extensions: Vec<Box<dyn CommandExtension>>,
+        exit_with_acc: bool,
+        /// The first extension that couldn't be registered, which [`build`](InterpreterBuilder::build) reports.
+        conflict: Option<String>,
+    }
+
+    impl InterpreterBuilder {
+        pub fn new() -> InterpreterBuilder {
+            InterpreterBuilder::default()
+        }
+
+        /// Adds an extension. Its symbol must not be the symbol of a built-in command or of another extension.
+        pub fn extension(mut self, extension: impl CommandExtension + 'static) -> InterpreterBuilder {
+            if self.conflict.is_none() {
+                match self.registry.register(extension.symbol()) {
+                    Ok(()) => self.extensions.push(Box::new(extension)),
+                    Err(msg) => self.conflict = Some(msg)
+                }
+            }
+            self
+        }
+
+        /// Makes `.` exit with the accumulator's value, like `--exit-with-acc` does.
+        pub fn exit_with_acc(mut self, exit_with_acc: bool) -> InterpreterBuilder {
+            self.exit_with_acc = exit_with_acc;
+            self
+        }
+
+        /// Returns the interpreter, or a message about the first extension whose symbol was already taken.
+        pub fn build(self) -> Result<Interpreter, String> {
+            if let Some(conflict) = self.conflict {
+                return Err(conflict)
+            }
+            Ok(Interpreter { registry: self.registry, extensions: Rc::new(RefCell::new(self.extensions)), exit_with_acc: self.exit_with_acc })
+        }
+    }
+
+    /// Runs programs on a [`Machine`], with the commands of the extensions it was built with.
+    /// The machines it loads share the extensions, so an extension that keeps state keeps it from one program to the next.
+    pub struct Interpreter {
+        registry: Registry,
+        extensions: Extensions,
+        exit_with_acc: bool,
+    }
+
+    /// What running a program produced.
+    #[derive(Debug)]
+    pub struct Run {
+        pub output: String,
+        /// How the program ended, or [`None`] if it ran out of steps first.
+        pub outcome: Option<Outcome>,
+    }
+
+    impl Interpreter {
+        /// Tokenizes source, recognizing the symbols of the extensions. Returns an [`UnknownSymbolError`](Error::UnknownSymbolError) for any other symbol.
+        pub fn tokenize(&self, src: &str) -> Result<Program, (Error, String)> {
+            self.registry.tokenize(src)
+                .map_err(|(c, index)| (Error::UnknownSymbolError, format!("unrecognized symbol {c} found at index {index}")))
+        }
+
+        /// Tokenizes source into a machine that can be stepped through, which reads input from stdin unless told otherwise.
+        pub fn load(&self, src: &str) -> Result<Machine, (Error, String)> {
+            let program = self.tokenize(src)?;
+            let targets = match_conditionals(&program.tokens);
+            let mut machine = Machine::new(program.tokens, targets, self.exit_with_acc);
+            machine.set_extensions(Rc::clone(&self.extensions), program.extensions);
+            Ok(machine)
+        }
+
+        /// Runs a program until it stops or throws an error, reading its input from the lines of `input`.
+        /// Like the executable, a program that never stops never returns, so [`run_with_limit`](Interpreter::run_with_limit) should run programs that can't be trusted to.
+        pub fn run(&self, src: &str, input: &str) -> Result<Run, (Error, String)> {
+            self.run_with_limit(src, input, usize::MAX)
+        }
+
+        /// Like [`run`](Interpreter::run), but gives up after `steps` commands, leaving the outcome [`None`] if the program hadn't ended by then.
+        pub fn run_with_limit(&self, src: &str, input: &str, steps: usize) -> Result<Run, (Error, String)> {
+            let mut machine = self.load(src)?;
+            machine.discard_history();
+            let mut lines: VecDeque<String> = input.lines().map(|line| format!("{line}\n")).collect();
+            machine.set_input(move |buf| {
+                let line = lines.pop_front().unwrap_or_default();
+                buf.push_str(&line);
+                Ok(line.len())
+            });
+            let mut outcome = None;
+            for _ in 0..steps {
+                if let Some(ended) = machine.step() {
+                    outcome = Some(ended.clone());
+                    break
+                }
+            }
+            Ok(Run { output: machine.take_output(), outcome })
+        }
+    }
+}
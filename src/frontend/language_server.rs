@@ -0,0 +1,265 @@
+pub mod lsp {
+    use std::collections::HashMap;
+    use std::io::{self, BufRead, Write};
+    use std::ops::Range;
+    use std::process::exit;
+    use serde_json::{json, Value};
+    use crate::checker::check::*;
+    use crate::lexer::lex::*;
+
+    /// The README's command table is where hover text comes from, so that the two never disagree.
+    const README: &str = include_str!("../../README.md");
+
+    /// What a row of the README's command table says about a command.
+    struct CommandDoc {
+        name: String,
+        function: String,
+        halts: String,
+    }
+
+    /// Parses the README's command table into the documentation of each symbol.
+    fn command_docs() -> HashMap<char, CommandDoc> {
+        let mut docs = HashMap::new();
+        let rows = README.lines()
+            .skip_while(|line| !line.starts_with("| Command"))
+            .skip(2)
+            .take_while(|line| line.starts_with('|'));
+        for row in rows {
+            // `\|` is how the table escapes the `|` command
+            let cells: Vec<String> = row.replace("\\|", "\0").split('|').map(|cell| cell.trim().replace('\0', "|")).collect();
+            if cells.len() < 5 {
+                continue
+            }
+            let parts: Vec<&str> = cells[1].split('`').collect();
+            let mut symbols: Vec<char> = parts.iter().skip(1).step_by(2).filter_map(|s| s.chars().next()).collect();
+            if parts.len() == 5 && parts[2] == "-" {
+                symbols = (symbols[0]..=symbols[1]).collect();
+            }
+            for symbol in symbols {
+                docs.insert(symbol, CommandDoc { name: cells[2].clone(), function: cells[3].clone(), halts: cells[4].clone() });
+            }
+        }
+        docs
+    }
+
+    /// Runs a language server over standard input and output until the client tells it to exit.
+    ///
+    /// The server publishes the diagnostics of [`check_source`], describes commands on hover, jumps between a conditional and its matching `@`,
+    /// and lists the sections of a document. A section starts at a comment that begins its line and ends where the next one starts.
+    pub fn serve() {
+        let docs = command_docs();
+        let mut documents: HashMap<String, String> = HashMap::new();
+        let mut input = io::stdin().lock();
+        let mut shutdown = false;
+        while let Some(body) = read_message(&mut input) {
+            let Ok(message) = serde_json::from_slice::<Value>(&body) else {
+                send(json!({ "jsonrpc": "2.0", "id": null, "error": { "code": -32700, "message": "invalid JSON" } }));
+                continue
+            };
+            let id = message.get("id").cloned();
+            let params = &message["params"];
+            let uri = params["textDocument"]["uri"].as_str().unwrap_or("").to_string();
+            let result = match message["method"].as_str().unwrap_or("") {
+                "initialize" => json!({
+                    "capabilities": {
+                        "textDocumentSync": 1,
+                        "hoverProvider": true,
+                        "definitionProvider": true,
+                        "documentSymbolProvider": true
+                    },
+                    "serverInfo": { "name": "guryvsr", "version": env!("CARGO_PKG_VERSION") }
+                }),
+                "textDocument/didOpen" => {
+                    let text = params["textDocument"]["text"].as_str().unwrap_or("").to_string();
+                    publish_diagnostics(&uri, &text);
+                    documents.insert(uri, text);
+                    continue
+                }
+                "textDocument/didChange" => {
+                    if let Some(text) = params["contentChanges"].as_array().and_then(|c| c.last()).and_then(|c| c["text"].as_str()) {
+                        publish_diagnostics(&uri, text);
+                        documents.insert(uri, text.to_string());
+                    }
+                    continue
+                }
+                "textDocument/didClose" => {
+                    documents.remove(&uri);
+                    publish_diagnostics(&uri, "");
+                    continue
+                }
+                "textDocument/hover" => match documents.get(&uri) {
+                    Some(text) => hover(text, &params["position"], &docs),
+                    None => Value::Null
+                },
+                "textDocument/definition" => match documents.get(&uri) {
+                    Some(text) => definition(text, &uri, &params["position"]),
+                    None => Value::Null
+                },
+                "textDocument/documentSymbol" => match documents.get(&uri) {
+                    Some(text) => sections(text),
+                    None => Value::Null
+                },
+                "shutdown" => {
+                    shutdown = true;
+                    Value::Null
+                }
+                "exit" => exit(if shutdown { 0 } else { 1 }),
+                method => {
+                    // notifications that aren't handled are ignored
+                    if id.is_some() {
+                        send(json!({ "jsonrpc": "2.0", "id": id, "error": { "code": -32601, "message": format!("{method} is not supported") } }));
+                    }
+                    continue
+                }
+            };
+            send(json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+        }
+    }
+
+    /// Reads the body of the next message, or returns [`None`] once the input ends.
+    fn read_message(input: &mut impl BufRead) -> Option<Vec<u8>> {
+        let mut length = None;
+        loop {
+            let mut line = "".to_string();
+            if input.read_line(&mut line).ok()? == 0 {
+                return None
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break
+            }
+            if let Some(value) = line.strip_prefix("Content-Length:") {
+                length = value.trim().parse().ok();
+            }
+        }
+        let mut body = vec![0; length?];
+        input.read_exact(&mut body).ok()?;
+        Some(body)
+    }
+
+    fn send(message: Value) {
+        let body = message.to_string();
+        let mut out = io::stdout().lock();
+        write!(out, "Content-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+        out.flush().unwrap();
+    }
+
+    fn publish_diagnostics(uri: &str, text: &str) {
+        let diagnostics: Vec<Value> = check_source(text).into_iter().map(|diagnostic| json!({
+            "range": range(text, &diagnostic.span),
+            "severity": if diagnostic.error.is_some() { 1 } else { 2 },
+            "code": diagnostic.error.map(|error| format!("{error:?}")),
+            "source": "guryvsr",
+            "message": diagnostic.message
+        })).collect();
+        send(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics }
+        }));
+    }
+
+    fn hover(text: &str, position: &Value, docs: &HashMap<char, CommandDoc>) -> Value {
+        let Some((index, token, span)) = command_at(text, position) else {
+            return Value::Null
+        };
+        let symbol = token_to_symbol(&token);
+        let Some(doc) = symbol.chars().next().and_then(|c| docs.get(&c)) else {
+            return Value::Null
+        };
+        json!({
+            "contents": {
+                "kind": "markdown",
+                "value": format!("**`{symbol}`** {} (command {index})\n\n{}\n\nHalts data pointer for 1 command: {}", doc.name, doc.function, doc.halts)
+            },
+            "range": range(text, &span)
+        })
+    }
+
+    fn definition(text: &str, uri: &str, position: &Value) -> Value {
+        let Some((index, _, _)) = command_at(text, position) else {
+            return Value::Null
+        };
+        let commands = commands(text);
+        let tokens: Vec<Token> = commands.iter().map(|(token, _)| *token).collect();
+        match match_conditionals(&tokens)[index] {
+            Some(partner) => json!({ "uri": uri, "range": range(text, &commands[partner].1) }),
+            None => Value::Null
+        }
+    }
+
+    fn sections(text: &str) -> Value {
+        let lexemes = scan_all(text);
+        let labels: Vec<usize> = (0..lexemes.len()).filter(|&i| {
+            let start = lexemes[i].span.start;
+            matches!(lexemes[i].kind, LexemeKind::Comment) && text[..start].rsplit('\n').next().unwrap().trim().is_empty()
+        }).collect();
+
+        let mut symbols: Vec<Value> = vec![];
+        for (n, &label) in labels.iter().enumerate() {
+            let span = &lexemes[label].span;
+            let name = text[span.start + 1..span.end - 1].trim().lines().next().unwrap_or("");
+            if name.is_empty() {
+                continue
+            }
+            let last = labels.get(n + 1).map_or(lexemes.len(), |&next| next) - 1;
+            let commands_before = lexemes[..label].iter().filter(|l| matches!(l.kind, LexemeKind::Command(_))).count();
+            let commands_in = lexemes[label..=last].iter().filter(|l| matches!(l.kind, LexemeKind::Command(_))).count();
+            symbols.push(json!({
+                "name": name,
+                "detail": match commands_in {
+                    0 => "no commands".to_string(),
+                    n => format!("commands {} to {}", commands_before, commands_before + n - 1)
+                },
+                "kind": 3,
+                "range": range(text, &(span.start..lexemes[last].span.end)),
+                "selectionRange": range(text, span)
+            }));
+        }
+        Value::Array(symbols)
+    }
+
+    fn commands(text: &str) -> Vec<(Token, Range<usize>)> {
+        scan_all(text).into_iter().filter_map(|lexeme| match lexeme.kind {
+            LexemeKind::Command(token) => Some((token, lexeme.span)),
+            _ => None
+        }).collect()
+    }
+
+    /// Returns the index, token and span of the command at an LSP position.
+    fn command_at(text: &str, position: &Value) -> Option<(usize, Token, Range<usize>)> {
+        let offset = offset(text, position)?;
+        commands(text).into_iter().enumerate()
+            .find(|(_, (_, span))| span.contains(&offset))
+            .map(|(index, (token, span))| (index, token, span))
+    }
+
+    fn range(text: &str, span: &Range<usize>) -> Value {
+        json!({ "start": position(text, span.start), "end": position(text, span.end) })
+    }
+
+    /// Converts a byte offset into an LSP position, whose character is counted in UTF-16 code units.
+    fn position(text: &str, offset: usize) -> Value {
+        let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let character: usize = text[line_start..offset].chars().map(char::len_utf16).sum();
+        json!({ "line": text[..line_start].matches('\n').count(), "character": character })
+    }
+
+    /// Converts an LSP position into a byte offset.
+    fn offset(text: &str, position: &Value) -> Option<usize> {
+        let line = position["line"].as_u64()? as usize;
+        let character = position["character"].as_u64()? as usize;
+        let line_start = match line {
+            0 => 0,
+            n => text.match_indices('\n').nth(n - 1)?.0 + 1
+        };
+        let mut units = 0;
+        for (i, c) in text[line_start..].char_indices() {
+            if units >= character || c == '\n' {
+                return Some(line_start + i)
+            }
+            units += c.len_utf16();
+        }
+        Some(text.len())
+    }
+}
@@ -1,7 +1,9 @@
 pub mod lex {
     use regex::Regex;
+    use std::ops::Range;
     use crate::errors::err::Error;
     
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum Token {
         Nothing,           // default
         NoOp,              // _
@@ -48,65 +50,199 @@ pub mod lex {
         InputInt,          // I
         InputStr,          // S
     }
+
+    /// The kind of work a command does, for tools that present commands by kind, like highlighters.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Category {
+        Literal,       // # and digits
+        Memory,        // moving values between the accumulator and the tape
+        Arithmetic,    // arithmetic, comparison and bitwise commands
+        Conditional,   // conditionals and @
+        Jump,          // J and j
+        PointerMotion, // commands that move the data pointer or change its direction
+        Io,            // input and output
+        Control,       // _ and .
+    }
+
+    impl Token {
+        /// Returns `true` if the command is a conditional (`?`, `!`, `T`, `t`, `A` or `a`) that jumps to a matching `@`.
+        pub fn is_conditional(&self) -> bool {
+            matches!(self, Token::ZeroOrEmpty | Token::NotZeroOrEmpty | Token::TgtZeroOrEmpty | Token::TgtNotZeroOrEmpty | Token::AccZeroOrEmpty | Token::AccNotZeroOrEmpty)
+        }
+
+        /// Returns the [`Category`] of the command. `Nothing` counts as control, like `_` and `.`.
+        pub fn category(&self) -> Category {
+            match *self {
+                Token::CreatingNumber | Token::Digit(_) => Category::Literal,
+                Token::Unload | Token::Distribute | Token::Recall | Token::Copy | Token::ClearAcc | Token::ClearCurrCell => Category::Memory,
+                Token::Add | Token::Neg | Token::Mul | Token::Div | Token::Mod | Token::Eq | Token::NotEq(_) | Token::Gt | Token::GE(_) | Token::Lt | Token::LE(_)
+                | Token::BitAnd | Token::BitOr | Token::BitNot | Token::BitXor => Category::Arithmetic,
+                Token::DestinationIfTrue => Category::Conditional,
+                _ if self.is_conditional() => Category::Conditional,
+                Token::JumpCellsC | Token::JumpToCellC => Category::Jump,
+                Token::JumpCellsD | Token::JumpToCellD | Token::FlipD(_) | Token::MoveDUntilEmpty | Token::MoveDUntilFull => Category::PointerMotion,
+                Token::OutputInt | Token::OutputChar | Token::InputInt | Token::InputStr => Category::Io,
+                _ => Category::Control
+            }
+        }
+    }
+    #[allow(dead_code)] // the executable expands macros before tokenizing, so only programs that use the library call this
     pub fn tokenize(txt: &mut str) -> Vec<Token> {
-        let mut tokens: Vec<Token> = vec![];
+        match try_tokenize(txt) {
+            Ok(tokens) => tokens,
+            Err((c, index)) => {
+                Error::UnknownSymbolError.throw(&format!("unrecognized symbol {c} found at index {index}"), true);
+                unreachable!()
+            }
+        }
+    }
+
+    /// Like [`tokenize`], but returns the first unrecognized symbol and the number of commands before it instead of exiting.
+    pub fn try_tokenize(txt: &str) -> Result<Vec<Token>, (char, usize)> {
+        tokenize_with(txt, symbol_to_token)
+    }
+
+    /// Like [`try_tokenize`], but each symbol is looked up with `lookup`, so that commands can be added on top of the built-in ones.
+    pub fn tokenize_with<T>(txt: &str, lookup: impl Fn(char) -> Option<T>) -> Result<Vec<T>, (char, usize)> {
+        let mut tokens: Vec<T> = vec![];
         let ignore = Regex::new(r"`(.|\s)*?`|\s*").unwrap();
         let prog = &*ignore.replace_all(txt.trim(), "");
 
         let chars = prog.chars();
-        let mut index = 0;
-        for c in chars {
+        for (index, c) in chars.enumerate() {
+            match lookup(c) {
+                Some(token) => tokens.push(token),
+                None => return Err((c, index))
+            }
+        }
+        Ok(tokens)
+    }
+
+    /// A piece of source text, which is either a command, a comment or a macro directive, along with its byte range in the source.
+    pub struct Lexeme {
+        pub kind: LexemeKind,
+        pub span: Range<usize>,
+    }
+
+    pub enum LexemeKind {
+        Command(Token),
+        Comment,
+        /// A macro directive like `{define name body}` or `{name(args)}`, from its `{` to the `}` that closes it.
+        /// Only source whose macros haven't been expanded has these.
+        Directive,
+        /// A symbol that isn't a command. Only [`scan_all`] produces these.
+        Unknown(char),
+    }
+
+    /// Splits the source into commands, comments and macro directives. Unlike [`tokenize`], comments and directives are kept,
+    /// and the whitespace between two lexemes can be recovered from the gap between their spans.
+    ///
+    /// If an unrecognized symbol is found, an [`Err`] containing the symbol and its command index is returned.
+    pub fn scan(txt: &str) -> Result<Vec<Lexeme>, (char, usize)> {
+        let lexemes = scan_all(txt);
+        let mut commands = 0;
+        for lexeme in &lexemes {
+            match lexeme.kind {
+                LexemeKind::Command(_) => commands += 1,
+                LexemeKind::Comment | LexemeKind::Directive => {}
+                LexemeKind::Unknown(c) => return Err((c, commands))
+            }
+        }
+        Ok(lexemes)
+    }
+
+    /// Like [`scan`], but unrecognized symbols are kept as [`LexemeKind::Unknown`] instead of stopping the scan,
+    /// so that every problem in the source can be reported at once.
+    pub fn scan_all(txt: &str) -> Vec<Lexeme> {
+        let mut lexemes: Vec<Lexeme> = vec![];
+        let mut start = 0;
+        while let Some(c) = txt[start..].chars().next() {
+            let mut end = start + c.len_utf8();
+            if c == '`' && let Some(len) = txt[end..].find('`') {
+                end += len + 1;
+                lexemes.push(Lexeme { kind: LexemeKind::Comment, span: start..end });
+            } else if c == '{' && let Some(close) = closing_brace(&txt[start..]) {
+                end = start + close + 1;
+                lexemes.push(Lexeme { kind: LexemeKind::Directive, span: start..end });
+            } else if !c.is_whitespace() {
+                let kind = match symbol_to_token(c) {
+                    Some(token) => LexemeKind::Command(token),
+                    None => LexemeKind::Unknown(c)
+                };
+                lexemes.push(Lexeme { kind, span: start..end });
+            }
+            start = end;
+        }
+        lexemes
+    }
+
+    /// Returns the offset of the `}` that closes the `{` at the start of `text`, skipping comments.
+    pub fn closing_brace(text: &str) -> Option<usize> {
+        let mut depth = 0;
+        let mut chars = text.char_indices();
+        while let Some((offset, c)) = chars.next() {
             match c {
-                '_' => tokens.push(Token::NoOp),
-                '.' => tokens.push(Token::Stop),
-                '#' => tokens.push(Token::CreatingNumber),
-                '0'..='9' => tokens.push(Token::Digit(c.to_digit(10).unwrap() as isize)),
-                'U' => tokens.push(Token::Unload),
-                'u' => tokens.push(Token::Distribute),
-                'R' => tokens.push(Token::Recall),
-                'r' => tokens.push(Token::Copy),
-                'C' => tokens.push(Token::ClearAcc),
-                'c' => tokens.push(Token::ClearCurrCell),
-                '?' => tokens.push(Token::ZeroOrEmpty),
-                '!' => tokens.push(Token::NotZeroOrEmpty),
-                'T' => tokens.push(Token::TgtZeroOrEmpty),
-                't' => tokens.push(Token::TgtNotZeroOrEmpty),
-                'A' => tokens.push(Token::AccZeroOrEmpty),
-                'a' => tokens.push(Token::AccNotZeroOrEmpty),
-                '@' => tokens.push(Token::DestinationIfTrue),
-                'J' => tokens.push(Token::JumpCellsC),
-                'j' => tokens.push(Token::JumpToCellC),
-                'K' => tokens.push(Token::JumpCellsD),
-                'k' => tokens.push(Token::JumpToCellD),
-                'F' => tokens.push(Token::FlipD(true)),
-                'f' => tokens.push(Token::FlipD(false)),
-                'M' => tokens.push(Token::MoveDUntilEmpty),
-                'm' => tokens.push(Token::MoveDUntilFull),
-                '+' => tokens.push(Token::Add),
-                '-' => tokens.push(Token::Neg),
-                '*' => tokens.push(Token::Mul),
-                '/' => tokens.push(Token::Div),
-                '%' => tokens.push(Token::Mod),
-                '=' => tokens.push(Token::Eq),
-                'N' | 'n' => tokens.push(Token::NotEq(c.is_uppercase())),
-                '>' => tokens.push(Token::Gt),
-                'G' | 'g' => tokens.push(Token::GE(c.is_uppercase())),
-                '<' => tokens.push(Token::Lt),
-                'L' | 'l' => tokens.push(Token::LE(c.is_uppercase())),
-                '&' => tokens.push(Token::BitAnd),
-                '|' => tokens.push(Token::BitOr),
-                '~' => tokens.push(Token::BitNot),
-                '^' => tokens.push(Token::BitXor),
-                'i' => tokens.push(Token::OutputInt),
-                's' => tokens.push(Token::OutputChar),
-                'I' => tokens.push(Token::InputInt),
-                'S' => tokens.push(Token::InputStr),
-                _ => Error::UnknownSymbolError.throw(&format!("unrecognized symbol {c} found at index {index}"), true)
+                '`' => { chars.by_ref().find(|&(_, c)| c == '`')?; }
+                '{' => depth += 1,
+                '}' if depth == 1 => return Some(offset),
+                '}' => depth -= 1,
+                _ => {}
             }
-            index += 1
         }
-        tokens
+        None
+    }
+
+    /// Returns the command a symbol stands for, or [`None`] if the symbol is not a command.
+    pub fn symbol_to_token(c: char) -> Option<Token> {
+        Some(match c {
+            '_' => Token::NoOp,
+            '.' => Token::Stop,
+            '#' => Token::CreatingNumber,
+            '0'..='9' => Token::Digit(c.to_digit(10).unwrap() as isize),
+            'U' => Token::Unload,
+            'u' => Token::Distribute,
+            'R' => Token::Recall,
+            'r' => Token::Copy,
+            'C' => Token::ClearAcc,
+            'c' => Token::ClearCurrCell,
+            '?' => Token::ZeroOrEmpty,
+            '!' => Token::NotZeroOrEmpty,
+            'T' => Token::TgtZeroOrEmpty,
+            't' => Token::TgtNotZeroOrEmpty,
+            'A' => Token::AccZeroOrEmpty,
+            'a' => Token::AccNotZeroOrEmpty,
+            '@' => Token::DestinationIfTrue,
+            'J' => Token::JumpCellsC,
+            'j' => Token::JumpToCellC,
+            'K' => Token::JumpCellsD,
+            'k' => Token::JumpToCellD,
+            'F' => Token::FlipD(true),
+            'f' => Token::FlipD(false),
+            'M' => Token::MoveDUntilEmpty,
+            'm' => Token::MoveDUntilFull,
+            '+' => Token::Add,
+            '-' => Token::Neg,
+            '*' => Token::Mul,
+            '/' => Token::Div,
+            '%' => Token::Mod,
+            '=' => Token::Eq,
+            'N' | 'n' => Token::NotEq(c.is_uppercase()),
+            '>' => Token::Gt,
+            'G' | 'g' => Token::GE(c.is_uppercase()),
+            '<' => Token::Lt,
+            'L' | 'l' => Token::LE(c.is_uppercase()),
+            '&' => Token::BitAnd,
+            '|' => Token::BitOr,
+            '~' => Token::BitNot,
+            '^' => Token::BitXor,
+            'i' => Token::OutputInt,
+            's' => Token::OutputChar,
+            'I' => Token::InputInt,
+            'S' => Token::InputStr,
+            _ => return None
+        })
     }
+
     pub fn token_to_symbol(token: &Token) -> &str {
         match *token {
             Token::Nothing => "<none>",
@@ -164,7 +300,86 @@ pub mod lex {
             Token::OutputInt => "i",
             Token::OutputChar => "s",
             Token::InputInt => "I",
-            Token::InputStr => "S"
+            Token::InputStr => "S",
+        }
+    }
+
+    /// Returns, for each command, the index of the command it is paired with:
+    /// the matching `@` for a conditional, and the matching conditional for an `@`.
+    /// Commands that aren't part of a pair get [`None`].
+    pub fn match_conditionals(tokens: &[Token]) -> Vec<Option<usize>> {
+        let mut matches = vec![None; tokens.len()];
+        let mut open: Vec<usize> = vec![];
+        for (index, token) in tokens.iter().enumerate() {
+            if token.is_conditional() {
+                open.push(index);
+            } else if *token == Token::DestinationIfTrue && let Some(start) = open.pop() {
+                matches[start] = Some(index);
+                matches[index] = Some(start);
+            }
+        }
+        matches
+    }
+
+    /// Returns the value of the number literal (`#` followed by at least one digit) that ends right before the provided index,
+    /// or [`None`] if there isn't one or its value doesn't fit in the accumulator.
+    pub fn literal_before(tokens: &[Token], index: usize) -> Option<isize> {
+        let mut start = index;
+        while start > 0 && matches!(tokens[start - 1], Token::Digit(_)) {
+            start -= 1
+        }
+        if start == index || start == 0 || tokens[start - 1] != Token::CreatingNumber {
+            return None
+        }
+        tokens[start..index].iter().try_fold(0isize, |value, token| match *token {
+            Token::Digit(n) => value.checked_mul(10)?.checked_add(n),
+            _ => None
+        })
+    }
+
+    /// Returns the value a `J` or `j` always jumps with if it is right after a number literal, or a negated one like `#3-`.
+    pub fn static_jump(tokens: &[Token], index: usize) -> Option<isize> {
+        if index > 0 && tokens[index - 1] == Token::Neg {
+            return literal_before(tokens, index - 1).map(|value| -value)
+        }
+        literal_before(tokens, index)
+    }
+
+    /// Returns the value each `J` and `j` always jumps with, as found by [`static_jump`], or [`None`] for the other commands.
+    /// A jump whose literal another `J` or `j` with a known value can land inside of, or on, isn't always run after its literal, so it gets [`None`] too.
+    /// Jumps to computed targets are assumed to land where their program means them to, which is never inside of a literal.
+    pub fn static_jumps(tokens: &[Token]) -> Vec<Option<isize>> {
+        let mut jumps: Vec<Option<isize>> = tokens.iter().enumerate()
+            .map(|(index, token)| if matches!(token, Token::JumpCellsC | Token::JumpToCellC) { static_jump(tokens, index) } else { None })
+            .collect();
+        // a jump that turns out not to be static lands nowhere in particular, which can make the jumps it landed in static again,
+        // but they are left as they are, so that this always ends
+        loop {
+            let landings: Vec<usize> = jumps.iter().enumerate()
+                .filter_map(|(index, value)| match tokens[index] {
+                    Token::JumpCellsC => index.checked_add_signed((*value)?),
+                    _ => usize::try_from((*value)?).ok()
+                })
+                .filter_map(|target| target.checked_add(1))
+                .collect();
+            let mut changed = false;
+            for index in 0..tokens.len() {
+                if jumps[index].is_none() {
+                    continue
+                }
+                // the literal's first digit, since landing on its `#` runs the whole literal
+                let mut start = if tokens[index - 1] == Token::Neg { index - 1 } else { index };
+                while matches!(tokens[start - 1], Token::Digit(_)) {
+                    start -= 1
+                }
+                if landings.iter().any(|landing| (start..=index).contains(landing)) {
+                    jumps[index] = None;
+                    changed = true;
+                }
+            }
+            if !changed {
+                return jumps
+            }
         }
     }
 }
\ No newline at end of file
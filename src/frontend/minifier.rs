@@ -0,0 +1,86 @@
+pub mod minify {
+    use crate::errors::err::warn;
+    use crate::lexer::lex::*;
+
+    /// Rewrites a program as the shortest source with the same commands, removing every comment and all whitespace.
+    ///
+    /// If `normalize_aliases` is set, the aliases `f`, `n`, `g` and `l` are written as `F`, `N`, `G` and `L`.
+    /// If `strip_noops` is set, the no-ops that [`removable_noops`] finds are removed too.
+    pub fn minify(tokens: &[Token], normalize_aliases: bool, strip_noops: bool) -> String {
+        let removable = if strip_noops { removable_noops(tokens) } else { vec![false; tokens.len()] };
+        let mut out = "".to_string();
+        for (token, remove) in tokens.iter().zip(removable) {
+            if remove {
+                continue
+            }
+            let token = if normalize_aliases { canonical_alias(*token) } else { *token };
+            out.push_str(token_to_symbol(&token));
+        }
+        out
+    }
+
+    fn canonical_alias(token: Token) -> Token {
+        match token {
+            Token::FlipD(_) => Token::FlipD(true),
+            Token::NotEq(_) => Token::NotEq(true),
+            Token::GE(_) => Token::GE(true),
+            Token::LE(_) => Token::LE(true),
+            _ => token
+        }
+    }
+
+    /// Finds the no-ops (`_`, and `@`s without a matching conditional) that can be removed without changing what the program does.
+    ///
+    /// A no-op still moves the data pointer, so it is only removable if a `k` moves the data pointer to another cell
+    /// before anything can depend on where it is, i.e. if only no-ops and number literals come between the no-op and the `k`.
+    ///
+    /// Removing a command also shifts every command after it, which changes where `J` and `j` land.
+    /// A no-op is therefore never removed from between a `J` and its target, or from at or before the target of a `j`.
+    /// This needs every target to be known, so if any `J` or `j` doesn't take its target from a number literal right before it,
+    /// a warning is printed and nothing is removed.
+    pub fn removable_noops(tokens: &[Token]) -> Vec<bool> {
+        let matches = match_conditionals(tokens);
+        let mut removable: Vec<bool> = tokens.iter().enumerate().map(|(index, token)| {
+            let noop = match *token {
+                Token::NoOp => true,
+                Token::DestinationIfTrue => matches[index].is_none(),
+                _ => false
+            };
+            noop && move_is_overridden(tokens, index)
+        }).collect();
+
+        let mut computed = false;
+        for (index, token) in tokens.iter().enumerate() {
+            if !matches!(*token, Token::JumpCellsC | Token::JumpToCellC) {
+                continue
+            }
+            let Some(target) = literal_before(tokens, index) else {
+                warn(&format!("`{}` at index {index} jumps to a computed command index, so no commands can be removed safely", token_to_symbol(token)));
+                computed = true;
+                continue
+            };
+            let (first, last) = match *token {
+                Token::JumpCellsC => (index + 1, index.saturating_add(target as usize)),
+                _ => (0, target as usize)
+            };
+            for remove in removable.iter_mut().take(last.saturating_add(1)).skip(first) {
+                *remove = false;
+            }
+        }
+        if computed {
+            return vec![false; tokens.len()]
+        }
+        removable
+    }
+
+    fn move_is_overridden(tokens: &[Token], index: usize) -> bool {
+        for token in &tokens[index + 1..] {
+            match *token {
+                Token::JumpToCellD => return true,
+                Token::CreatingNumber | Token::Digit(_) | Token::NoOp | Token::DestinationIfTrue => {}
+                _ => return false
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,143 @@
+pub mod ast {
+    use std::ops::Range;
+    use crate::lexer::lex::*;
+
+    /// A program as a tree, where each conditional owns the commands up to its matching `@`.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Program {
+        pub nodes: Vec<Node>,
+    }
+
+    /// A command, a number literal, or a conditional with its body.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Node {
+        pub kind: NodeKind,
+        /// The indices of the commands the node is made of, which for a conditional runs up to and including its matching `@`.
+        pub commands: Range<usize>,
+        /// The byte range of the node in the source, from its first command to its last.
+        pub span: Range<usize>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum NodeKind {
+        /// A single command that isn't part of a literal or a matched conditional. This includes `#`s without digits after them,
+        /// digits without a `#` before them, and conditionals and `@`s without a match, which are errors or no-ops when run.
+        Command(Token),
+        /// `#` followed by one or more digits, with the value they make, or [`None`] if it doesn't fit in the accumulator.
+        Literal(Option<isize>),
+        /// A conditional, the nodes between it and its matching `@`, and the byte range of the `@` in the source.
+        Conditional { test: Token, body: Vec<Node>, end: Range<usize> },
+    }
+
+    impl Node {
+        /// Returns the index of the node's first command.
+        pub fn index(&self) -> usize {
+            self.commands.start
+        }
+    }
+
+    /// Walks a [`Program`] in source order. Every method does nothing by default, so a visitor only implements what it needs.
+    pub trait Visitor {
+        fn visit_command(&mut self, _node: &Node, _token: Token) {}
+        fn visit_literal(&mut self, _node: &Node, _value: Option<isize>) {}
+        /// Called before the body of a conditional is walked.
+        fn enter_conditional(&mut self, _node: &Node, _test: Token) {}
+        /// Called after the body of a conditional is walked, for its matching `@`.
+        fn leave_conditional(&mut self, _node: &Node, _test: Token) {}
+    }
+
+    impl Program {
+        /// Calls the visitor's methods for every node, in source order.
+        pub fn walk(&self, visitor: &mut impl Visitor) {
+            walk_nodes(&self.nodes, visitor)
+        }
+
+        /// Returns the number of commands in the program.
+        pub fn len(&self) -> usize {
+            self.nodes.last().map_or(0, |node| node.commands.end)
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.nodes.is_empty()
+        }
+    }
+
+    fn walk_nodes(nodes: &[Node], visitor: &mut impl Visitor) {
+        for node in nodes {
+            match &node.kind {
+                NodeKind::Command(token) => visitor.visit_command(node, *token),
+                NodeKind::Literal(value) => visitor.visit_literal(node, *value),
+                NodeKind::Conditional { test, body, .. } => {
+                    visitor.enter_conditional(node, *test);
+                    walk_nodes(body, visitor);
+                    visitor.leave_conditional(node, *test);
+                }
+            }
+        }
+    }
+
+    /// Parses source into a [`Program`]. Like [`scan`], an [`Err`] containing an unrecognized symbol and its command index is returned if there is one.
+    pub fn parse(src: &str) -> Result<Program, (char, usize)> {
+        let commands: Vec<(Token, Range<usize>)> = scan(src)?.into_iter()
+            .filter_map(|lexeme| match lexeme.kind {
+                LexemeKind::Command(token) => Some((token, lexeme.span)),
+                _ => None
+            })
+            .collect();
+        Ok(parse_commands(&commands))
+    }
+
+    /// Parses commands without source, like the ones loaded from bytecode, into a [`Program`].
+    /// Each command's span is its index, as if it were in source with a byte for each command, like the source `minify` prints.
+    pub fn parse_tokens(tokens: &[Token]) -> Program {
+        let commands: Vec<(Token, Range<usize>)> = tokens.iter().enumerate().map(|(index, token)| (*token, index..index + 1)).collect();
+        parse_commands(&commands)
+    }
+
+    fn parse_commands(commands: &[(Token, Range<usize>)]) -> Program {
+        let tokens: Vec<Token> = commands.iter().map(|(token, _)| *token).collect();
+        let targets = match_conditionals(&tokens);
+        let mut parser = Parser { commands, targets, index: 0 };
+        Program { nodes: parser.nodes(commands.len()) }
+    }
+
+    struct Parser<'a> {
+        commands: &'a [(Token, Range<usize>)],
+        targets: Vec<Option<usize>>,
+        index: usize,
+    }
+
+    impl Parser<'_> {
+        /// Parses nodes until the command at `end`, which is the matching `@` of the conditional being parsed, or the end of the program.
+        fn nodes(&mut self, end: usize) -> Vec<Node> {
+            let mut nodes = vec![];
+            while self.index < end {
+                nodes.push(self.node());
+            }
+            nodes
+        }
+
+        fn node(&mut self) -> Node {
+            let start = self.index;
+            let (token, span) = self.commands[start].clone();
+            self.index += 1;
+            let kind = match token {
+                Token::CreatingNumber if matches!(self.commands.get(self.index), Some((Token::Digit(_), _))) => {
+                    while matches!(self.commands.get(self.index), Some((Token::Digit(_), _))) {
+                        self.index += 1;
+                    }
+                    let literal: Vec<Token> = self.commands[start..self.index].iter().map(|(token, _)| *token).collect();
+                    NodeKind::Literal(literal_before(&literal, literal.len()))
+                }
+                _ if token.is_conditional() && let Some(end) = self.targets[start] => {
+                    let body = self.nodes(end);
+                    self.index += 1;
+                    NodeKind::Conditional { test: token, body, end: self.commands[end].1.clone() }
+                }
+                _ => NodeKind::Command(token)
+            };
+            let end = self.commands[self.index - 1].1.end;
+            Node { kind, commands: start..self.index, span: span.start..end }
+        }
+    }
+}
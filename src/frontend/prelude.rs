@@ -1,7 +1,9 @@
 #[allow(dead_code)]
 pub mod prelude {
     use std::collections::HashMap;
+    use std::ops::RangeInclusive;
     use crate::errors::err::*;
+    #[derive(Clone)]
     pub struct Acc {
         val: Option<isize>
     }
@@ -52,16 +54,55 @@ pub mod prelude {
             "accumulator was empty".to_string()
         }
     }
+    #[derive(Clone)]
     pub struct Tape {
-        cells: HashMap<isize, isize>
+        cells: HashMap<isize, isize>,
+        /// The sum of the hashes of the cells, kept up to date as they change so that the tape never has to be rehashed.
+        /// Only a tape made with [`Tape::hashed`] keeps it, since it slows down every write.
+        hash: Option<u64>
     }
+
+    /// Tapes are equal when their cells are, whether or not they keep a hash.
+    impl PartialEq for Tape {
+        fn eq(&self, other: &Tape) -> bool {
+            self.cells == other.cells
+        }
+    }
+
+    impl Eq for Tape {}
+
+    /// Scrambles a number so that numbers that are close together get unrelated hashes (the finalizer of SplitMix64).
+    fn mix(mut n: u64) -> u64 {
+        n = (n ^ (n >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        n = (n ^ (n >> 27)).wrapping_mul(0x94d049bb133111eb);
+        n ^ (n >> 31)
+    }
+
+    fn cell_hash(index: isize, value: isize) -> u64 {
+        mix(mix(index as u64) ^ value as u64)
+    }
+
     impl Tape {
         pub(crate) fn new() -> Tape {
             Tape {
-                cells: HashMap::new()
+                cells: HashMap::new(),
+                hash: None
+            }
+        }
+
+        /// Makes an empty tape that keeps a hash of its contents, for a [`LoopDetector`] to compare.
+        pub(crate) fn hashed() -> Tape {
+            Tape {
+                cells: HashMap::new(),
+                hash: Some(0)
             }
         }
 
+        /// Returns a hash of the tape's contents, which takes constant time, or [`None`] if the tape wasn't made with [`Tape::hashed`].
+        pub(crate) fn hash(&self) -> Option<u64> {
+            self.hash
+        }
+
         /// Returns a [`Some`] value containing the value at the cell of the provided index, or [`None`] if the cell at the provided index is empty.
         pub(crate) fn get(&self, index: isize) -> Option<isize> {
             if let Some(n) = self.cells.get(&index) {
@@ -70,9 +111,22 @@ pub mod prelude {
             None
         }
 
+        /// Returns the indices and values of the full cells, in order of their indices.
+        pub(crate) fn cells(&self) -> Vec<(isize, isize)> {
+            let mut cells: Vec<(isize, isize)> = self.cells.iter().map(|(&index, &value)| (index, value)).collect();
+            cells.sort_unstable();
+            cells
+        }
+
         /// Replaces the value of the cell at the provided index with another value.
         pub(crate) fn set(&mut self, index: isize, value: isize) {
-            self.cells.insert(index, value);
+            let old = self.cells.insert(index, value);
+            if let Some(hash) = &mut self.hash {
+                if let Some(old) = old {
+                    *hash = hash.wrapping_sub(cell_hash(index, old));
+                }
+                *hash = hash.wrapping_add(cell_hash(index, value));
+            }
         }
 
         /// Clears the cell at the provided index.
@@ -81,7 +135,11 @@ pub mod prelude {
             if !self.cells.contains_key(&index) {
                 return None
             }
-            self.cells.remove(&index)
+            let value = self.cells.remove(&index)?;
+            if let Some(hash) = &mut self.hash {
+                *hash = hash.wrapping_sub(cell_hash(index, value));
+            }
+            Some(value)
         }
 
         pub(crate) fn cell_is_full(&self, index: isize) -> bool {
@@ -98,6 +156,21 @@ pub mod prelude {
             true
         }
 
+        /// Returns the index of the first full cell found when moving from the provided index (inclusive) by `dir`,
+        /// or [`None`] if there is no full cell that way. Nearby cells are visited one by one, and if that takes longer than looking at every full cell,
+        /// the full cells are looked at instead, so the search never takes longer than that even when there is no full cell that way.
+        pub(crate) fn next_full(&self, index: isize, dir: isize) -> Option<isize> {
+            let mut cell = index;
+            for _ in 0..self.cells.len() {
+                if self.cells.contains_key(&cell) {
+                    return Some(cell)
+                }
+                cell = cell.checked_add(dir)?;
+            }
+            let ahead = self.cells.keys().copied().filter(|&cell| if dir > 0 { cell >= index } else { cell <= index });
+            if dir > 0 { ahead.min() } else { ahead.max() }
+        }
+
         /// Returns an [`Ok`] if the cell at `index - 1` exists, and an [`Err`] if not.
         /// If said cell is empty, a [`None`] is contained within the returned [`Ok`].
         /// Otherwise, a [`Some`] value containing the cell's value is contained within the returned [`Ok`].
@@ -113,4 +186,70 @@ pub mod prelude {
             }
         }
     }
-}
\ No newline at end of file
+
+    /// The state of the interpreter at a conditional or jump, kept to be compared with later ones.
+    struct Snapshot {
+        hash: u64,
+        tape: Tape,
+        acc: Option<isize>,
+        data_ptr_index: isize,
+        data_ptr_dir: isize,
+        code_ptr_index: usize,
+        creating_number: bool
+    }
+
+    /// Finds out when a program returns to a state it was in before without reading input in between, which means it will
+    /// run the same commands forever. States are only compared at conditionals and jumps, since every loop goes through one.
+    ///
+    /// Like Brent's algorithm, only one state is kept, and it is replaced after being compared with twice as many states
+    /// as the one before it, so a loop is found within a few times its length without the memory growing.
+    /// States are compared by their hashes first, and in full only when the hashes are the same.
+    pub(crate) struct LoopDetector {
+        saved: Option<Snapshot>,
+        /// How many states the saved state is compared with before it is replaced.
+        limit: usize,
+        compared: usize,
+        /// The lowest and highest indices of the commands run since the saved state.
+        lowest: usize,
+        highest: usize
+    }
+
+    impl LoopDetector {
+        pub(crate) fn new() -> LoopDetector {
+            LoopDetector { saved: None, limit: 1, compared: 0, lowest: usize::MAX, highest: 0 }
+        }
+
+        /// Forgets the saved state, since reading input can make the program go another way from the same state.
+        pub(crate) fn reset(&mut self) {
+            *self = LoopDetector::new();
+        }
+
+        /// Notes that the command at `code_ptr_index` is being run.
+        pub(crate) fn run(&mut self, code_ptr_index: usize) {
+            self.lowest = self.lowest.min(code_ptr_index);
+            self.highest = self.highest.max(code_ptr_index);
+        }
+
+        /// Compares the state at a conditional or jump with the saved one. If they are the same, the range of the indices of
+        /// the commands that were run in between, which will repeat forever, is returned.
+        pub(crate) fn visit(&mut self, tape: &Tape, acc: &Acc, data_ptr_index: isize, data_ptr_dir: isize, code_ptr_index: usize, creating_number: bool) -> Option<RangeInclusive<usize>> {
+            let acc = acc.get_value().ok();
+            let hash = [acc.map_or(u64::MAX, |value| value as u64), data_ptr_index as u64, data_ptr_dir as u64, code_ptr_index as u64, creating_number as u64]
+                .into_iter()
+                .fold(tape.hash().expect("loops are only detected on tapes made with Tape::hashed"), |hash, part| mix(hash ^ part));
+            if let Some(saved) = &self.saved && saved.hash == hash && saved.tape == *tape
+                && (saved.acc, saved.data_ptr_index, saved.data_ptr_dir, saved.code_ptr_index, saved.creating_number) == (acc, data_ptr_index, data_ptr_dir, code_ptr_index, creating_number) {
+                return Some(self.lowest..=self.highest)
+            }
+            self.compared += 1;
+            if self.saved.is_none() || self.compared == self.limit {
+                self.saved = Some(Snapshot { hash, tape: tape.clone(), acc, data_ptr_index, data_ptr_dir, code_ptr_index, creating_number });
+                self.limit *= 2;
+                self.compared = 0;
+                self.lowest = code_ptr_index;
+                self.highest = code_ptr_index;
+            }
+            None
+        }
+    }
+}
@@ -0,0 +1,549 @@
+pub mod macros {
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::fs;
+    use std::ops::Range;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use crate::checker::check::line_column;
+    use crate::errors::err::Error;
+    use crate::lexer::lex::{closing_brace, scan_all, LexemeKind};
+
+    /// A file read while expanding a program.
+    struct SourceFile {
+        /// The file's path as it is shown in messages.
+        name: Arc<str>,
+        path: PathBuf,
+        text: String,
+    }
+
+    /// Where something is written in a file.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Location {
+        pub file: Arc<str>,
+        pub line: usize,
+        pub column: usize,
+    }
+
+    impl fmt::Display for Location {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}:{}:{}", self.file, self.line, self.column)
+        }
+    }
+
+    /// A macro call that a command was expanded through.
+    #[derive(Clone, Debug)]
+    pub struct CallSite {
+        pub name: String,
+        pub at: Location,
+    }
+
+    /// Where a command of the expanded source comes from.
+    #[derive(Clone, Debug)]
+    pub struct Origin {
+        /// Where the command is written, which is in a macro's body if it comes from one.
+        pub at: Location,
+        /// The macro calls the command was expanded through, innermost first. Empty if it is written outside of every macro.
+        pub calls: Arc<Vec<CallSite>>,
+    }
+
+    impl Origin {
+        /// Describes the macro calls the command was expanded through, like ", in `store` called at prog.gur:5:1", or returns an empty string if there are none.
+        pub fn call_chain(&self) -> String {
+            segment_chain(&self.calls)
+        }
+    }
+
+    /// A macro call, and the commands of the expanded source it stands for.
+    #[derive(Clone, Debug)]
+    pub struct Call {
+        pub name: String,
+        pub at: Location,
+        /// The indices of the commands it expanded into, counting every command of the expanded source.
+        pub commands: Range<usize>,
+    }
+
+    /// A position that `j` and `J` can jump to by name, written `{label name}`.
+    #[derive(Clone, Debug)]
+    pub struct Label {
+        pub name: String,
+        pub at: Location,
+        /// The index of the command after the label, which is the one that runs after a jump to it.
+        pub index: usize,
+    }
+
+    /// Whether a label reference is written for `j` or for `J`.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum ReferenceKind {
+        /// `{to name}`, the index of the command before the label.
+        Absolute,
+        /// `{by name}`, the distance from the command after the literal to the command before the label.
+        Relative,
+    }
+
+    /// A reference to a label, and the number literal it expanded into.
+    #[derive(Clone, Debug)]
+    pub struct Reference {
+        pub name: String,
+        pub at: Location,
+        pub kind: ReferenceKind,
+        /// The indices of the commands of its number literal.
+        pub commands: Range<usize>,
+    }
+
+    /// A program with its macros and includes expanded.
+    pub struct Expansion {
+        /// The source with every definition and include removed, and every call replaced by its macro's body.
+        pub source: String,
+        /// Where each command of `source` comes from, in order.
+        pub origins: Vec<Origin>,
+        /// Every macro call, in the order they were made.
+        pub calls: Vec<Call>,
+        /// Every label, in the order they are in the expanded source.
+        pub labels: Vec<Label>,
+        /// Every label reference, in the order they are in the expanded source.
+        pub references: Vec<Reference>,
+        /// The byte offset in `source` of each command, in order.
+        offsets: Vec<usize>,
+        /// The file that was expanded.
+        root: Arc<str>,
+    }
+
+    impl Expansion {
+        /// Describes where the command at the provided index comes from, or returns [`None`] if it is written in the expanded file outside of every macro,
+        /// where its index is enough to find it.
+        pub fn describe(&self, index: usize) -> Option<String> {
+            let origin = self.origins.get(index)?;
+            if origin.calls.is_empty() && origin.at.file == self.root {
+                return None
+            }
+            Some(format!("command {index} is at {}{}", origin.at, origin.call_chain()))
+        }
+
+        /// Returns where the command or unrecognized symbol at the provided byte offset of the expanded source comes from.
+        pub fn locate(&self, offset: usize) -> Option<&Origin> {
+            self.offsets.binary_search(&offset).ok().map(|index| &self.origins[index])
+        }
+
+        /// Returns the index of every command written in the expanded file outside of every macro, by the line and column it is written at.
+        pub fn written_commands(&self) -> HashMap<(usize, usize), usize> {
+            self.origins.iter().enumerate()
+                .filter(|(_, origin)| origin.calls.is_empty() && origin.at.file == self.root)
+                .map(|(index, origin)| ((origin.at.line, origin.at.column), index))
+                .collect()
+        }
+
+        /// Returns the commands that every macro call and label reference in the expanded file expanded into, by the line and column it is written at.
+        pub fn written_directives(&self) -> HashMap<(usize, usize), Range<usize>> {
+            let calls = self.calls.iter().map(|call| (&call.at, &call.commands));
+            let references = self.references.iter().map(|reference| (&reference.at, &reference.commands));
+            calls.chain(references)
+                .filter(|(at, _)| at.file == self.root)
+                .map(|(at, commands)| ((at.line, at.column), commands.clone()))
+                .collect()
+        }
+
+        /// Returns the byte range in `source`, the text of the expanded file, that each command is shown at: the command itself if it is written outside of every macro,
+        /// or else the macro call, label reference or include it was expanded from.
+        pub fn source_spans(&self, source: &str) -> Vec<Range<usize>> {
+            let commands = self.written_commands();
+            let directives = self.written_directives();
+            let mut spans = vec![0..0; self.origins.len()];
+            // the commands of an include are the ones between those of the directives around it
+            let mut include = 0..0;
+            let mut next = 0;
+            let (mut line, mut line_start, mut prev_start) = (1, 0, 0);
+            for lexeme in scan_all(source) {
+                let gap = &source[prev_start..lexeme.span.start];
+                line += gap.matches('\n').count();
+                if let Some(offset) = gap.rfind('\n') {
+                    line_start = prev_start + offset + 1;
+                }
+                prev_start = lexeme.span.start;
+                let at = (line, source[line_start..lexeme.span.start].chars().count() + 1);
+                let commands = match (&lexeme.kind, commands.get(&at), directives.get(&at)) {
+                    (LexemeKind::Command(_), Some(&index), _) => index..index + 1,
+                    (LexemeKind::Directive, _, Some(commands)) => commands.clone(),
+                    (LexemeKind::Directive, _, None) if source[lexeme.span.start + 1..].trim_start().starts_with("include") => {
+                        include = lexeme.span.clone();
+                        continue
+                    }
+                    _ => continue
+                };
+                spans[next.min(commands.start)..commands.start].fill(include.clone());
+                spans[commands.clone()].fill(lexeme.span.clone());
+                next = next.max(commands.end);
+            }
+            let end = spans.len();
+            spans[next..end].fill(include);
+            spans
+        }
+    }
+
+    /// A piece of a file that is expanded on its own: a whole file, a macro's body, or an argument.
+    #[derive(Clone)]
+    struct Segment {
+        file: Arc<SourceFile>,
+        range: Range<usize>,
+    }
+
+    impl Segment {
+        fn text(&self) -> &str {
+            &self.file.text[self.range.clone()]
+        }
+
+        fn location(&self, offset: usize) -> Location {
+            let (line, column) = line_column(&self.file.text, self.range.start + offset);
+            Location { file: self.file.name.clone(), line, column }
+        }
+
+        /// Returns the part of the segment between the provided offsets, without the whitespace around it.
+        fn slice(&self, range: Range<usize>) -> Segment {
+            let text = &self.text()[range.clone()];
+            let start = self.range.start + range.start + (text.len() - text.trim_start().len());
+            Segment { file: self.file.clone(), range: start..start + text.trim().len() }
+        }
+    }
+
+    struct Macro {
+        params: Vec<String>,
+        body: Segment,
+        defined_at: Location,
+    }
+
+    /// What a parameter stands for in a call: the argument's text, expanded where it was written.
+    struct Arg {
+        text: Segment,
+        env: Option<Arc<Env>>,
+        calls: Arc<Vec<CallSite>>,
+        /// The macros that were being expanded where the argument was written, which are the only ones it can't call.
+        expanding: Vec<String>,
+    }
+
+    type Env = HashMap<String, Arg>;
+
+    /// The words that start a directive other than a macro call.
+    const RESERVED: [&str; 5] = ["define", "include", "label", "to", "by"];
+
+    struct Expander {
+        macros: HashMap<String, Macro>,
+        /// The files being included, outermost first, to find include cycles.
+        including: Vec<PathBuf>,
+        /// The macros being expanded, outermost first, to find macros that expand into themselves.
+        expanding: Vec<String>,
+        /// The number literal each label reference expands into, as resolved by the previous pass, in order.
+        literals: Vec<String>,
+        out: Expansion,
+    }
+
+    fn is_name_char(c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '_'
+    }
+
+    /// Returns the length of the name at the start of `text`.
+    fn name_len(text: &str) -> usize {
+        text.find(|c: char| !is_name_char(c)).unwrap_or(text.len())
+    }
+
+    /// Splits a list of arguments at the commas that aren't inside braces, parentheses or comments.
+    fn split_args(text: &str) -> Vec<Range<usize>> {
+        let mut args = vec![];
+        let (mut depth, mut start, mut in_comment) = (0, 0, false);
+        for (offset, c) in text.char_indices() {
+            match c {
+                '`' => in_comment = !in_comment,
+                _ if in_comment => {}
+                '{' | '(' => depth += 1,
+                '}' | ')' => depth -= 1,
+                ',' if depth == 0 => {
+                    args.push(start..offset);
+                    start = offset + 1;
+                }
+                _ => {}
+            }
+        }
+        args.push(start..text.len());
+        args
+    }
+
+    impl Expander {
+        fn fail(segment: &Segment, offset: usize, error: Error, msg: &str) -> Result<(), (Error, String)> {
+            Err((error, format!("{}: {msg}", segment.location(offset))))
+        }
+
+        fn emit(&mut self, c: char, at: Location, calls: &Arc<Vec<CallSite>>) {
+            if !c.is_whitespace() {
+                self.out.offsets.push(self.out.source.len());
+                self.out.origins.push(Origin { at, calls: calls.clone() });
+            }
+            self.out.source.push(c);
+        }
+
+        /// Expands a segment, where `env` holds the arguments of the macro call whose body it is part of, if any,
+        /// and `calls` the macro calls being expanded.
+        fn expand(&mut self, segment: &Segment, env: Option<&Arc<Env>>, calls: &Arc<Vec<CallSite>>) -> Result<(), (Error, String)> {
+            let text = segment.text();
+            let mut offset = 0;
+            while let Some(c) = text[offset..].chars().next() {
+                match c {
+                    '`' if let Some(len) = text[offset + 1..].find('`') => {
+                        self.out.source.push_str(&text[offset..offset + len + 2]);
+                        offset += len + 2;
+                    }
+                    '{' => {
+                        let Some(close) = closing_brace(&text[offset..]) else {
+                            return Self::fail(segment, offset, Error::SyntaxError, "`{` does not have a corresponding `}`")
+                        };
+                        self.directive(segment, offset, offset + 1..offset + close, env, calls)?;
+                        offset += close + 1;
+                    }
+                    '$' if let Some(env) = env => {
+                        let len = name_len(&text[offset + 1..]);
+                        let name = &text[offset + 1..offset + 1 + len];
+                        let Some(arg) = env.get(name) else {
+                            return Self::fail(segment, offset, Error::SyntaxError, &format!("`${name}` is not a parameter of the macro it is in"))
+                        };
+                        let expanding = std::mem::replace(&mut self.expanding, arg.expanding.clone());
+                        let expanded = self.expand(&arg.text, arg.env.as_ref(), &arg.calls);
+                        self.expanding = expanding;
+                        expanded?;
+                        offset += 1 + len;
+                    }
+                    _ => {
+                        self.emit(c, segment.location(offset), calls);
+                        offset += c.len_utf8();
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// Runs the directive between the braces at `inner`, whose `{` is at `start`.
+        fn directive(&mut self, segment: &Segment, start: usize, inner: Range<usize>, env: Option<&Arc<Env>>, calls: &Arc<Vec<CallSite>>) -> Result<(), (Error, String)> {
+            let text = &segment.text()[inner.clone()];
+            let lead = text.len() - text.trim_start().len();
+            let word_len = name_len(&text[lead..]);
+            let word = &text[lead..lead + word_len];
+            let rest = inner.start + lead + word_len..inner.end;
+            match word {
+                "" => Self::fail(segment, start, Error::SyntaxError, "expected `define`, `include`, `label`, `to`, `by` or the name of a macro after `{`"),
+                "define" | "include" if env.is_some() => {
+                    Self::fail(segment, start, Error::SyntaxError, &format!("`{word}` can't be used in a macro's body"))
+                }
+                "define" => self.define(segment, start, rest),
+                "include" => self.include(segment, start, rest, calls),
+                "label" | "to" | "by" => {
+                    let name = segment.text()[rest].trim();
+                    if name.is_empty() || name_len(name) != name.len() {
+                        return Self::fail(segment, start, Error::SyntaxError, &format!("expected the name of a label after `{word}`"))
+                    }
+                    match word {
+                        "label" => self.label(segment, start, name, calls),
+                        "to" => self.reference(segment, start, name, ReferenceKind::Absolute, calls),
+                        _ => self.reference(segment, start, name, ReferenceKind::Relative, calls)
+                    }
+                }
+                name => self.call(segment, start, name, rest, env, calls),
+            }
+        }
+
+        fn define(&mut self, segment: &Segment, start: usize, rest: Range<usize>) -> Result<(), (Error, String)> {
+            let text = &segment.text()[rest.clone()];
+            let lead = text.len() - text.trim_start().len();
+            let len = name_len(&text[lead..]);
+            let name = &text[lead..lead + len];
+            if name.is_empty() || RESERVED.contains(&name) {
+                return Self::fail(segment, start, Error::SyntaxError, "expected the name of the macro after `define`")
+            }
+            let mut body = rest.start + lead + len..rest.end;
+            let mut params = vec![];
+            if text[lead + len..].starts_with('(') {
+                let Some(close) = text[lead + len..].find(')') else {
+                    return Self::fail(segment, start, Error::SyntaxError, &format!("the parameters of `{name}` do not end with `)`"))
+                };
+                let list = &text[lead + len + 1..lead + len + close];
+                if !list.trim().is_empty() {
+                    for param in list.split(',').map(str::trim) {
+                        if param.is_empty() || name_len(param) != param.len() || params.iter().any(|p| p == param) {
+                            return Self::fail(segment, start, Error::SyntaxError, &format!("`{param}` is not a valid parameter name for `{name}`"))
+                        }
+                        params.push(param.to_string());
+                    }
+                }
+                body.start += close + 1;
+            }
+            let defined_at = segment.location(start);
+            if let Some(existing) = self.macros.get(name) {
+                return Self::fail(segment, start, Error::SyntaxError, &format!("`{name}` is already defined at {}", existing.defined_at))
+            }
+            let body = segment.slice(body);
+            self.macros.insert(name.to_string(), Macro { params, body, defined_at });
+            Ok(())
+        }
+
+        fn include(&mut self, segment: &Segment, start: usize, rest: Range<usize>, calls: &Arc<Vec<CallSite>>) -> Result<(), (Error, String)> {
+            let text = segment.text()[rest].trim();
+            let Some(relative) = text.strip_prefix('"').and_then(|text| text.strip_suffix('"')) else {
+                return Self::fail(segment, start, Error::SyntaxError, "expected a path in double quotes after `include`")
+            };
+            let path = segment.file.path.parent().unwrap_or(Path::new("")).join(relative);
+            let Ok(text) = fs::read_to_string(&path) else {
+                return Self::fail(segment, start, Error::FileError, &format!("the file {path:?} does not exist or cannot be read"))
+            };
+            let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+            if let Some(first) = self.including.iter().position(|including| *including == canonical) {
+                let cycle: Vec<String> = self.including[first..].iter().chain([&canonical]).map(|path| format!("{path:?}")).collect();
+                return Self::fail(segment, start, Error::SyntaxError, &format!("including {path:?} makes a cycle: {}", cycle.join(" includes ")))
+            }
+            let file = Arc::new(SourceFile { name: path.to_string_lossy().into(), path, text });
+            self.including.push(canonical);
+            self.expand(&Segment { range: 0..file.text.len(), file }, None, calls)?;
+            self.including.pop();
+            Ok(())
+        }
+
+        fn label(&mut self, segment: &Segment, start: usize, name: &str, calls: &Arc<Vec<CallSite>>) -> Result<(), (Error, String)> {
+            if let Some(existing) = self.out.labels.iter().find(|label| label.name == name) {
+                let msg = format!("the label `{name}` is already at {}{}", existing.at, segment_chain(calls));
+                return Self::fail(segment, start, Error::SyntaxError, &msg)
+            }
+            let index = self.out.origins.len();
+            self.out.labels.push(Label { name: name.to_string(), at: segment.location(start), index });
+            Ok(())
+        }
+
+        /// Expands a reference into the literal the previous pass resolved it to, to be checked against the labels once every one is known.
+        fn reference(&mut self, segment: &Segment, start: usize, name: &str, kind: ReferenceKind, calls: &Arc<Vec<CallSite>>) -> Result<(), (Error, String)> {
+            let at = segment.location(start);
+            let literal = self.literals.get(self.out.references.len()).cloned().unwrap_or_else(|| "#0".to_string());
+            let first = self.out.origins.len();
+            for c in literal.chars() {
+                self.emit(c, at.clone(), calls);
+            }
+            self.out.references.push(Reference { name: name.to_string(), at, kind, commands: first..self.out.origins.len() });
+            Ok(())
+        }
+
+        fn call(&mut self, segment: &Segment, start: usize, name: &str, rest: Range<usize>, env: Option<&Arc<Env>>, calls: &Arc<Vec<CallSite>>) -> Result<(), (Error, String)> {
+            let Some(called) = self.macros.get(name) else {
+                return Self::fail(segment, start, Error::SyntaxError, &format!("`{name}` is not a defined macro"))
+            };
+            let text = segment.text()[rest.clone()].trim();
+            let args: Vec<Segment> = if text.is_empty() {
+                vec![]
+            } else if text.starts_with('(') && text.ends_with(')') {
+                let open = rest.start + segment.text()[rest.clone()].find('(').unwrap() + 1;
+                let close = rest.start + segment.text()[rest.clone()].rfind(')').unwrap();
+                let inner = &segment.text()[open..close];
+                if inner.trim().is_empty() {
+                    vec![]
+                } else {
+                    split_args(inner).into_iter().map(|arg| segment.slice(open + arg.start..open + arg.end)).collect()
+                }
+            } else {
+                return Self::fail(segment, start, Error::SyntaxError, &format!("the arguments of `{name}` must be in parentheses"))
+            };
+            if args.len() != called.params.len() {
+                return Self::fail(segment, start, Error::SyntaxError, &format!("`{name}` takes {} arguments but was given {}", called.params.len(), args.len()))
+            }
+            if let Some(first) = self.expanding.iter().position(|expanding| expanding == name) {
+                let cycle: Vec<String> = self.expanding[first..].iter().chain([&name.to_string()]).map(|name| format!("`{name}`")).collect();
+                return Self::fail(segment, start, Error::SyntaxError, &format!("`{name}` expands into itself: {}", cycle.join(" calls ")))
+            }
+
+            let env: Env = called.params.iter().cloned().zip(args).map(|(param, text)| {
+                (param, Arg { text, env: env.cloned(), calls: calls.clone(), expanding: self.expanding.clone() })
+            }).collect();
+            let body = called.body.clone();
+            let at = segment.location(start);
+            let inner_calls = Arc::new([CallSite { name: name.to_string(), at: at.clone() }].into_iter().chain(calls.iter().cloned()).collect());
+            let index = self.out.calls.len();
+            let first = self.out.origins.len();
+            self.out.calls.push(Call { name: name.to_string(), at, commands: first..first });
+            self.expanding.push(name.to_string());
+            self.expand(&body, Some(&Arc::new(env)), &inner_calls)?;
+            self.expanding.pop();
+            self.out.calls[index].commands.end = self.out.origins.len();
+            Ok(())
+        }
+    }
+
+    /// Describes the macro calls being expanded, like [`Origin::call_chain`].
+    fn segment_chain(calls: &[CallSite]) -> String {
+        calls.iter().map(|call| format!(", in `{}` called at {}", call.name, call.at)).collect()
+    }
+
+    /// Returns the number literal a reference expands into, given the literal it expanded into on the previous pass.
+    /// The literal never gets shorter, with its digits padded with zeros instead, so that resolving the references always ends.
+    fn resolve(reference: &Reference, labels: &[Label], previous: &str) -> Result<String, (Error, String)> {
+        let fail = |msg: String| Err((Error::SyntaxError, format!("{}: {msg}", reference.at)));
+        let Some(label) = labels.iter().find(|label| label.name == reference.name) else {
+            return fail(format!("there is no label `{}`", reference.name))
+        };
+        // a jump continues at the command after its target, so it targets the command before the label
+        let Some(target) = label.index.checked_sub(1) else {
+            return fail(format!("the label `{}` is before the first command, which a jump can't continue at", reference.name))
+        };
+        let value = match reference.kind {
+            ReferenceKind::Absolute => target as isize,
+            ReferenceKind::Relative => target as isize - reference.commands.end as isize
+        };
+        let sign = if value < 0 { "-" } else { "" };
+        let digits = value.unsigned_abs().to_string();
+        let width = previous.len().saturating_sub(1 + sign.len()).max(digits.len());
+        Ok(format!("#{digits:0>width$}{sign}"))
+    }
+
+    /// Expands the macros and includes of a program's source, where `path` is the file it was read from.
+    ///
+    /// `{define name(a, b) body}` defines a macro, and `{name(x, y)}` (or `{name}` for a macro without parameters) is replaced by its body,
+    /// where `$a` and `$b` stand for `x` and `y`. `{include "path.gur"}` is replaced by the expansion of the file at a path relative to the file it is in.
+    /// Definitions and includes can't be in a macro's body.
+    ///
+    /// `{label name}` marks the command after it, and `{to name}` and `{by name}` are replaced by the number literal that makes a `j` or a `J`
+    /// right after them continue at that command. Since the literals' lengths shift the indices after them, the program is expanded again
+    /// until every literal stays the same. The error is a [`SyntaxError`](Error::SyntaxError), or a [`FileError`](Error::FileError)
+    /// if an included file can't be read, and its message starts with where it is.
+    pub fn expand_source(src: &str, path: &Path) -> Result<Expansion, (Error, String)> {
+        let name: Arc<str> = path.to_string_lossy().into();
+        let file = Arc::new(SourceFile { name: name.clone(), path: path.to_path_buf(), text: src.to_string() });
+        let mut literals = vec![];
+        loop {
+            let mut expander = Expander {
+                macros: HashMap::new(),
+                including: vec![path.canonicalize().unwrap_or_else(|_| path.to_path_buf())],
+                expanding: vec![],
+                literals,
+                out: Expansion {
+                    source: "".to_string(), origins: vec![], calls: vec![], labels: vec![], references: vec![], offsets: vec![], root: name.clone()
+                },
+            };
+            expander.expand(&Segment { range: 0..file.text.len(), file: file.clone() }, None, &Arc::new(vec![]))?;
+            let out = expander.out;
+            let resolved = out.references.iter().enumerate()
+                .map(|(index, reference)| resolve(reference, &out.labels, expander.literals.get(index).map_or("", String::as_str)))
+                .collect::<Result<Vec<String>, _>>()?;
+            if resolved == expander.literals {
+                return Ok(out)
+            }
+            literals = resolved;
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// Returns the text of the source each command of a program's expansion is shown at.
+        fn shown_at(source: &str) -> Vec<&str> {
+            let expansion = expand_source(source, Path::new("visualized.gur")).unwrap();
+            expansion.source_spans(source).into_iter().map(|span| &source[span]).collect()
+        }
+
+        #[test]
+        fn commands_from_macros_are_shown_at_their_calls() {
+            assert_eq!(shown_at("{define s(n) #$n U}\n{s(12)} i\n."), ["{s(12)}", "{s(12)}", "{s(12)}", "{s(12)}", "i", "."]);
+            assert_eq!(shown_at("`a comment`\n{to end} j / {label end} ."), ["{to end}", "{to end}", "j", "/", "."]);
+        }
+    }
+}
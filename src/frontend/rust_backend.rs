@@ -0,0 +1,359 @@
+pub mod rs {
+    use crate::lexer::lex::*;
+
+    /// Everything a compiled program needs besides its commands. It mirrors the interpreter:
+    /// the same tape of optional cells, the same checks in the same order, and the same messages and exit statuses.
+    const RUNTIME: &str = r#"#![allow(dead_code, unused_mut, unused_imports, unused_assignments, unreachable_code, clippy::all)]
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::{self, BufWriter, Stdout, Write};
+use std::process::exit;
+
+struct Machine {
+    tape: HashMap<isize, isize>,
+    acc: Option<isize>,
+    dp: isize,
+    dir: isize,
+    pc: usize,
+    current: &'static str,
+    last: &'static str,
+    creating_number: bool,
+    out: BufWriter<Stdout>,
+}
+
+impl Machine {
+    fn new() -> Machine {
+        Machine {
+            tape: HashMap::new(),
+            acc: None,
+            dp: 0,
+            dir: 1,
+            pc: 0,
+            current: "<none>",
+            last: "<none>",
+            creating_number: false,
+            out: BufWriter::new(io::stdout()),
+        }
+    }
+
+    fn cell(&self) -> Option<isize> {
+        self.tape.get(&self.dp).copied()
+    }
+
+    /// Prints an error and the details, then exits with the error's status.
+    fn fail(&mut self, error: &str, status: i32, msg: &str) -> ! {
+        self.throw(error, msg);
+        self.details();
+        exit(status)
+    }
+
+    fn throw(&mut self, error: &str, msg: &str) {
+        self.out.flush().unwrap();
+        eprintln!("\x1b[31;1m{error}:\x1b[0m {msg}");
+    }
+
+    fn details(&self) {
+        let show = |value: Option<isize>| value.map_or("<none>".to_string(), |n| n.to_string());
+        eprintln!("\x1b[33;1m[Details]\x1b[0m
+\x1b[1m*\x1b[0m {}
+\x1b[1m*\x1b[0m code pointer index: {}
+\x1b[1m*\x1b[0m data pointer index: {}
+\x1b[1m*\x1b[0m data pointer direction: {}
+\x1b[1m*\x1b[0m current command: {}
+\x1b[1m*\x1b[0m previous command executed: {}
+\x1b[1m*\x1b[0m current cell value: {}
+\x1b[1m*\x1b[0m left cell value: {}",
+            self.acc.map_or("accumulator was empty".to_string(), |n| format!("accumulator value: {n}")),
+            self.pc,
+            self.dp,
+            if self.dir == 1 { "positive" } else { "negative" },
+            self.current,
+            self.last,
+            show(self.cell()),
+            match self.dp.checked_sub(1) {
+                Some(left) => show(self.tape.get(&left).copied()),
+                None => "<cell does not exist>".to_string()
+            }
+        );
+    }
+
+    /// Returns the left and current cells for a command that combines them into the accumulator.
+    fn operands(&mut self, symbol: char, divides: bool) -> (isize, isize) {
+        let left = self.dp.checked_sub(1).and_then(|left| self.tape.get(&left).copied());
+        let (Some(l), Some(c)) = (left, self.cell()) else {
+            self.fail("OpError", 12, &format!("execution of `{symbol}` went wrong"))
+        };
+        if divides && c == 0 {
+            self.fail("OpError", 12, &format!("division by zero caused by `{symbol}`"))
+        }
+        if self.acc.is_some() {
+            self.fail("AccumulatorError", 13, &format!("execution of `{symbol}` went wrong"))
+        }
+        (l, c)
+    }
+
+    fn checked(&mut self, symbol: char, (result, overflowed): (isize, bool)) {
+        if overflowed {
+            self.fail("OverflowError", 17, &format!("command `{symbol}` caused overflow"))
+        }
+        self.acc = Some(result);
+    }
+
+    fn need_acc(&mut self, error: &str, status: i32, symbol: char) -> isize {
+        match self.acc {
+            Some(a) => a,
+            None => self.fail(error, status, &format!("execution of `{symbol}` went wrong"))
+        }
+    }
+
+    fn need_cell(&mut self, msg: &str) -> isize {
+        match self.cell() {
+            Some(c) => c,
+            None => self.fail("OpError", 12, msg)
+        }
+    }
+
+    fn read_line(&mut self) -> String {
+        self.out.flush().unwrap();
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input).is_err() {
+            self.throw("InputError", "invalid input");
+            exit(15)
+        }
+        input.pop();
+        input
+    }
+
+    /// Jumps to the matching `@` of a conditional whose condition holds.
+    fn jump(&mut self, symbol: char, target: Option<usize>) {
+        match target {
+            Some(target) => self.pc = target,
+            None => self.fail("SyntaxError", 14, &format!("conditional `{symbol}` does not have a corresponding `@`"))
+        }
+    }
+
+    fn move_until_empty(&mut self) {
+        while self.tape.contains_key(&self.dp) {
+            match self.dp.checked_add(self.dir) {
+                Some(dp) => self.dp = dp,
+                None => self.fail("OverflowError", 17, "data pointer went out of bounds when executing `M`")
+            }
+        }
+    }
+
+    /// Moves to the nearest full cell, looking at the full cells rather than walking over the empty ones, which may go on forever.
+    fn move_until_full(&mut self) {
+        let (dp, dir) = (self.dp, self.dir);
+        let ahead = self.tape.keys().copied().filter(|&cell| if dir > 0 { cell >= dp } else { cell <= dp });
+        match if dir > 0 { ahead.min() } else { ahead.max() } {
+            Some(dp) => self.dp = dp,
+            None => {
+                self.dp = if dir > 0 { isize::MAX } else { isize::MIN };
+                self.fail("OverflowError", 17, "data pointer went out of bounds when executing `m`")
+            }
+        }
+    }
+
+    fn step(&mut self, moving: isize) {
+        if self.pc + 1 >= LEN {
+            self.fail("OutOfBoundsError", 16, "code pointer went out of bounds")
+        }
+        if self.dp.checked_add(moving * self.dir).is_none() {
+            self.fail("OutOfBoundsError", 16, "data pointer went out of bounds")
+        }
+        self.last = self.current;
+        self.pc += 1;
+        self.dp += moving * self.dir;
+    }
+}
+"#;
+
+    /// Compiles a program into the source of a standalone Rust program that behaves like the interpreter running it,
+    /// down to the errors it throws and their messages. The compiled program accepts `--details` and `--exit-with-acc` like `guryvsr` does.
+    ///
+    /// Every command becomes an arm of a `match` on the code pointer, which rustc turns into a jump table, so `J` and `j` can jump to any command.
+    pub fn compile_rust(tokens: &[Token]) -> String {
+        let matches = match_conditionals(tokens);
+        let symbols: Vec<String> = tokens.iter().map(|token| format!("{:?}", token_to_symbol(token))).collect();
+        let mut arms = "".to_string();
+        for (index, token) in tokens.iter().enumerate() {
+            let arm = arm(token, matches[index]);
+            let arm = arm.trim_end().replace('\n', "\n                ");
+            arms.push_str(&format!("            {index} => {{\n                {arm}\n            }}\n"));
+        }
+        format!("{RUNTIME}
+const LEN: usize = {};
+const SYMBOLS: [&str; {0}] = [{}];
+
+fn main() {{
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let details = args.iter().any(|arg| arg == \"--details\" || arg == \"-d\");
+    let exit_with_acc = args.iter().any(|arg| arg == \"--exit-with-acc\");
+    let mut m = Machine::new();
+    while m.pc < LEN {{
+        let mut moving = 1;
+        m.current = SYMBOLS[m.pc];
+        match m.pc {{
+{arms}            _ => unreachable!()
+        }}
+        m.step(moving);
+    }}
+}}
+", tokens.len(), symbols.join(", "))
+    }
+
+    /// Returns the body of the arm that runs a command.
+    fn arm(token: &Token, target: Option<usize>) -> String {
+        let symbol = token_to_symbol(token).chars().next().unwrap();
+        let reset = match token {
+            Token::CreatingNumber | Token::Digit(_) => "",
+            _ => "m.creating_number = false;\n"
+        };
+        let body = match *token {
+            Token::NoOp | Token::DestinationIfTrue | Token::Nothing => "".to_string(),
+            Token::Stop => "let mut status = 0;
+if let (true, Some(value)) = (exit_with_acc, m.acc) {
+    if !(0..=255).contains(&value) {
+        m.fail(\"OverflowError\", 17, &format!(\"exit status {value} is not between 0 and 255\"))
+    }
+    status = value as i32;
+}
+if details {
+    writeln!(m.out).unwrap();
+    m.out.flush().unwrap();
+    m.details();
+}
+m.out.flush().unwrap();
+exit(status)".to_string(),
+            Token::CreatingNumber => "if m.creating_number {
+    m.fail(\"SyntaxError\", 14, \"already creating number\")
+}
+if m.acc.is_some() {
+    m.fail(\"AccumulatorError\", 13, \"execution of `#` went wrong\")
+}
+m.creating_number = true;
+moving = 0;".to_string(),
+            Token::Digit(n) => format!("if !m.creating_number {{
+    m.fail(\"SyntaxError\", 14, \"execution of `{n}` went wrong\")
+}}
+m.acc = match m.acc {{
+    Some(a) => match a.checked_mul(10).and_then(|a| a.checked_add({n})) {{
+        Some(a) => Some(a),
+        None => m.fail(\"OverflowError\", 17, \"digit {n} caused overflow\")
+    }},
+    None => Some({n})
+}};
+moving = 0;"),
+            Token::Unload => format!("let a = m.need_acc(\"AccumulatorError\", 13, '{symbol}');
+m.tape.insert(m.dp, a);
+m.acc = None;"),
+            Token::Distribute => format!("let a = m.need_acc(\"AccumulatorError\", 13, '{symbol}');
+m.tape.insert(m.dp, a);"),
+            Token::Recall => "m.need_cell(\"execution of `R` went wrong\");
+m.acc = m.tape.remove(&m.dp);".to_string(),
+            Token::Copy => "m.acc = Some(m.need_cell(\"execution of `r` went wrong\"));".to_string(),
+            Token::ClearAcc => "m.acc = None;".to_string(),
+            Token::ClearCurrCell => "m.tape.remove(&m.dp);".to_string(),
+            Token::ZeroOrEmpty => format!("if m.cell().unwrap_or(0) == 0 {{
+    m.jump('?', {target:?});
+}}"),
+            Token::NotZeroOrEmpty => format!("if m.cell().unwrap_or(0) != 0 {{
+    m.jump('!', {target:?});
+}}"),
+            Token::TgtZeroOrEmpty | Token::TgtNotZeroOrEmpty => format!("let a = m.need_acc(\"AccumulatorError\", 13, '{symbol}');
+if (m.tape.get(&a).copied().unwrap_or(0) == 0) == {} {{
+    m.jump('{symbol}', {target:?});
+}}
+m.acc = None;", *token == Token::TgtZeroOrEmpty),
+            Token::AccZeroOrEmpty => format!("if m.acc.unwrap_or(0) == 0 {{
+    m.jump('A', {target:?});
+}}"),
+            Token::AccNotZeroOrEmpty => format!("if m.acc.unwrap_or(0) != 0 {{
+    m.jump('a', {target:?});
+}}"),
+            Token::JumpCellsC => "let a = m.need_acc(\"OpError\", 12, 'J');
+m.acc = None;
+let target = if a < 0 { m.pc.checked_sub(a.unsigned_abs()) } else { m.pc.checked_add(a as usize) };
+match target {
+    Some(target) if target < LEN => m.pc = target,
+    _ => m.fail(\"OverflowError\", 17, \"code pointer went out of bounds when executing `J`\")
+}".to_string(),
+            Token::JumpToCellC => "let a = m.need_acc(\"OpError\", 12, 'j');
+if a < 0 || a >= LEN as isize {
+    m.fail(\"OverflowError\", 17, \"code pointer went out of bounds when executing `j`\")
+}
+m.pc = a as usize;
+m.acc = None;".to_string(),
+            Token::JumpCellsD => "let a = m.need_acc(\"OpError\", 12, 'K');
+m.acc = None;
+match m.dp.checked_add(a) {
+    Some(dp) => m.dp = dp,
+    None => m.fail(\"OverflowError\", 17, \"data pointer went out of bounds when executing `K`\")
+}
+moving = 0;".to_string(),
+            Token::JumpToCellD => "m.dp = m.need_acc(\"OpError\", 12, 'k');
+m.acc = None;
+moving = 0;".to_string(),
+            Token::MoveDUntilEmpty => "m.move_until_empty();
+moving = 0;".to_string(),
+            Token::MoveDUntilFull => "m.move_until_full();
+moving = 0;".to_string(),
+            Token::FlipD(_) => "m.dir = -m.dir;".to_string(),
+            Token::Add => "let (l, c) = m.operands('+', false);
+m.checked('+', l.overflowing_add(c));".to_string(),
+            Token::Mul => "let (l, c) = m.operands('*', false);
+m.checked('*', l.overflowing_mul(c));".to_string(),
+            Token::Div => "let (l, c) = m.operands('/', true);
+m.checked('/', l.overflowing_div_euclid(c));".to_string(),
+            Token::Mod => "let (l, c) = m.operands('%', true);
+m.checked('%', l.overflowing_rem(c));".to_string(),
+            Token::Neg => "let a = m.need_acc(\"AccumulatorError\", 13, '-');
+match a.checked_neg() {
+    Some(a) => m.acc = Some(a),
+    None => m.fail(\"OverflowError\", 17, \"command `-` caused overflow\")
+}".to_string(),
+            Token::Eq | Token::NotEq(_) | Token::Gt | Token::GE(_) | Token::Lt | Token::LE(_) | Token::BitAnd | Token::BitOr | Token::BitXor => {
+                let expression = match *token {
+                    Token::Eq => "isize::from(l == c)",
+                    Token::NotEq(_) => "isize::from(l != c)",
+                    Token::Gt => "isize::from(l > c)",
+                    Token::GE(_) => "isize::from(l >= c)",
+                    Token::Lt => "isize::from(l < c)",
+                    Token::LE(_) => "isize::from(l <= c)",
+                    Token::BitAnd => "l & c",
+                    Token::BitOr => "l | c",
+                    _ => "l ^ c",
+                };
+                format!("let (l, c) = m.operands('{symbol}', false);
+m.acc = Some({expression});")
+            }
+            Token::BitNot => "m.acc = Some(!m.need_cell(\"execution of `~` went wrong\"));".to_string(),
+            Token::OutputInt => "let c = m.need_cell(\"something went wrong while executing `i`\");
+write!(m.out, \"{c}\").unwrap();".to_string(),
+            Token::OutputChar => "let c = m.need_cell(\"something went wrong while executing `s`\");
+match u32::try_from(c).ok().and_then(char::from_u32) {
+    Some(chr) => write!(m.out, \"{chr}\").unwrap(),
+    None => m.fail(\"OpError\", 12, \"the current cell's value cannot be represented as a valid UTF-8 character\")
+}".to_string(),
+            Token::InputInt => "let input = m.read_line();
+match input.parse::<isize>() {
+    Ok(value) => m.acc = Some(value),
+    Err(_) => {
+        m.throw(\"InputError\", \"invalid input\");
+        exit(15)
+    }
+}
+moving = 0;".to_string(),
+            Token::InputStr => "let input = m.read_line();
+let mut curr = m.dp;
+for chr in input.chars() {
+    m.tape.insert(curr, chr as isize);
+    if curr.checked_add(1).is_none() {
+        m.fail(\"InputError\", 15, \"input too long; went beyond tape boundaries\")
+    }
+    curr += 1;
+}".to_string(),
+        };
+        format!("{reset}{body}")
+    }
+}
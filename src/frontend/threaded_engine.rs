@@ -0,0 +1,418 @@
+pub mod threaded {
+    use std::io::{self, BufWriter, Stdout, Write};
+    use crate::errors::err::*;
+    use crate::exec::exec::{print_details, Halt};
+    use crate::exec::prelude::prelude::*;
+    use crate::lexer::lex::*;
+
+    /// A command compiled into a closure. It runs the command, moves the data pointer, and returns the index of the command to run next,
+    /// or how the program ended if it stopped or threw an error.
+    type Op = Box<dyn Fn(&mut Machine) -> Result<usize, End>>;
+
+    /// How a program ended, once its error or details have been printed.
+    struct End {
+        status: i32,
+        code_ptr_index: usize,
+    }
+
+    struct Machine {
+        tape: Tape,
+        acc: Acc,
+        data_ptr_index: isize,
+        data_ptr_dir: isize,
+        creating_number: bool,
+        last_executed: Token,
+        out: BufWriter<Stdout>,
+        details: bool,
+        exit_with_acc: bool,
+    }
+
+    impl Machine {
+        /// Throws an error while running `current`, with `code_ptr_index` as the code pointer, then prints the details.
+        fn fail(&mut self, error: Error, msg: &str, code_ptr_index: usize, current: Token) -> End {
+            self.out.flush().unwrap();
+            error.throw(msg, false);
+            print_details(&self.acc, &self.tape, self.data_ptr_index, self.data_ptr_dir, code_ptr_index, &current, &self.last_executed);
+            End { status: error.exit_code(), code_ptr_index }
+        }
+
+        /// Reads a line of input for the command at `code_ptr_index`. Invalid input is thrown without the details.
+        fn read_line(&mut self, code_ptr_index: usize) -> Result<String, End> {
+            self.out.flush().unwrap();
+            let mut input = "".to_string();
+            if io::stdin().read_line(&mut input).is_err() {
+                Error::InputError.throw("invalid input", false);
+                return Err(End { status: Error::InputError.exit_code(), code_ptr_index })
+            }
+            input.pop();
+            Ok(input)
+        }
+
+        /// Returns the values of the cells to the left of and at the data pointer, or [`None`] if either is empty or doesn't exist.
+        fn operands(&self) -> Option<(isize, isize)> {
+            Some((self.tape.left_of(self.data_ptr_index).ok()??, self.tape.get(self.data_ptr_index)?))
+        }
+    }
+
+    /// What is known about a command when it is compiled.
+    #[derive(Clone, Copy)]
+    struct Site {
+        index: usize,
+        token: Token,
+        len: usize,
+        /// Whether the command is `#` or a digit, which are the only commands after which a number is still being created.
+        literal: bool,
+    }
+
+    impl Site {
+        /// Leaves the command with the code pointer at `code_ptr_index`, and returns the index of the command after it.
+        fn advance(self, m: &mut Machine, code_ptr_index: usize, moving: bool) -> Result<usize, End> {
+            if code_ptr_index + 1 >= self.len {
+                return Err(m.fail(Error::OutOfBoundsError, "code pointer went out of bounds", code_ptr_index, self.token))
+            }
+            if moving {
+                match m.data_ptr_index.checked_add(m.data_ptr_dir) {
+                    Some(index) => m.data_ptr_index = index,
+                    None => return Err(m.fail(Error::OutOfBoundsError, "data pointer went out of bounds", code_ptr_index, self.token))
+                }
+            }
+            m.last_executed = self.token;
+            m.creating_number = self.literal;
+            Ok(code_ptr_index + 1)
+        }
+
+        fn fail<T>(self, m: &mut Machine, error: Error, msg: &str) -> Result<T, End> {
+            Err(m.fail(error, msg, self.index, self.token))
+        }
+
+        fn symbol(self) -> String {
+            token_to_symbol(&self.token).to_string()
+        }
+    }
+
+    /// Compiles a command that runs `body` and then moves on to the next command, moving the data pointer unless `moving` is false.
+    fn simple(site: Site, moving: bool, body: impl Fn(&mut Machine) -> Result<(), End> + 'static) -> Op {
+        Box::new(move |m| {
+            body(m)?;
+            site.advance(m, site.index, moving)
+        })
+    }
+
+    /// Compiles a conditional that jumps to its matching `@` when `jumps` returns true, clearing the accumulator afterwards if `clears_acc` is set.
+    fn conditional(site: Site, target: Option<usize>, clears_acc: bool, jumps: impl Fn(&mut Machine) -> Result<bool, End> + 'static) -> Op {
+        let msg = format!("conditional `{}` does not have a corresponding `@`", site.symbol());
+        Box::new(move |m| {
+            let mut code_ptr_index = site.index;
+            if jumps(m)? {
+                match target {
+                    Some(target) => code_ptr_index = target,
+                    None => return site.fail(m, Error::SyntaxError, &msg)
+                }
+            }
+            if clears_acc {
+                m.acc.clear();
+            }
+            site.advance(m, code_ptr_index, true)
+        })
+    }
+
+    /// Compiles a command that combines the cells to the left of and at the data pointer into the accumulator.
+    /// `combine` returns [`None`] on overflow.
+    fn binary(site: Site, combine: fn(isize, isize) -> Option<isize>) -> Op {
+        let went_wrong = format!("execution of `{}` went wrong", site.symbol());
+        let division_by_zero = format!("division by zero caused by `{}`", site.symbol());
+        let overflow = format!("command `{}` caused overflow", site.symbol());
+        let divides = matches!(site.token, Token::Div | Token::Mod);
+        Box::new(move |m| {
+            let Some((left, current)) = m.operands() else { return site.fail(m, Error::OpError, &went_wrong) };
+            if divides && current == 0 {
+                return site.fail(m, Error::OpError, &division_by_zero)
+            }
+            if m.acc.is_not_empty() {
+                return site.fail(m, Error::AccumulatorError, &went_wrong)
+            }
+            match combine(left, current) {
+                Some(value) => m.acc.set_value(value),
+                None => return site.fail(m, Error::OverflowError, &overflow)
+            }
+            site.advance(m, site.index, true)
+        })
+    }
+
+    fn compile(site: Site, target: Option<usize>) -> Op {
+        let went_wrong = format!("execution of `{}` went wrong", site.symbol());
+        match site.token {
+            Token::NoOp | Token::DestinationIfTrue | Token::Nothing => simple(site, true, |_| Ok(())),
+            Token::Stop => Box::new(move |m| {
+                let mut status = 0;
+                if m.exit_with_acc && let Ok(value) = m.acc.get_value() {
+                    if !(0..=255).contains(&value) {
+                        return site.fail(m, Error::OverflowError, &format!("exit status {value} is not between 0 and 255"))
+                    }
+                    status = value as i32;
+                }
+                if m.details {
+                    writeln!(m.out).unwrap();
+                    m.out.flush().unwrap();
+                    print_details(&m.acc, &m.tape, m.data_ptr_index, m.data_ptr_dir, site.index, &site.token, &m.last_executed);
+                }
+                m.out.flush().unwrap();
+                Err(End { status, code_ptr_index: site.index })
+            }),
+            Token::CreatingNumber => simple(site, false, move |m| {
+                if m.creating_number {
+                    return site.fail(m, Error::SyntaxError, "already creating number")
+                }
+                if m.acc.is_not_empty() {
+                    return site.fail(m, Error::AccumulatorError, &went_wrong)
+                }
+                Ok(())
+            }),
+            Token::Digit(n) => Box::new(move |m| {
+                if !m.creating_number {
+                    return site.fail(m, Error::SyntaxError, &went_wrong)
+                }
+                if m.acc.append(n).is_err() {
+                    m.out.flush().unwrap();
+                    print_details(&m.acc, &m.tape, m.data_ptr_index, m.data_ptr_dir, site.index, &site.token, &m.last_executed);
+                    return Err(End { status: Error::OverflowError.exit_code(), code_ptr_index: site.index })
+                }
+                site.advance(m, site.index, false)
+            }),
+            Token::Unload | Token::Distribute => simple(site, true, move |m| {
+                let Ok(value) = m.acc.get_value() else { return site.fail(m, Error::AccumulatorError, &went_wrong) };
+                m.tape.set(m.data_ptr_index, value);
+                if site.token == Token::Unload {
+                    m.acc.clear();
+                }
+                Ok(())
+            }),
+            Token::Recall | Token::Copy => simple(site, true, move |m| {
+                let Some(value) = m.tape.get(m.data_ptr_index) else { return site.fail(m, Error::OpError, &went_wrong) };
+                m.acc.set_value(value);
+                if site.token == Token::Recall {
+                    m.tape.clear(m.data_ptr_index);
+                }
+                Ok(())
+            }),
+            Token::ClearAcc => simple(site, true, |m| { m.acc.clear(); Ok(()) }),
+            Token::ClearCurrCell => simple(site, true, |m| { m.tape.clear(m.data_ptr_index); Ok(()) }),
+            Token::ZeroOrEmpty => conditional(site, target, false, |m| Ok(m.tape.get(m.data_ptr_index).is_none_or(|value| value == 0))),
+            Token::NotZeroOrEmpty => conditional(site, target, false, |m| Ok(m.tape.get(m.data_ptr_index).is_some_and(|value| value != 0))),
+            Token::TgtZeroOrEmpty | Token::TgtNotZeroOrEmpty => {
+                let zero = site.token == Token::TgtZeroOrEmpty;
+                conditional(site, target, true, move |m| {
+                    let Ok(index) = m.acc.get_value() else { return site.fail(m, Error::AccumulatorError, &went_wrong) };
+                    Ok(m.tape.get(index).is_none_or(|value| value == 0) == zero)
+                })
+            }
+            Token::AccZeroOrEmpty => conditional(site, target, false, |m| Ok(m.acc.get_value().map_or(true, |value| value == 0))),
+            Token::AccNotZeroOrEmpty => conditional(site, target, false, |m| Ok(m.acc.get_value().is_ok_and(|value| value != 0))),
+            Token::JumpCellsC => Box::new(move |m| {
+                let Some(value) = m.acc.clear() else { return site.fail(m, Error::OpError, &went_wrong) };
+                let will_overflow = if value < 0 {
+                    site.index.overflowing_sub(value.unsigned_abs())
+                } else {
+                    site.index.overflowing_add(value as usize)
+                };
+                if will_overflow.1 || will_overflow.0 >= site.len {
+                    return site.fail(m, Error::OverflowError, "code pointer went out of bounds when executing `J`")
+                }
+                site.advance(m, will_overflow.0, true)
+            }),
+            Token::JumpToCellC => Box::new(move |m| {
+                let Ok(value) = m.acc.get_value() else { return site.fail(m, Error::OpError, &went_wrong) };
+                if value >= site.len as isize || value < 0 {
+                    return site.fail(m, Error::OverflowError, "code pointer went out of bounds when executing `j`")
+                }
+                m.acc.clear();
+                site.advance(m, value as usize, true)
+            }),
+            Token::JumpCellsD => simple(site, false, move |m| {
+                let Some(value) = m.acc.clear() else { return site.fail(m, Error::OpError, &went_wrong) };
+                match m.data_ptr_index.checked_add(value) {
+                    Some(index) => m.data_ptr_index = index,
+                    None => return site.fail(m, Error::OverflowError, "data pointer went out of bounds when executing `K`")
+                }
+                Ok(())
+            }),
+            Token::JumpToCellD => simple(site, false, move |m| {
+                let Some(value) = m.acc.clear() else { return site.fail(m, Error::OpError, &went_wrong) };
+                m.data_ptr_index = value;
+                Ok(())
+            }),
+            Token::MoveDUntilEmpty => simple(site, false, move |m| {
+                while m.tape.cell_is_full(m.data_ptr_index) {
+                    match m.data_ptr_index.checked_add(m.data_ptr_dir) {
+                        Some(index) => m.data_ptr_index = index,
+                        None => return site.fail(m, Error::OverflowError, "data pointer went out of bounds when executing `M`")
+                    }
+                }
+                Ok(())
+            }),
+            Token::MoveDUntilFull => simple(site, false, move |m| {
+                match m.tape.next_full(m.data_ptr_index, m.data_ptr_dir) {
+                    Some(index) => m.data_ptr_index = index,
+                    None => {
+                        m.data_ptr_index = if m.data_ptr_dir > 0 { isize::MAX } else { isize::MIN };
+                        return site.fail(m, Error::OverflowError, "data pointer went out of bounds when executing `m`")
+                    }
+                }
+                Ok(())
+            }),
+            Token::FlipD(_) => simple(site, true, |m| { m.data_ptr_dir *= -1; Ok(()) }),
+            Token::Add => binary(site, isize::checked_add),
+            Token::Neg => simple(site, true, move |m| {
+                let Ok(value) = m.acc.get_value() else { return site.fail(m, Error::AccumulatorError, &went_wrong) };
+                let Some(value) = value.checked_neg() else { return site.fail(m, Error::OverflowError, "command `-` caused overflow") };
+                m.acc.set_value(value);
+                Ok(())
+            }),
+            Token::Mul => binary(site, isize::checked_mul),
+            Token::Div => binary(site, |left, current| match left.overflowing_div_euclid(current) {
+                (value, false) => Some(value),
+                _ => None
+            }),
+            Token::Mod => binary(site, |left, current| match left.overflowing_rem(current) {
+                (value, false) => Some(value),
+                _ => None
+            }),
+            Token::Eq => binary(site, |left, current| Some(isize::from(left == current))),
+            Token::NotEq(_) => binary(site, |left, current| Some(isize::from(left != current))),
+            Token::Gt => binary(site, |left, current| Some(isize::from(left > current))),
+            Token::GE(_) => binary(site, |left, current| Some(isize::from(left >= current))),
+            Token::Lt => binary(site, |left, current| Some(isize::from(left < current))),
+            Token::LE(_) => binary(site, |left, current| Some(isize::from(left <= current))),
+            Token::BitAnd => binary(site, |left, current| Some(left & current)),
+            Token::BitOr => binary(site, |left, current| Some(left | current)),
+            Token::BitXor => binary(site, |left, current| Some(left ^ current)),
+            Token::BitNot => simple(site, true, move |m| {
+                let Some(value) = m.tape.get(m.data_ptr_index) else { return site.fail(m, Error::OpError, &went_wrong) };
+                m.acc.set_value(!value);
+                Ok(())
+            }),
+            Token::OutputInt => simple(site, true, move |m| {
+                let Some(value) = m.tape.get(m.data_ptr_index) else { return site.fail(m, Error::OpError, "something went wrong while executing `i`") };
+                write!(m.out, "{value}").unwrap();
+                Ok(())
+            }),
+            Token::OutputChar => simple(site, true, move |m| {
+                let Some(value) = m.tape.get(m.data_ptr_index) else { return site.fail(m, Error::OpError, "something went wrong while executing `s`") };
+                let Some(chr) = u32::try_from(value).ok().and_then(char::from_u32) else {
+                    return site.fail(m, Error::OpError, "the current cell's value cannot be represented as a valid UTF-8 character")
+                };
+                write!(m.out, "{chr}").unwrap();
+                Ok(())
+            }),
+            Token::InputInt => simple(site, false, move |m| {
+                let Ok(value) = m.read_line(site.index)?.parse::<isize>() else {
+                    Error::InputError.throw("invalid input", false);
+                    return Err(End { status: Error::InputError.exit_code(), code_ptr_index: site.index })
+                };
+                m.acc.set_value(value);
+                Ok(())
+            }),
+            Token::InputStr => simple(site, true, move |m| {
+                let mut curr = m.data_ptr_index;
+                for chr in m.read_line(site.index)?.chars() {
+                    m.tape.set(curr, chr as isize);
+                    match curr.checked_add(1) {
+                        Some(next) => curr = next,
+                        None => return site.fail(m, Error::InputError, "input too long; went beyond tape boundaries")
+                    }
+                }
+                Ok(())
+            }),
+        }
+    }
+
+    /// Compiles the `#` at `index` into a single op that runs the whole number literal it begins, along with the `J` or `j` right after it
+    /// (through a `-` if the literal is negated), so that a jump to a constant target is one call instead of one for each of its commands.
+    /// The commands after the `#` keep their own ops, for jumps that land inside the literal.
+    /// If the `#` would throw an error or moving the data pointer would take it out of bounds, `plain` runs the `#` on its own instead.
+    /// Returns `plain` if the literal has no digits or one of its commands can throw an error no matter what the state is.
+    fn fuse_literal(tokens: &[Token], index: usize, plain: Op) -> Op {
+        let mut value: isize = 0;
+        let mut end = index + 1;
+        while let Some(&Token::Digit(n)) = tokens.get(end) {
+            match value.checked_mul(10).and_then(|value| value.checked_add(n)) {
+                Some(next) => value = next,
+                None => return plain
+            }
+            end += 1;
+        }
+        if end == index + 1 || end >= tokens.len() {
+            return plain
+        }
+        let (negated, jump) = if tokens[end] == Token::Neg { (true, end + 1) } else { (false, end) };
+        let offset = if negated { -value } else { value };
+        let target = match tokens.get(jump) {
+            Some(Token::JumpToCellC) => usize::try_from(offset).ok(),
+            Some(Token::JumpCellsC) => jump.checked_add_signed(offset),
+            _ => None
+        }.filter(|&target| target + 1 < tokens.len());
+        match target {
+            Some(target) => {
+                let moves = if negated { 2 } else { 1 };
+                let token = tokens[jump];
+                Box::new(move |m| {
+                    if m.creating_number || m.acc.is_not_empty() {
+                        return plain(m)
+                    }
+                    let Some(data_ptr_index) = m.data_ptr_index.checked_add(moves * m.data_ptr_dir) else { return plain(m) };
+                    m.data_ptr_index = data_ptr_index;
+                    m.last_executed = token;
+                    m.creating_number = false;
+                    Ok(target + 1)
+                })
+            }
+            None => {
+                let last_digit = tokens[end - 1];
+                Box::new(move |m| {
+                    if m.creating_number || m.acc.is_not_empty() {
+                        return plain(m)
+                    }
+                    m.acc.set_value(value);
+                    m.last_executed = last_digit;
+                    m.creating_number = true;
+                    Ok(end)
+                })
+            }
+        }
+    }
+
+    /// Runs a program like [`execute`](crate::exec::exec::execute), but compiles every command into a closure first,
+    /// so that running a command is a single indirect call instead of a `match` on its token, and a number literal, with the jump that uses it, is one call in all.
+    /// Output is buffered, and written out before reading input and before the program ends.
+    pub fn execute_threaded(tokens: Vec<Token>, targets: &[Option<usize>], details: bool, exit_with_acc: bool) -> Halt {
+        let ops: Vec<Op> = tokens.iter().enumerate()
+            .map(|(index, &token)| {
+                let literal = matches!(token, Token::CreatingNumber | Token::Digit(_));
+                let op = compile(Site { index, token, len: tokens.len(), literal }, targets[index]);
+                if token == Token::CreatingNumber { fuse_literal(&tokens, index, op) } else { op }
+            })
+            .collect();
+        let mut m = Machine {
+            tape: Tape::new(),
+            acc: Acc::new(),
+            data_ptr_index: 0,
+            data_ptr_dir: 1,
+            creating_number: false,
+            last_executed: Token::Nothing,
+            out: BufWriter::new(io::stdout()),
+            details,
+            exit_with_acc,
+        };
+        let mut code_ptr_index = 0;
+        let end = loop {
+            if code_ptr_index >= ops.len() {
+                break End { status: 0, code_ptr_index }
+            }
+            match ops[code_ptr_index](&mut m) {
+                Ok(next) => code_ptr_index = next,
+                Err(end) => break end
+            }
+        };
+        Halt { status: end.status, acc: m.acc, tape: m.tape, code_ptr_index: end.code_ptr_index, data_ptr_index: m.data_ptr_index, data_ptr_dir: m.data_ptr_dir }
+    }
+}
@@ -0,0 +1,278 @@
+pub mod tui {
+    use std::io::{self, IsTerminal, Stdout, Write};
+    use std::ops::Range;
+    use std::time::{Duration, Instant};
+    use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+    use crossterm::style::{Print, PrintStyledContent, Stylize};
+    use crossterm::{cursor, execute, queue, terminal};
+    use crate::debugger::debug::*;
+    use crate::lexer::lex::*;
+    use crate::preprocessor::macros::Expansion;
+
+    /// The speeds a program can be played at, in steps per second.
+    const SPEEDS: [u32; 11] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 10000];
+    /// The most times the screen is drawn per second while playing. Faster speeds run several steps per frame.
+    const FRAMES_PER_SECOND: u32 = 30;
+    /// The width of a cell in the tape strip, including the space after it.
+    const CELL_WIDTH: usize = 8;
+    /// The number of rows that aren't the source: the title, the headings, the tape, the accumulator, the output and the help.
+    const FIXED_ROWS: usize = 12;
+    const OUTPUT_ROWS: usize = 3;
+
+    /// Puts the terminal in raw mode on the alternate screen, and restores it when dropped, even if drawing panics.
+    struct Screen;
+
+    impl Screen {
+        fn enter() -> io::Result<Screen> {
+            terminal::enable_raw_mode()?;
+            execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+            Ok(Screen)
+        }
+    }
+
+    impl Drop for Screen {
+        fn drop(&mut self) {
+            let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+            let _ = terminal::disable_raw_mode();
+        }
+    }
+
+    /// Reads a line of input for the program on the normal screen, since the program's input is read from the same terminal as the keys.
+    fn prompt_line(line: &mut String) -> io::Result<usize> {
+        execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen)?;
+        terminal::disable_raw_mode()?;
+        eprint!("the program is reading a line of input: ");
+        let read = io::stdin().read_line(line);
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        read
+    }
+
+    /// Shortens `text` to `width` characters by cutting off its start, so that the end of a large number stays visible.
+    fn fit(text: &str, width: usize) -> String {
+        let len = text.chars().count();
+        if len <= width {
+            return text.to_string()
+        }
+        format!("…{}", text.chars().skip(len + 1 - width).collect::<String>())
+    }
+
+    /// Moves to the start of the next row and clears it.
+    fn next_row(out: &mut Stdout, row: &mut u16) -> io::Result<()> {
+        queue!(out, cursor::MoveTo(0, *row), terminal::Clear(terminal::ClearType::CurrentLine))?;
+        *row += 1;
+        Ok(())
+    }
+
+    struct Visualizer {
+        machine: Machine,
+        source: String,
+        /// The byte range of each command in the source.
+        spans: Vec<Range<usize>>,
+        title: String,
+        output: String,
+        playing: bool,
+        /// An index into [`SPEEDS`].
+        speed: usize,
+    }
+
+    impl Visualizer {
+        /// Runs a step, returning false if the program can't step any further.
+        fn step(&mut self) -> bool {
+            let ended = self.machine.step().is_some();
+            self.output.push_str(&self.machine.take_output());
+            !ended
+        }
+
+        /// Handles a key press, returning false if it quits.
+        fn handle(&mut self, key: KeyEvent) -> bool {
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return false,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+                KeyCode::Char(' ') => self.playing = !self.playing && self.machine.outcome().is_none(),
+                KeyCode::Right | KeyCode::Char('l' | 's') => {
+                    self.playing = false;
+                    self.step();
+                }
+                KeyCode::Left | KeyCode::Char('h' | 'b') => {
+                    self.playing = false;
+                    self.machine.step_back();
+                }
+                KeyCode::Up | KeyCode::Char('+' | '=') => self.speed = (self.speed + 1).min(SPEEDS.len() - 1),
+                KeyCode::Down | KeyCode::Char('-') => self.speed = self.speed.saturating_sub(1),
+                _ => {}
+            }
+            true
+        }
+
+        fn draw(&self, out: &mut Stdout) -> io::Result<()> {
+            let (width, height) = terminal::size()?;
+            let (width, height) = (width as usize, height as usize);
+            let state = self.machine.state();
+            let mut row = 0;
+
+            let status = match self.machine.outcome() {
+                Some(_) => "ended".to_string(),
+                None if self.playing => format!("playing at {} steps/s", SPEEDS[self.speed]),
+                None => format!("paused ({} steps/s)", SPEEDS[self.speed])
+            };
+            next_row(out, &mut row)?;
+            let title = format!(" {} · step {} · {status}", self.title, self.machine.steps());
+            queue!(out, PrintStyledContent(format!("{:<width$}", fit(&title, width)).bold().reverse()))?;
+
+            // the source, scrolled so that the current command is in the middle
+            let source_rows = height.saturating_sub(FIXED_ROWS).max(1);
+            next_row(out, &mut row)?;
+            queue!(out, PrintStyledContent("Source".bold()))?;
+            let current = self.spans.get(state.code_ptr_index).cloned().unwrap_or(0..0);
+            let lines: Vec<(usize, &str)> = self.source.split('\n')
+                .scan(0, |start, line| {
+                    let item = (*start, line);
+                    *start += line.len() + 1;
+                    Some(item)
+                })
+                .collect();
+            let current_line = lines.iter().rposition(|(start, _)| *start <= current.start).unwrap_or(0);
+            let first = current_line.saturating_sub(source_rows / 2).min(lines.len().saturating_sub(source_rows));
+            let failed = matches!(self.machine.outcome(), Some(Outcome::Failed(_)));
+            for (number, (start, line)) in lines.iter().enumerate().skip(first).take(source_rows) {
+                next_row(out, &mut row)?;
+                let gutter = format!("{:>4} ", number + 1);
+                let room = width.saturating_sub(gutter.len());
+                queue!(out, PrintStyledContent(gutter.dim()))?;
+                let line = line.replace(['\t', '\r'], " ");
+                if number == current_line && !self.spans.is_empty() {
+                    let (before, rest) = line.split_at(current.start - start);
+                    let (command, after) = rest.split_at(current.len().min(rest.len()));
+                    // scroll the line so that the current command is visible
+                    let skip = (before.chars().count() + 1).saturating_sub(room);
+                    let before: String = before.chars().skip(skip).collect();
+                    let command = if failed { command.red().bold().reverse() } else { command.yellow().bold().reverse() };
+                    queue!(out, Print(&before), PrintStyledContent(command))?;
+                    queue!(out, Print(after.chars().take(room.saturating_sub(before.chars().count() + 1)).collect::<String>()))?;
+                } else {
+                    queue!(out, Print(line.chars().take(room).collect::<String>()))?;
+                }
+            }
+            row = (2 + source_rows) as u16;
+
+            // the tape, centred on the data pointer
+            next_row(out, &mut row)?;
+            queue!(out, PrintStyledContent("Tape".bold()))?;
+            let cells = (width / CELL_WIDTH).max(1);
+            let first_cell = state.data_ptr_index.saturating_sub((cells / 2) as isize);
+            let indices: Vec<isize> = (0..cells).filter_map(|offset| first_cell.checked_add(offset as isize)).collect();
+            next_row(out, &mut row)?;
+            for &index in &indices {
+                queue!(out, PrintStyledContent(format!("{:>w$} ", fit(&index.to_string(), CELL_WIDTH - 1), w = CELL_WIDTH - 1).dim()))?;
+            }
+            next_row(out, &mut row)?;
+            for &index in &indices {
+                let text = match state.tape.get(index) {
+                    Some(value) => format!("{:>w$}", fit(&value.to_string(), CELL_WIDTH - 1), w = CELL_WIDTH - 1).stylize(),
+                    None => format!("{:>w$}", "·", w = CELL_WIDTH - 1).dark_grey()
+                };
+                if index == state.data_ptr_index {
+                    queue!(out, PrintStyledContent(text.yellow().bold().reverse()), Print(" "))?;
+                } else {
+                    queue!(out, PrintStyledContent(text), Print(" "))?;
+                }
+            }
+            next_row(out, &mut row)?;
+            let arrow = if state.data_ptr_dir == 1 { "▲ →" } else { "← ▲" };
+            let column = indices.iter().position(|&index| index == state.data_ptr_index).unwrap_or(0) * CELL_WIDTH;
+            let column = if state.data_ptr_dir == 1 { column + CELL_WIDTH - 2 } else { column + CELL_WIDTH - 4 };
+            queue!(out, cursor::MoveToColumn(column as u16), PrintStyledContent(arrow.yellow().bold()))?;
+
+            next_row(out, &mut row)?;
+            let acc = match state.acc.get_value() {
+                Ok(value) => value.to_string().bold(),
+                Err(()) => "empty".to_string().dark_grey()
+            };
+            queue!(out, PrintStyledContent("Accumulator ".bold()), PrintStyledContent(acc))?;
+            if state.creating_number {
+                queue!(out, PrintStyledContent("  (creating a number)".dim()))?;
+            }
+
+            // the last lines of output
+            next_row(out, &mut row)?;
+            queue!(out, PrintStyledContent("Output".bold()))?;
+            let output: Vec<String> = self.output.split('\n')
+                .map(|line| line.chars().map(|c| if c.is_control() { ' ' } else { c }).collect())
+                .collect();
+            for line in output.iter().skip(output.len().saturating_sub(OUTPUT_ROWS)) {
+                next_row(out, &mut row)?;
+                queue!(out, Print(line.chars().take(width).collect::<String>()))?;
+            }
+            for _ in output.len()..OUTPUT_ROWS {
+                next_row(out, &mut row)?;
+            }
+
+            next_row(out, &mut row)?;
+            let help = match self.machine.outcome() {
+                Some(Outcome::Stopped(status)) => format!("stopped with status {status} · ← back · q quit").green(),
+                Some(Outcome::Failed(fault)) => format!("{:?}: {} · ← back · q quit", fault.error, fault.msg).red(),
+                None => "space play/pause · → step · ← back · +/- speed · q quit".to_string().dim()
+            };
+            queue!(out, PrintStyledContent(help))?;
+            queue!(out, terminal::Clear(terminal::ClearType::FromCursorDown))?;
+            out.flush()
+        }
+
+        fn run(&mut self) -> io::Result<()> {
+            let mut out = io::stdout();
+            let mut next_step = Instant::now();
+            loop {
+                self.draw(&mut out)?;
+                let timeout = if self.playing { next_step.saturating_duration_since(Instant::now()) } else { Duration::from_secs(60) };
+                if event::poll(timeout)? {
+                    if let Event::Key(key) = event::read()? && key.kind != KeyEventKind::Release && !self.handle(key) {
+                        return Ok(())
+                    }
+                } else if self.playing {
+                    let speed = SPEEDS[self.speed];
+                    let batch = (speed / FRAMES_PER_SECOND).max(1);
+                    for _ in 0..batch {
+                        if !self.step() {
+                            self.playing = false;
+                            break
+                        }
+                    }
+                    next_step = Instant::now() + Duration::from_secs_f64(batch as f64 / speed as f64);
+                }
+            }
+        }
+    }
+
+    /// Shows a program running in a full-screen terminal UI: the source with the current command highlighted, the tape around
+    /// the data pointer, the accumulator, and the program's output. It can be played at different speeds, paused, and stepped
+    /// forwards and backwards.
+    ///
+    /// `source` is shown as it is written, and `tokens` are the commands of its `expansion`, which are run.
+    /// A command that comes from a macro is highlighted where the macro is called.
+    ///
+    /// Returns an [`Err`] if standard output isn't a terminal or drawing fails.
+    pub fn visualize(source: &str, title: &str, tokens: Vec<Token>, expansion: &Expansion) -> io::Result<()> {
+        if !io::stdout().is_terminal() {
+            return Err(io::Error::other("standard output is not a terminal"))
+        }
+        let spans = expansion.source_spans(source);
+        let targets = match_conditionals(&tokens);
+        let mut machine = Machine::new(tokens, targets, false);
+        if io::stdin().is_terminal() {
+            machine.set_input(prompt_line);
+        }
+        let mut visualizer = Visualizer {
+            machine,
+            source: source.to_string(),
+            spans,
+            title: title.to_string(),
+            output: "".to_string(),
+            playing: false,
+            speed: 3,
+        };
+        let _screen = Screen::enter()?;
+        visualizer.run()
+    }
+
+}
@@ -0,0 +1,28 @@
+//! The parts of `guryvsr` that other Rust programs can use, like the differential harness that test suites drive.
+//! The interpreter itself is the `guryvsr` executable.
+
+// some of what the executable uses, like warnings, isn't used here
+#[allow(dead_code)]
+#[path="frontend/errors.rs"]
+pub mod errors;
+
+#[path="frontend/lexer.rs"]
+pub mod lexer;
+
+#[allow(dead_code)]
+#[path="frontend/exec.rs"]
+mod exec;
+
+// the interactive debugger is only used by the executable, but the machine under it runs programs for the interpreter
+#[allow(dead_code)]
+#[path="frontend/debugger.rs"]
+pub mod debugger;
+
+#[path="frontend/interpreter.rs"]
+pub mod interpreter;
+
+#[path="frontend/parser.rs"]
+pub mod parser;
+
+#[path="frontend/differential.rs"]
+pub mod differential;
@@ -1,4 +1,5 @@
-use clap::Parser;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap::error::ErrorKind;
 #[path="frontend/lexer.rs"]
 mod lexer;
 use lexer::lex::*;
@@ -11,29 +12,464 @@ use errors::err::*;
 mod exec;
 use exec::exec::*;
 
+#[path="frontend/threaded_engine.rs"]
+mod threaded_engine;
+use threaded_engine::threaded::*;
+
+#[path="frontend/debugger.rs"]
+mod debugger;
+use debugger::debug::*;
+
+#[path="frontend/visualizer.rs"]
+mod visualizer;
+use visualizer::tui::*;
+
+#[path="frontend/formatter.rs"]
+mod formatter;
+use formatter::fmt::*;
+
+#[path="frontend/minifier.rs"]
+mod minifier;
+use minifier::minify::*;
+
+#[path="frontend/analyzer.rs"]
+mod analyzer;
+
+#[path="frontend/checker.rs"]
+mod checker;
+use checker::check::*;
+
+#[path="frontend/preprocessor.rs"]
+mod preprocessor;
+use preprocessor::macros::*;
+
+#[path="frontend/language_server.rs"]
+mod language_server;
+use language_server::lsp::*;
+
+#[path="frontend/exporter.rs"]
+mod exporter;
+use exporter::html::*;
+
+#[path="frontend/brainfuck.rs"]
+mod brainfuck;
+use brainfuck::bf::*;
+
+#[path="frontend/rust_backend.rs"]
+mod rust_backend;
+use rust_backend::rs::*;
+#[path="frontend/asm_backend.rs"]
+mod asm_backend;
+use asm_backend::x86::*;
+
+#[path="frontend/bytecode.rs"]
+mod bytecode;
+use bytecode::gurc::*;
+
+#[path="frontend/bundle.rs"]
+mod bundle;
+use bundle::standalone::*;
+
+#[path="frontend/graph.rs"]
+mod graph;
+use graph::cfg::*;
+
+#[path="frontend/assembler.rs"]
+mod assembler;
+use assembler::mnemonic::*;
+
+// the library's users need more of the parser than the executable does
+#[allow(dead_code)]
+#[path="frontend/parser.rs"]
+mod parser;
+
+#[path="frontend/decompiler.rs"]
+mod decompiler;
+use decompiler::pseudo::*;
+
 use std::fs::File;
 use std::path::{Path, PathBuf};
 use std::io::Read;
+use std::ops::RangeInclusive;
+use std::process::exit;
 
 /// Programming language inspired by brainfuck and Emmental.
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
-    /// File to run.
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// File to run, either source (`.gur`) or bytecode built with `build` (`.gurc`).
     #[arg(required = true)]
     input: Vec<PathBuf>,
+    #[command(flatten)]
+    run: RunArgs
+}
+
+/// How to run a program, which the interpreter and bundled executables share.
+#[derive(Args, Debug)]
+struct RunArgs {
     /// Print details after the program ends.
     /// The details will contain the accumulator's value,
     /// the code pointer's index (starting at 0), the data pointer's index (starting at 0),
     /// the direction of the data pointer (positive or negative), the current command, the previous command executed,
     /// the value of the current cell, and the value of the cell 1 unit in the negative direction of the current cell.
     #[arg(short, long)]
-    details: bool
+    details: bool,
+    /// Make `.` exit with the accumulator's value as the exit status instead of 0.
+    /// The value must be between 0 and 255, and an empty accumulator exits with 0.
+    /// Values from 10 to 18 are also the statuses of errors, which unlike a stop are reported on standard error.
+    #[arg(long)]
+    exit_with_acc: bool,
+    /// How to run the program.
+    #[arg(long, value_enum, default_value_t = Engine::Reference)]
+    engine: Engine,
+    /// Run the program in an interactive debugger that can step backwards as well as forwards.
+    /// Debugger commands are read from standard input; type `help` for a list of them.
+    #[arg(long)]
+    reverse_debug: bool,
+    /// Report every time a cell in a range is read, written or cleared, with the command that did it and the cell's old and new values.
+    /// The range is an index like `12`, a half-open range like `10..20`, or a closed range like `10..=19`. Can be given more than once.
+    #[arg(long, value_name = "CELLS", value_parser = parse_cells)]
+    watch_cell: Vec<RangeInclusive<isize>>,
+    /// Report every time the accumulator becomes empty or full.
+    #[arg(long)]
+    watch_acc: bool,
+    /// Throw an `InfiniteLoopError` when the program returns to a state it was in before without reading input in between,
+    /// since it would never halt. The error says which commands repeat. Can't be used with `--engine threaded`.
+    #[arg(long, conflicts_with_all = ["reverse_debug", "watch_cell", "watch_acc"])]
+    detect_loops: bool,
+    /// Also write the state printed with the details to a file as JSON, including every full cell of the tape.
+    /// Used by the differential harness to compare the engines.
+    #[arg(long, hide = true, value_name = "FILE")]
+    dump_state: Option<PathBuf>
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Engine {
+    /// Interpret each command as it is reached.
+    Reference,
+    /// Compile each command into a closure before running, and buffer output. Faster on programs that run many commands, and most of all on ones that print a lot.
+    Threaded
+}
+
+/// Run the gur yvsr program bundled into this executable.
+#[derive(Parser, Debug)]
+struct BundledCli {
+    #[command(flatten)]
+    run: RunArgs
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Rewrite files in the canonical layout.
+    ///
+    /// Conditionals are indented up to their matching `@`, number literals are kept together, and comments are kept.
+    Fmt {
+        /// Files to format.
+        #[arg(required = true)]
+        input: Vec<PathBuf>,
+        /// Don't write anything; instead, list the files that aren't formatted and fail if there are any.
+        #[arg(long)]
+        check: bool,
+        /// Maximum width of a line, not counting comments.
+        #[arg(short, long, default_value_t = 80)]
+        width: usize
+    },
+    /// Print the shortest source with the same commands as a file.
+    ///
+    /// Comments and whitespace are always removed.
+    Minify {
+        /// File to minify.
+        input: PathBuf,
+        /// Write the aliases `f`, `n`, `g` and `l` as `F`, `N`, `G` and `L`.
+        #[arg(long)]
+        normalize_aliases: bool,
+        /// Remove `_` and unmatched `@` where neither the data pointer's move nor the shift in command indices can be observed.
+        #[arg(long)]
+        strip_noops: bool
+    },
+    /// Look for problems in files without running them.
+    ///
+    /// Reports unrecognized symbols, conditionals without a matching `@`, `@`s without a matching conditional,
+    /// and misplaced digits and `#`s, after expanding macros and includes. In files with labels, also warns about `j`s and `J`s
+    /// after a hand-written literal that don't continue at a label. Fails if any problem would cause an error when run into.
+    Check {
+        /// Files to check.
+        #[arg(required = true)]
+        input: Vec<PathBuf>,
+        /// Also warn about commands that will or may throw an `OpError` or `AccumulatorError`, found by following what
+        /// is known about the tape and the accumulator through the program.
+        #[arg(long)]
+        deep: bool
+    },
+    /// Run a language server over standard input and output.
+    Lsp,
+    /// Render a file in another format.
+    Export {
+        /// File to export.
+        input: PathBuf,
+        /// Render a syntax-highlighted HTML page.
+        #[arg(long, required = true)]
+        html: bool,
+        /// Where to write the output. It is printed if this is left out.
+        #[arg(short, long)]
+        output: Option<PathBuf>
+    },
+    /// Translate a Brainfuck program into gur yvsr.
+    ///
+    /// Cells wrap around at 256. `,` reads a line at a time and gives out a newline after each one,
+    /// and an empty line or the end of input reads as 0.
+    FromBf {
+        /// Brainfuck file to translate.
+        input: PathBuf,
+        /// Where to write the output. It is printed if this is left out.
+        #[arg(short, long)]
+        output: Option<PathBuf>
+    },
+    /// Compile a file into source for another toolchain.
+    ///
+    /// The compiled program behaves like the interpreter, including its errors, and accepts `--details` and `--exit-with-acc`.
+    Compile {
+        /// File to compile.
+        input: PathBuf,
+        /// What to compile to.
+        #[arg(long, value_enum)]
+        target: Target,
+        /// Where to write the output. It is printed if this is left out.
+        #[arg(short, long)]
+        output: Option<PathBuf>
+    },
+    /// Compile a file into bytecode, which runs without being lexed again.
+    ///
+    /// The bytecode can be run like a `.gur` file. It holds the commands and the matching `@` of each conditional,
+    /// and is checked for corruption when loaded.
+    Build {
+        /// File to build.
+        input: PathBuf,
+        /// Include a table of where each command is in the source, so that the details of an error say where the current command is written.
+        #[arg(long)]
+        spans: bool,
+        /// Where to write the bytecode. Defaults to the file with its extension changed to `.gurc`.
+        #[arg(short, long)]
+        output: Option<PathBuf>
+    },
+    /// Make a standalone executable that runs a file.
+    ///
+    /// The executable is a copy of this one with the file's bytecode appended, so it runs without the interpreter installed.
+    /// It takes `--details` and `--exit-with-acc` like the interpreter does.
+    Bundle {
+        /// File to bundle.
+        input: PathBuf,
+        /// Where to write the executable. Defaults to the file without its extension.
+        #[arg(short, long)]
+        output: Option<PathBuf>
+    },
+    /// Show a file running in a full-screen terminal UI.
+    ///
+    /// The source is shown with the current command highlighted, along with the tape around the data pointer,
+    /// the accumulator and the program's output. Space plays and pauses, the arrow keys step forwards and backwards
+    /// and change the speed, and `q` quits.
+    Visualize {
+        /// File to visualize.
+        input: PathBuf
+    },
+    /// Draw the control-flow graph of a file.
+    ///
+    /// The program is split into blocks at conditionals, the commands run after their matching `@`s, `J`, `j` and `.`.
+    /// Jumps right after a number literal are drawn to their target, and other jumps to a node for computed targets.
+    Graph {
+        /// File to draw.
+        input: PathBuf,
+        /// What to draw the graph in.
+        #[arg(long, value_enum)]
+        format: GraphFormat,
+        /// Where to write the output. It is printed if this is left out.
+        #[arg(short, long)]
+        output: Option<PathBuf>
+    },
+    /// Print a file with its macros and includes expanded.
+    ///
+    /// `{define name(a, b) body}` defines a macro, `{name(x, y)}` is replaced by its body with `$a` and `$b` replaced by `x` and `y`,
+    /// and `{include "path.gur"}` is replaced by the expanded file, relative to the file it is in. Every command that runs a file,
+    /// builds it or compiles it expands it first.
+    Expand {
+        /// File to expand.
+        input: PathBuf,
+        /// Instead of the expanded source, list every macro call with the indices of the commands it expands into.
+        #[arg(long)]
+        calls: bool,
+        /// Where to write the output. It is printed if this is left out.
+        #[arg(short, long)]
+        output: Option<PathBuf>
+    },
+    /// List a file's commands with a line for each, in a format `asm` turns back into source.
+    ///
+    /// Each line has the command's index, its mnemonic indented by how many conditionals it is in, its symbol,
+    /// and a comment with its depth and, for conditionals and `@`s, the index of the command it is paired with.
+    Disasm {
+        /// File to list, either source (`.gur`) or bytecode (`.gurc`).
+        input: PathBuf,
+        /// Where to write the output. It is printed if this is left out.
+        #[arg(short, long)]
+        output: Option<PathBuf>
+    },
+    /// Print a file as pseudocode.
+    ///
+    /// Literals become assignments to `acc`, moves between the accumulator and the tape are written out, the data pointer's moves
+    /// are written as `p += dir` and added together where possible, conditionals become `if` blocks, and jumps to constant targets
+    /// become `goto`s to labels. Each line ends with the indices of the commands it stands for.
+    Decompile {
+        /// File to decompile, either source (`.gur`) or bytecode (`.gurc`).
+        input: PathBuf,
+        /// Where to write the output. It is printed if this is left out.
+        #[arg(short, long)]
+        output: Option<PathBuf>
+    },
+    /// Turn a listing of mnemonics, like the one `disasm` prints, into source.
+    ///
+    /// Each line holds a mnemonic, optionally followed by its symbol, which tells aliases apart and is required after `DIGIT`.
+    /// Numbers at the start of a line and everything after a `;` are ignored. A line like `loop:` is a label,
+    /// and `TO loop` and `BY loop` are the number literals that make a `JUMP_TO_CMD` or `JUMP_CMDS` after them continue at it.
+    Asm {
+        /// Listing to assemble. It is read from standard input if this is left out.
+        input: Option<PathBuf>,
+        /// Where to write the source. It is printed if this is left out.
+        #[arg(short, long)]
+        output: Option<PathBuf>
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Target {
+    /// A standalone Rust `main.rs` that only needs `rustc` to build.
+    Rust,
+    /// GNU assembler source for x86-64 Linux that only needs `as` and `ld` to build.
+    #[value(name = "x86_64-linux")]
+    X86_64Linux
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum GraphFormat {
+    /// Graphviz's DOT language, which `dot -Tsvg` turns into an image.
+    Dot
 }
 
 fn main() {
+    if let Some(bytecode) = embedded_program() {
+        run_bundled(&bytecode)
+    }
     let command_args = Cli::parse();
-    let path = command_args.input.iter().nth(0);
+    match command_args.command {
+        Some(Command::Fmt { input, check, width }) => fmt(&input, check, width),
+        Some(Command::Minify { input, normalize_aliases, strip_noops }) => {
+            let expansion = read_expanded(&input);
+            println!("{}", minify(&tokenize_expanded(&expansion), normalize_aliases, strip_noops))
+        }
+        Some(Command::Check { input, deep }) => check(&input, deep),
+        Some(Command::Lsp) => serve(),
+        Some(Command::Export { input, html: _, output }) => export(&input, output.as_deref()),
+        Some(Command::FromBf { input, output }) => from_bf(&input, output.as_deref()),
+        Some(Command::Compile { input, target, output }) => {
+            let tokens = tokenize_expanded(&read_expanded(&input));
+            let source = match target {
+                Target::Rust => compile_rust(&tokens),
+                Target::X86_64Linux => compile_x86_64_linux(&tokens)
+            };
+            write_output(output.as_deref(), &source)
+        }
+        Some(Command::Build { input, spans, output }) => build(&input, spans, output.as_deref()),
+        Some(Command::Bundle { input, output }) => make_bundle(&input, output.as_deref()),
+        Some(Command::Visualize { input }) => {
+            let src = read_program(Some(&input));
+            let expansion = expand(&src, &input);
+            let tokens = tokenize_expanded(&expansion);
+            if let Err(error) = visualize(&src, &input.file_name().unwrap().to_string_lossy(), tokens, &expansion) {
+                eprintln!("cannot visualize {input:?}: {error}");
+                exit(1)
+            }
+        }
+        Some(Command::Graph { input, format, output }) => {
+            let tokens = tokenize_expanded(&read_expanded(&input));
+            let graph = match format {
+                GraphFormat::Dot => to_dot(&tokens)
+            };
+            write_output(output.as_deref(), &graph)
+        }
+        Some(Command::Disasm { input, output }) => {
+            let (tokens, targets) = load_program(Some(&input));
+            write_output(output.as_deref(), &disassemble(&tokens, &targets))
+        }
+        Some(Command::Decompile { input, output }) => {
+            let (tokens, _) = load_program(Some(&input));
+            write_output(output.as_deref(), &decompile(&tokens))
+        }
+        Some(Command::Asm { input, output }) => asm(input.as_deref(), output.as_deref()),
+        Some(Command::Expand { input, calls, output }) => {
+            let expansion = read_expanded(&input);
+            if !calls {
+                return write_output(output.as_deref(), &expansion.source)
+            }
+            let listing: String = expansion.calls.iter().map(|call| match call.commands.len() {
+                0 => format!("{}: `{}` expands into no commands\n", call.at, call.name),
+                _ => format!("{}: `{}` expands into commands {} to {}\n", call.at, call.name, call.commands.start, call.commands.end - 1)
+            }).collect();
+            write_output(output.as_deref(), &listing)
+        }
+        None => {
+            command_args.run.check(Cli::command());
+            let (tokens, targets) = load_program(command_args.input.first());
+            run_program(tokens, targets, command_args.run)
+        }
+    }
+}
+
+impl RunArgs {
+    /// Rejects `--engine threaded` with the flags that only the reference engine runs with,
+    /// which clap can't do by itself since `--engine` always has a value.
+    fn check(&self, mut command: clap::Command) {
+        if matches!(self.engine, Engine::Threaded) && (self.reverse_debug || !self.watch_cell.is_empty() || self.watch_acc) {
+            command.error(ErrorKind::ArgumentConflict, "`--engine threaded` cannot be used with `--reverse-debug`, `--watch-cell` or `--watch-acc`").exit()
+        }
+        if matches!(self.engine, Engine::Threaded) && self.detect_loops {
+            command.error(ErrorKind::ArgumentConflict, "`--engine threaded` cannot be used with `--detect-loops`").exit()
+        }
+    }
+}
+
+/// Runs a program the way the flags say to.
+fn run_program(tokens: Vec<Token>, targets: Vec<Option<usize>>, args: RunArgs) {
+    let watch = Watch { cells: args.watch_cell, acc: args.watch_acc };
+    if args.reverse_debug {
+        debug_program(tokens, targets, args.exit_with_acc, watch)
+    }
+    let halt = match args.engine {
+        _ if !watch.is_empty() => execute_watched(tokens, targets, args.details, args.exit_with_acc, watch),
+        Engine::Threaded => execute_threaded(tokens, &targets, args.details, args.exit_with_acc),
+        Engine::Reference => execute(tokens, &targets, args.details, args.exit_with_acc, args.detect_loops)
+    };
+    if let Some(path) = args.dump_state {
+        dump_state(&path, &halt)
+    }
+    exit(halt.status)
+}
+
+/// Writes the state a program ended in to a file as JSON, including every full cell of the tape.
+fn dump_state(path: &Path, halt: &Halt) {
+    let state = serde_json::json!({
+        "acc": halt.acc.get_value().ok(),
+        "code_ptr_index": halt.code_ptr_index,
+        "data_ptr_index": halt.data_ptr_index,
+        "data_ptr_dir": halt.data_ptr_dir,
+        "tape": halt.tape.cells(),
+    });
+    if let Err(error) = std::fs::write(path, state.to_string()) {
+        Error::FileError.throw(&format!("cannot write the state to {path:?}: {error}"), true)
+    }
+}
+
+/// Reads the program at the provided path, throwing a [`FileError`](Error::FileError) if it isn't a readable .gur file.
+fn read_program(path: Option<&PathBuf>) -> String {
     if path.is_none() {
         Error::FileError.throw("invalid file path", true)
     }
@@ -61,5 +497,243 @@ fn main() {
     if success.is_err() {
         Error::FileError.throw("file cannot be read because it contains non-UTF-8 characters", true)
     }
-    execute(tokenize(&mut buf), command_args.details)
+    buf
+}
+
+/// Reads the program at the provided path like [`read_program`], and expands its macros and includes.
+fn read_expanded(path: &PathBuf) -> Expansion {
+    expand(&read_program(Some(path)), path)
+}
+
+/// Expands the macros and includes of the source of the program at the provided path, exiting if that fails.
+fn expand(src: &str, path: &Path) -> Expansion {
+    match expand_source(src, path) {
+        Ok(expansion) => expansion,
+        Err((error, msg)) => {
+            error.throw(&msg, true);
+            unreachable!()
+        }
+    }
+}
+
+/// Tokenizes an expanded program, reporting an unrecognized symbol where it is written rather than where it ends up after expansion.
+fn tokenize_expanded(expansion: &Expansion) -> Vec<Token> {
+    match try_tokenize(&expansion.source) {
+        Ok(tokens) => tokens,
+        Err((c, index)) => {
+            let origin = scan_all(&expansion.source).into_iter()
+                .find(|lexeme| matches!(lexeme.kind, LexemeKind::Unknown(_)))
+                .and_then(|lexeme| expansion.locate(lexeme.span.start));
+            let msg = match origin {
+                Some(origin) => format!("{}: unrecognized symbol {c} found at index {index}{}", origin.at, origin.call_chain()),
+                None => format!("unrecognized symbol {c} found at index {index}")
+            };
+            Error::UnknownSymbolError.throw(&msg, true);
+            unreachable!()
+        }
+    }
+}
+
+/// Loads a program to run, either from source or from bytecode, along with the matching `@` of each conditional.
+fn load_program(path: Option<&PathBuf>) -> (Vec<Token>, Vec<Option<usize>>) {
+    if let Some(path) = path && path.extension().is_some_and(|extension| extension == "gurc") {
+        let Ok(bytes) = std::fs::read(path) else {
+            Error::FileError.throw(&format!("the file {path:?} does not exist or cannot be read"), true);
+            unreachable!()
+        };
+        return match decode(&bytes) {
+            Ok(program) => {
+                if let Some(spans) = program.spans {
+                    let source = path.with_extension("gur");
+                    let text = std::fs::read_to_string(&source).ok();
+                    COMMAND_ORIGIN.set(Box::new(move |index| describe_span(&spans, &source, text.as_deref(), index))).ok();
+                }
+                (program.tokens, program.targets)
+            }
+            Err(msg) => {
+                Error::FileError.throw(&format!("the file {path:?} is not valid bytecode: {msg}"), true);
+                unreachable!()
+            }
+        }
+    }
+    let Some(path) = path else {
+        Error::FileError.throw("invalid file path", true);
+        unreachable!()
+    };
+    let expansion = read_expanded(path);
+    let tokens = tokenize_expanded(&expansion);
+    let targets = match_conditionals(&tokens);
+    COMMAND_ORIGIN.set(Box::new(move |index| expansion.describe(index))).ok();
+    (tokens, targets)
+}
+
+/// Describes where the command at an index of a program loaded from bytecode was written, from the offset in its span table.
+/// The offset is shown as a line and column of `source` if its text was read and still has a command or a macro directive there,
+/// since the source may have changed after the bytecode was built.
+fn describe_span(spans: &[usize], source: &Path, text: Option<&str>, index: usize) -> Option<String> {
+    let offset = *spans.get(index)?;
+    match text {
+        Some(text) if text.get(offset..).is_some_and(|rest| rest.starts_with(|c| c == '{' || symbol_to_token(c).is_some())) => {
+            let (line, column) = line_column(text, offset);
+            Some(format!("command {index} is at {}:{line}:{column}", source.display()))
+        }
+        _ => Some(format!("command {index} is at byte {offset} of the source it was built from"))
+    }
+}
+
+fn build(path: &PathBuf, spans: bool, output: Option<&Path>) {
+    let src = read_program(Some(path));
+    let expansion = expand(&src, path);
+    let tokens = tokenize_expanded(&expansion);
+    // where each command is written in the file, or the macro call or include it comes from
+    let spans: Option<Vec<usize>> = spans.then(|| expansion.source_spans(&src).into_iter().map(|span| span.start).collect());
+    let output = output.map_or_else(|| path.with_extension("gurc"), Path::to_path_buf);
+    if std::fs::write(&output, encode(&tokens, spans.as_deref())).is_err() {
+        Error::FileError.throw(&format!("the file {output:?} cannot be written to"), true)
+    }
+}
+
+fn make_bundle(path: &PathBuf, output: Option<&Path>) {
+    let tokens = tokenize_expanded(&read_expanded(path));
+    let Ok(executable) = std::env::current_exe().and_then(std::fs::read) else {
+        Error::FileError.throw("the running executable cannot be read", true);
+        return
+    };
+    let output = output.map_or_else(|| path.with_extension(std::env::consts::EXE_EXTENSION), Path::to_path_buf);
+    if std::fs::write(&output, bundle(&executable, &encode(&tokens, None))).is_err() {
+        Error::FileError.throw(&format!("the file {output:?} cannot be written to"), true)
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if std::fs::set_permissions(&output, std::fs::Permissions::from_mode(0o755)).is_err() {
+            Error::FileError.throw(&format!("the file {output:?} cannot be made executable"), true)
+        }
+    }
+}
+
+fn run_bundled(bytecode: &[u8]) {
+    let args = BundledCli::parse();
+    args.run.check(BundledCli::command());
+    match decode(bytecode) {
+        Ok(program) => run_program(program.tokens, program.targets, args.run),
+        Err(msg) => Error::FileError.throw(&format!("the bundled program is not valid bytecode: {msg}"), true)
+    }
+}
+
+fn fmt(paths: &[PathBuf], check: bool, width: usize) {
+    let mut unformatted = false;
+    for path in paths {
+        let src = read_program(Some(path));
+        let formatted = match format_source(&src, width) {
+            Ok(formatted) => formatted,
+            Err((c, index)) => {
+                Error::UnknownSymbolError.throw(&format!("unrecognized symbol {c} found in {path:?} at index {index}"), true);
+                return
+            }
+        };
+        if commands(&formatted) != commands(&src) {
+            Error::SyntaxError.throw(&format!("formatting {path:?} would change its commands, so it was left as it is"), true)
+        }
+        if formatted == src {
+            continue
+        }
+        if check {
+            eprintln!("{path:?} is not formatted");
+            unformatted = true;
+        } else if std::fs::write(path, formatted).is_err() {
+            Error::FileError.throw(&format!("the file {path:?} cannot be written to"), true)
+        }
+    }
+    if unformatted { exit(1) }
+}
+
+/// Returns the commands in source that [`scan`] has already accepted, without exiting like [`tokenize`] would.
+fn commands(src: &str) -> Vec<Token> {
+    scan(src).unwrap_or_default().into_iter().filter_map(|lexeme| match lexeme.kind {
+        LexemeKind::Command(token) => Some(token),
+        _ => None
+    }).collect()
+}
+
+fn check(paths: &[PathBuf], deep: bool) {
+    let mut failed = false;
+    for path in paths {
+        let expansion = match expand_source(&read_program(Some(path)), path) {
+            Ok(expansion) => expansion,
+            Err((error, msg)) => {
+                error.throw(&msg, false);
+                failed = true;
+                continue
+            }
+        };
+        let src = &expansion.source;
+        let mut diagnostics = check_source(src);
+        let labels: Vec<usize> = expansion.labels.iter().map(|label| label.index).collect();
+        let references: Vec<_> = expansion.references.iter().map(|reference| reference.commands.clone()).collect();
+        diagnostics.extend(check_jumps(src, &labels, &references));
+        if deep {
+            diagnostics.extend(check_deep(src));
+        }
+        diagnostics.sort_by_key(|d| d.span.start);
+        for diagnostic in diagnostics {
+            // every diagnostic is on a command or an unrecognized symbol, which is where the expansion says it comes from
+            let origin = expansion.locate(diagnostic.span.start).unwrap();
+            let msg = format!("{}: {}{}", origin.at, diagnostic.message, origin.call_chain());
+            match diagnostic.error {
+                Some(error) => {
+                    error.throw(&msg, false);
+                    failed = true;
+                }
+                None => warn(&msg)
+            }
+        }
+    }
+    if failed { exit(1) }
+}
+
+fn export(path: &PathBuf, output: Option<&Path>) {
+    let src = read_program(Some(path));
+    let expansion = expand(&src, path);
+    let tokens = tokenize_expanded(&expansion);
+    let title = path.file_name().unwrap().to_string_lossy();
+    write_output(output, &export_html(&src, &title, &tokens, &expansion))
+}
+
+fn asm(path: Option<&Path>, output: Option<&Path>) {
+    let listing = match path {
+        Some(path) => std::fs::read_to_string(path).ok(),
+        None => std::io::read_to_string(std::io::stdin()).ok()
+    };
+    let Some(listing) = listing else {
+        Error::FileError.throw(&format!("the listing {:?} does not exist or cannot be read", path.unwrap_or(Path::new("<stdin>"))), true);
+        return
+    };
+    match assemble(&listing) {
+        Ok(source) => write_output(output, &source),
+        Err(msg) => Error::SyntaxError.throw(&format!("{msg} in {:?}", path.unwrap_or(Path::new("<stdin>"))), true)
+    }
+}
+
+fn from_bf(path: &PathBuf, output: Option<&Path>) {
+    let Ok(src) = std::fs::read_to_string(path) else {
+        Error::FileError.throw(&format!("the file {path:?} does not exist or cannot be read"), true);
+        return
+    };
+    match transpile(&src) {
+        Ok(program) => write_output(output, &program),
+        Err(msg) => Error::SyntaxError.throw(&format!("{msg} in {path:?}"), true)
+    }
+}
+
+/// Writes to the provided file, or prints if there isn't one.
+fn write_output(output: Option<&Path>, text: &str) {
+    match output {
+        Some(output) => {
+            if std::fs::write(output, text).is_err() {
+                Error::FileError.throw(&format!("the file {output:?} cannot be written to"), true)
+            }
+        }
+        None => print!("{text}")
+    }
 }
@@ -0,0 +1,85 @@
+mod common;
+
+use std::path::Path;
+use std::process::Output;
+use guryvsr::lexer::lex::*;
+use common::*;
+
+fn guryvsr(args: &[&str], stdin: &str) -> Output {
+    run(common::guryvsr().args(args), stdin.as_bytes())
+}
+
+fn disasm(path: &Path) -> String {
+    let output = guryvsr(&["disasm", path.to_str().unwrap()], "");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn asm(listing: &str) -> String {
+    let output = guryvsr(&["asm"], listing);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn write(name: &str, source: &str) -> TempProgram {
+    program("assembler", name, source)
+}
+
+#[test]
+fn round_trips_keep_every_command() {
+    let aliases = write("aliases", "#1U ? F ?@ f @ N n G g L l .");
+    let mut programs = programs();
+    programs.push(aliases.to_path_buf());
+    for program in programs {
+        let source = asm(&disasm(&program));
+        let original = try_tokenize(&std::fs::read_to_string(&program).unwrap()).unwrap();
+        assert_eq!(try_tokenize(&source).unwrap(), original, "{program:?}");
+    }
+}
+
+#[test]
+fn listings_show_depth_and_matches() {
+    let listing = disasm(&write("nested", "#1U ? F ?@ _ @ ."));
+    let lines: Vec<&str> = listing.lines().collect();
+    assert_eq!(lines[3], "3  IF_ZERO                  ?   ; depth 0, matches 8");
+    assert_eq!(lines[5], "5    IF_ZERO                ?   ; depth 1, matches 6");
+    assert_eq!(lines[7], "7    NOP                    _   ; depth 1");
+    assert_eq!(lines[8], "8  END_IF                   @   ; depth 0, matches 3");
+}
+
+#[test]
+fn bytecode_can_be_listed() {
+    let source = write("built", "#12U F i .");
+    let bytecode = source.with_extension("gurc");
+    let output = guryvsr(&["build", source.to_str().unwrap(), "-o", bytecode.to_str().unwrap()], "");
+    assert!(output.status.success());
+    assert_eq!(disasm(&bytecode), disasm(&source));
+}
+
+#[test]
+fn labels_become_label_directives() {
+    let listing = "\
+        TO end          ; skip the division\n\
+        JUMP_TO_CMD\n\
+        DIV\n\
+        end:\n\
+        STOP\n";
+    let source = asm(listing);
+    assert_eq!(source, "{to end} j/ {label end} .\n");
+    let output = common::guryvsr().arg(write("labelled", &source).as_os_str()).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn bad_lines_are_syntax_errors() {
+    for (listing, message) in [
+        ("NOP\nPUSH", "line 2: unknown mnemonic `PUSH`"),
+        ("CREATE_INT\nDIGIT", "line 2: `DIGIT` needs the digit after it"),
+        ("FLIP N", "line 1: `N` is not the symbol of `FLIP`"),
+        ("NOP _ _", "line 1: unexpected `_`"),
+    ] {
+        let output = guryvsr(&["asm"], listing);
+        assert_eq!(output.status.code(), Some(14), "{listing}");
+        assert!(String::from_utf8_lossy(&output.stderr).contains(message), "{listing}: {}", String::from_utf8_lossy(&output.stderr));
+    }
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use std::process::Command;
+use common::*;
+
+#[test]
+fn bundled_programs_match_the_interpreter() {
+    let dir = temp_dir("bundle");
+    for program in programs() {
+        let name = program.file_stem().unwrap().to_str().unwrap();
+        let binary = dir.join(name);
+        let status = guryvsr().arg("bundle").arg(&program).arg("-o").arg(&binary).status().unwrap();
+        assert!(status.success(), "bundling {name} failed");
+
+        for flags in [&[][..], &["--details"], &["--exit-with-acc"], &["--engine", "threaded"], &["--watch-acc", "-d"], &["--detect-loops"]] {
+            let expected = interpret(&program, flags, &input_for(&program));
+            let actual = run(Command::new(&binary).args(flags), &input_for(&program));
+            assert_eq!(String::from_utf8_lossy(&actual.stdout), String::from_utf8_lossy(&expected.stdout), "stdout of {name} with {flags:?}");
+            assert_eq!(String::from_utf8_lossy(&actual.stderr), String::from_utf8_lossy(&expected.stderr), "stderr of {name} with {flags:?}");
+            assert_eq!(actual.status.code(), expected.status.code(), "exit status of {name} with {flags:?}");
+        }
+    }
+}
+
+#[test]
+fn bundled_programs_reject_unknown_flags() {
+    let program = program("bundle", "flags", "#1 .");
+    let dir = temp_dir("bundle");
+    let binary = dir.join("flags");
+    assert!(guryvsr().arg("bundle").arg(program.as_os_str()).arg("-o").arg(&binary).status().unwrap().success());
+    let output = run(Command::new(&binary).arg("--detials"), b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--detials"));
+    let output = run(Command::new(&binary).args(["--detect-loops", "--watch-acc"]), b"");
+    assert_eq!(output.status.code(), Some(2));
+    let output = run(Command::new(&binary).args(["--detect-loops", "--engine", "threaded"]), b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with `--detect-loops`"));
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use std::path::Path;
+use std::process::Output;
+use common::*;
+
+/// Runs a program with the input that sits next to its source in a `.in` file, if there is one.
+fn run(program: &Path, source: &Path) -> Output {
+    interpret(program, &[], &input_for(source))
+}
+
+fn build(source: &Path, output: &Path, spans: bool) {
+    let mut command = guryvsr();
+    command.arg("build").arg(source).arg("-o").arg(output);
+    if spans {
+        command.arg("--spans");
+    }
+    assert!(command.status().unwrap().success(), "building {source:?} failed");
+}
+
+#[test]
+fn bytecode_runs_like_the_source() {
+    let dir = temp_dir("bytecode");
+    for program in programs() {
+        let name = program.file_stem().unwrap().to_str().unwrap();
+        let expected = run(&program, &program);
+        for spans in [false, true] {
+            let bytecode = dir.join(format!("{name}-{spans}.gurc"));
+            build(&program, &bytecode, spans);
+            let actual = run(&bytecode, &program);
+            // the span table adds a note saying where the current command is to the details of an error
+            let stderr: String = String::from_utf8_lossy(&actual.stderr).split_inclusive('\n')
+                .filter(|line| !(spans && line.contains("note:")))
+                .collect();
+            assert_eq!(String::from_utf8_lossy(&actual.stdout), String::from_utf8_lossy(&expected.stdout), "stdout of {name}");
+            assert_eq!(stderr, String::from_utf8_lossy(&expected.stderr), "stderr of {name}");
+            assert_eq!(actual.status.code(), expected.status.code(), "exit status of {name}");
+        }
+    }
+}
+
+#[test]
+fn corrupted_bytecode_is_rejected() {
+    let dir = temp_dir("bytecode");
+    let source = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/programs/hello.gur"));
+    let bytecode = dir.join("hello.gurc");
+    build(source, &bytecode, true);
+    let bytes = std::fs::read(&bytecode).unwrap();
+
+    let mut flipped = bytes.clone();
+    flipped[14] ^= 1;
+    let mut version = bytes.clone();
+    version[4] = 2;
+    let cases = [
+        (flipped, "checksum mismatch"),
+        (version, "unsupported bytecode version 2"),
+        (bytes[..bytes.len() - 1].to_vec(), "checksum mismatch"),
+        (b"#1.".to_vec(), "missing bytecode header"),
+    ];
+    for (index, (bytes, message)) in cases.into_iter().enumerate() {
+        let corrupted = dir.join(format!("corrupted-{index}.gurc"));
+        std::fs::write(&corrupted, bytes).unwrap();
+        let output = run(&corrupted, &corrupted);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(10), "{stderr}");
+        assert!(stderr.contains("FileError") && stderr.contains(message), "{stderr}");
+    }
+}
+
+#[test]
+fn spans_say_where_errors_happen() {
+    let source = program("bytecode-spans", "spans", "{define bad /}\n#1U {bad} .");
+    let with_spans = source.with_extension("gurc");
+    let without_spans = source.with_file_name("plain.gurc");
+    build(&source, &with_spans, true);
+    build(&source, &without_spans, false);
+    let output = run(&with_spans, &source);
+    assert_eq!(output.status.code(), Some(12));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&format!("note:\x1b[0m command 3 is at {}:2:5", source.display())), "{stderr}");
+    assert!(!String::from_utf8_lossy(&run(&without_spans, &source).stderr).contains("note:"));
+
+    // without the source, only the offset is known
+    std::fs::remove_file(&*source).unwrap();
+    let stderr = String::from_utf8_lossy(&run(&with_spans, &source).stderr).into_owned();
+    assert!(stderr.contains("command 3 is at byte 19 of the source it was built from"), "{stderr}");
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use std::process::Output;
+use common::*;
+
+fn check_deep(name: &str, source: &str) -> Output {
+    guryvsr().args(["check", "--deep"]).arg(program("check-deep", name, source).as_os_str()).output().unwrap()
+}
+
+fn warnings(output: &Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stderr).lines()
+        .map(|line| line.split(".gur:").nth(1).unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn certain_errors_are_warnings() {
+    let output = check_deep("certain", "#5U #0U F / .");
+    // the findings are only warnings, since the commands might never run
+    assert!(output.status.success());
+    assert_eq!(warnings(&output), ["1:11: `/` at index 7 will throw OpError because the current cell is 0"]);
+    // the data pointer can be moved anywhere on a tape that is known to be empty
+    let output = check_deep("moved", "I K R .");
+    assert_eq!(warnings(&output), ["1:5: `R` at index 2 will throw OpError because the current cell is empty"]);
+    // commands that are never reached aren't reported
+    let output = check_deep("unreachable", "\nU . U");
+    assert_eq!(warnings(&output), ["2:1: `U` at index 0 will throw AccumulatorError because the accumulator is empty"]);
+}
+
+#[test]
+fn errors_on_some_paths_are_possible() {
+    // the line read by `S` may be empty
+    let output = check_deep("possible", "S F R .");
+    assert_eq!(warnings(&output), [
+        "1:5: `R` at index 2 may throw OpError because the current cell may be empty",
+    ]);
+    let output = check_deep("accumulator", "I #1 .");
+    assert_eq!(warnings(&output), ["1:3: `#` at index 1 will throw AccumulatorError because the accumulator is full"]);
+}
+
+#[test]
+fn conditionals_narrow_down_what_is_known() {
+    // `!` only falls through when the cell is empty or 0, and `?` only jumps when it is
+    assert!(warnings(&check_deep("narrowed", "#1U ? R @ .")).is_empty());
+    assert_eq!(warnings(&check_deep("fall_through", "#3U ! _ @ R .")), [
+        "1:11: `R` at index 6 will throw OpError because the current cell is empty",
+    ]);
+}
+
+#[test]
+fn computed_jumps_make_errors_possible() {
+    assert_eq!(warnings(&check_deep("static", "I A . @ R .")), [
+        "1:9: `R` at index 4 will throw OpError because the current cell is empty",
+    ]);
+    assert_eq!(warnings(&check_deep("computed", "I A j @ R .")), [
+        "1:9: `R` at index 4 may throw OpError because the current cell may be empty",
+    ]);
+}
+
+#[test]
+fn corpus_has_no_false_alarms() {
+    for path in programs() {
+        if path.ends_with("op_error.gur") {
+            continue
+        }
+        let output = guryvsr().args(["check", "--deep"]).arg(&path).output().unwrap();
+        assert!(output.stderr.is_empty(), "{path:?}: {}", String::from_utf8_lossy(&output.stderr));
+    }
+}
+
+#[test]
+fn unrecognized_symbols_do_not_stop_the_other_files() {
+    let broken = program("check-deep", "u", "U x ?");
+    let certain = program("check-deep", "v", "#5U #0U F / .");
+    let output = guryvsr().args(["check", "--deep"]).arg(broken.as_os_str()).arg(certain.as_os_str()).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("u.gur:1:3: unrecognized symbol"), "{stderr}");
+    assert!(stderr.contains("v.gur:1:11: `/` at index 7"), "{stderr}");
+}
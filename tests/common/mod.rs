@@ -0,0 +1,128 @@
+//! What the integration tests share: temporary programs, running the `guryvsr` executable, and the corpus in `tests/programs`.
+// every test file uses some of these, but none uses all of them
+#![allow(dead_code)]
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// A directory made by [`temp_dir`], which is removed along with everything in it when it goes out of scope.
+pub struct TempDir(PathBuf);
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Gives every directory made by [`temp_dir`] its own name, so that tests running at the same time never remove each other's files.
+static DIRS: AtomicUsize = AtomicUsize::new(0);
+
+/// Makes a directory of its own for a test to write files to, named after its test file.
+pub fn temp_dir(suite: &str) -> TempDir {
+    let dir = std::env::temp_dir().join(format!("guryvsr-{suite}-{}-{}", std::process::id(), DIRS.fetch_add(1, Ordering::Relaxed)));
+    std::fs::create_dir_all(&dir).unwrap();
+    TempDir(dir)
+}
+
+/// Writes a file into a directory and returns its path.
+pub fn write(dir: &Path, name: &str, contents: &str) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+/// A program written by [`program`], which is removed along with its directory when it goes out of scope.
+pub struct TempProgram {
+    path: PathBuf,
+    _dir: TempDir
+}
+
+impl Deref for TempProgram {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for TempProgram {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Writes a program to `name.gur` in a directory of its own for the suite.
+pub fn program(suite: &str, name: &str, source: &str) -> TempProgram {
+    let dir = temp_dir(suite);
+    TempProgram { path: write(&dir, &format!("{name}.gur"), source), _dir: dir }
+}
+
+/// Writes a program for tests that don't need to name it.
+pub fn unnamed_program(suite: &str, source: &str) -> TempProgram {
+    program(suite, "program", source)
+}
+
+/// Returns the `.gur` files in a directory under `tests`, sorted by name.
+pub fn corpus(dir: &str) -> Vec<PathBuf> {
+    let mut programs: Vec<PathBuf> = std::fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join(dir)).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "gur"))
+        .collect();
+    programs.sort();
+    programs
+}
+
+/// Returns the programs in `tests/programs`.
+pub fn programs() -> Vec<PathBuf> {
+    corpus("programs")
+}
+
+/// Returns the input that sits next to a program in a `.in` file, or nothing if there isn't one.
+pub fn input_for(program: &Path) -> Vec<u8> {
+    std::fs::read(program.with_extension("in")).unwrap_or_default()
+}
+
+/// Returns whether a tool like `rustc` or `as` can be run, for tests that build programs with it.
+pub fn has_tool(name: &str) -> bool {
+    Command::new(name).arg("--version").stdout(Stdio::null()).stderr(Stdio::null()).status().is_ok_and(|status| status.success())
+}
+
+pub fn guryvsr() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_guryvsr"))
+}
+
+/// How long [`run`] waits for a command before it is assumed to hang. Every program the tests run finishes well within it.
+pub const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Runs a command with the provided input, killing it if it runs for longer than `timeout`, and returns [`None`] if it was killed.
+pub fn run_with_timeout(command: &mut Command, input: &[u8], timeout: Duration) -> Option<Output> {
+    let (status, stdout, stderr) = guryvsr::differential::diff::run_with_timeout(command, input, timeout).unwrap();
+    Some(Output { status: status?, stdout, stderr })
+}
+
+/// Runs a command with the provided input and waits for it to exit, failing if it takes longer than [`TIMEOUT`].
+pub fn run(command: &mut Command, input: &[u8]) -> Output {
+    run_with_timeout(command, input, TIMEOUT).unwrap_or_else(|| panic!("{command:?} didn't exit within {TIMEOUT:?}"))
+}
+
+/// Runs the interpreter on a program with some flags and input.
+pub fn interpret(program: &Path, args: &[&str], input: &[u8]) -> Output {
+    run(guryvsr().arg(program).args(args), input)
+}
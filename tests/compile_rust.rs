@@ -0,0 +1,28 @@
+mod common;
+
+use std::process::Command;
+use common::*;
+
+#[test]
+fn compiled_programs_match_the_interpreter() {
+    let dir = temp_dir("compile-rust");
+    for program in programs() {
+        let name = program.file_stem().unwrap().to_str().unwrap();
+        let source = dir.join(format!("{name}.rs"));
+        let binary = dir.join(name);
+        let status = guryvsr()
+            .args(["compile", "--target", "rust", "-o"])
+            .args([&source, &program])
+            .status()
+            .unwrap();
+        assert!(status.success(), "compiling {name} failed");
+        let output = Command::new("rustc").args(["-O", "-o"]).args([&binary, &source]).output().unwrap();
+        assert!(output.status.success(), "rustc rejected {name}: {}", String::from_utf8_lossy(&output.stderr));
+
+        let expected = interpret(&program, &[], &input_for(&program));
+        let actual = run(&mut Command::new(&binary), &input_for(&program));
+        assert_eq!(String::from_utf8_lossy(&actual.stdout), String::from_utf8_lossy(&expected.stdout), "stdout of {name}");
+        assert_eq!(String::from_utf8_lossy(&actual.stderr), String::from_utf8_lossy(&expected.stderr), "stderr of {name}");
+        assert_eq!(actual.status.code(), expected.status.code(), "exit status of {name}");
+    }
+}
@@ -0,0 +1,52 @@
+#![cfg(all(target_arch = "x86_64", target_os = "linux"))]
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use common::*;
+
+/// Compiles a program and builds it with `as` and `ld`, returning the executable.
+fn build(dir: &Path, program: &Path) -> PathBuf {
+    let name = program.file_stem().unwrap().to_str().unwrap();
+    let source = dir.join(format!("{name}.s"));
+    let object = dir.join(format!("{name}.o"));
+    let binary = dir.join(name);
+    let status = guryvsr()
+        .args(["compile", "--target", "x86_64-linux", "-o"])
+        .args([&source, program])
+        .status()
+        .unwrap();
+    assert!(status.success(), "compiling {name} failed");
+    let output = Command::new("as").arg("-o").args([&object, &source]).output().unwrap();
+    assert!(output.status.success(), "as rejected {name}: {}", String::from_utf8_lossy(&output.stderr));
+    let output = Command::new("ld").arg("-o").args([&binary, &object]).output().unwrap();
+    assert!(output.status.success(), "ld rejected {name}: {}", String::from_utf8_lossy(&output.stderr));
+    binary
+}
+
+#[test]
+fn compiled_programs_match_the_interpreter() {
+    let dir = temp_dir("compile-x86-64");
+    for program in programs() {
+        let name = program.file_stem().unwrap().to_str().unwrap();
+        let binary = build(&dir, &program);
+
+        let expected = interpret(&program, &[], &input_for(&program));
+        let actual = run(&mut Command::new(&binary), &input_for(&program));
+        assert_eq!(String::from_utf8_lossy(&actual.stdout), String::from_utf8_lossy(&expected.stdout), "stdout of {name}");
+        assert_eq!(String::from_utf8_lossy(&actual.stderr), String::from_utf8_lossy(&expected.stderr), "stderr of {name}");
+        assert_eq!(actual.status.code(), expected.status.code(), "exit status of {name}");
+    }
+}
+
+#[test]
+fn running_out_of_memory_throws_an_error() {
+    // writes a cell every 5001 cells, so every write needs a page of its own
+    let program = program("compile-x86-64", "pages", "_ #1U #5000K #0j");
+    let dir = temp_dir("compile-x86-64");
+    let binary = build(&dir, &program);
+    let output = run(Command::new("sh").arg("-c").arg("ulimit -v 200000 && exec \"$0\"").arg(&binary), b"");
+    assert_eq!(output.status.code(), Some(16));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("could not allocate memory for the tape"), "{}", String::from_utf8_lossy(&output.stderr));
+}
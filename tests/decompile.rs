@@ -0,0 +1,80 @@
+mod common;
+
+use common::*;
+
+fn decompile(name: &str, source: &str) -> String {
+    let output = guryvsr().arg("decompile").arg(program("decompile", name, source).as_os_str()).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn constant_jumps_become_gotos() {
+    assert_eq!(decompile("goto", "#4j / _ _ #7U i ."), "\
+p += dir                            // 2
+goto L5                             // 0-2
+acc = floor(tape[p - 1] / tape[p])  // 3
+p += 2*dir                          // 3-4
+L5:
+acc = 7                             // 6-7
+tape[p + dir] = acc; acc = empty    // 8
+print_int(tape[p + 2*dir])          // 9
+p += 3*dir                          // 5-9
+stop                                // 10
+");
+}
+
+#[test]
+fn conditionals_become_if_blocks() {
+    // the body runs when `?` doesn't jump, and the `@` only moves the data pointer when the body falls through to it
+    assert_eq!(decompile("if", "#5U ? R C @ #3-u ."), "\
+acc = 5                                // 0-1
+tape[p] = acc; acc = empty             // 2
+p += 2*dir                             // 2-3
+if not empty_or_zero(tape[p - dir]) {  // 3
+    acc = tape[p]; tape[p] = empty     // 4
+    acc = empty                        // 5
+    p += 3*dir                         // 4-6
+}                                      // 6
+acc = -3                               // 7-9
+tape[p + dir] = acc                    // 10
+p += 2*dir                             // 9-10
+stop                                   // 11
+");
+}
+
+#[test]
+fn moves_are_not_merged_across_computed_jumps() {
+    assert_eq!(decompile("computed", "I j _ ."), "\
+acc = read_int()            // 0
+p += dir                    // 1
+goto command take(acc) + 1  // 1
+p += dir                    // 2
+stop                        // 3
+");
+}
+
+#[test]
+fn jumps_landed_on_are_not_gotos() {
+    assert_eq!(decompile("landing", "#5j . #1J ."), "\
+p += dir                        // 2
+goto L6                         // 0-2
+stop                            // 3
+acc = 1                         // 4-5
+L6:
+p += dir                        // 6
+goto command 6 + take(acc) + 1  // 6
+stop                            // 7
+");
+}
+
+#[test]
+fn the_corpus_decompiles() {
+    for path in programs() {
+        let output = guryvsr().arg("decompile").arg(&path).output().unwrap();
+        assert!(output.status.success(), "{path:?}: {}", String::from_utf8_lossy(&output.stderr));
+        // every line but a label's ends with the commands it stands for
+        let pseudocode = String::from_utf8(output.stdout).unwrap();
+        assert!(pseudocode.lines().all(|line| line.contains("  // ") || line.trim_end().ends_with(':')), "{path:?}:\n{pseudocode}");
+    }
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use std::path::Path;
+use std::process::Output;
+use common::*;
+
+fn run(program: &Path, input: &[u8]) -> Output {
+    interpret(program, &["--detect-loops"], input)
+}
+
+fn program(name: &str, source: &str) -> TempProgram {
+    common::program("detect-loops", name, source)
+}
+
+#[test]
+fn repeated_states_are_infinite_loops() {
+    // `j` goes back to the `F`, which flips the direction so that the data pointer comes back every second time round
+    let output = run(&program("flip", "_ F #0j"), b"");
+    assert_eq!(output.status.code(), Some(18));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("InfiniteLoopError:\x1b[0m the program returned to the state it was in at command 4 without reading input, so commands 1 to 4 repeat forever"), "{stderr}");
+}
+
+#[test]
+fn output_does_not_break_loops() {
+    // the loop prints the same cell every time round, which doesn't change the state
+    let output = run(&program("output", "#7U F _ F i F _ _ F #5j"), b"");
+    assert_eq!(output.status.code(), Some(18));
+    assert!(output.stdout.starts_with(b"7"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("commands 6 to 13 repeat forever"));
+}
+
+#[test]
+fn input_starts_over() {
+    // every line read could send the program another way, so it only stops once the input runs out
+    let output = run(&program("input", "_ F I C #0j"), b"1\n2\n3\n");
+    assert_eq!(output.status.code(), Some(15));
+}
+
+#[test]
+fn programs_that_halt_are_unaffected() {
+    for path in programs() {
+        let input = input_for(&path);
+        let expected = interpret(&path, &[], &input);
+        let actual = run(&path, &input);
+        assert_eq!(actual.status.code(), expected.status.code(), "{path:?}");
+        assert_eq!(actual.stdout, expected.stdout, "{path:?}");
+    }
+}
+
+#[test]
+fn detecting_loops_is_rejected_with_the_threaded_engine() {
+    let output = interpret(&program("threaded", "#1 ."), &["--detect-loops", "--engine", "threaded"], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with `--detect-loops`"), "{}", String::from_utf8_lossy(&output.stderr));
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use guryvsr::differential::diff::*;
+use guryvsr::lexer::lex::*;
+
+fn harness(backends: &[Backend]) -> Harness {
+    Harness::new(env!("CARGO_BIN_EXE_guryvsr"), backends)
+}
+
+fn assert_agree(harness: &mut Harness, tokens: &[Token], input: &[u8]) {
+    let mismatches = harness.compare(tokens, input).unwrap();
+    let program: String = tokens.iter().map(token_to_symbol).collect();
+    assert!(mismatches.is_empty(), "{program} with input {:?}:\n{}", String::from_utf8_lossy(input),
+            mismatches.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n"));
+}
+
+#[test]
+fn interpreted_backends_agree_on_the_corpus() {
+    let mut harness = harness(&Backend::INTERPRETED);
+    for program in common::programs() {
+        let tokens = try_tokenize(&std::fs::read_to_string(&program).unwrap()).unwrap();
+        assert_agree(&mut harness, &tokens, &common::input_for(&program));
+    }
+}
+
+#[test]
+fn interpreted_backends_agree_on_random_programs() {
+    let mut harness = harness(&Backend::INTERPRETED);
+    let mut generator = Generator::new(0x5eed);
+    for _ in 0..40 {
+        let tokens = generator.program();
+        let input = generator.input();
+        assert_agree(&mut harness, &tokens, &input);
+    }
+}
+
+#[test]
+fn compiled_backends_agree_on_random_programs() {
+    let mut backends = vec![Backend::Reference];
+    if common::has_tool("rustc") {
+        backends.push(Backend::Rust);
+    }
+    if cfg!(all(target_arch = "x86_64", target_os = "linux")) && common::has_tool("as") && common::has_tool("ld") {
+        backends.push(Backend::X86_64Linux);
+    }
+    if backends.len() == 1 {
+        eprintln!("skipped: neither rustc nor as and ld can be run");
+        return
+    }
+    let mut harness = harness(&backends);
+    let mut generator = Generator::new(7);
+    for _ in 0..4 {
+        let tokens = generator.program();
+        let input = generator.input();
+        assert_agree(&mut harness, &tokens, &input);
+    }
+}
+
+#[test]
+fn generated_programs_are_well_formed() {
+    let mut generator = Generator::new(1);
+    for _ in 0..200 {
+        let tokens = generator.program();
+        assert_eq!(tokens.last(), Some(&Token::Stop));
+        let matches = match_conditionals(&tokens);
+        for (index, token) in tokens.iter().enumerate() {
+            let in_literal = index > 0 && matches!(tokens[index - 1], Token::CreatingNumber | Token::Digit(_));
+            assert!(!token.is_conditional() || matches[index].is_some(), "unmatched conditional at {index}");
+            assert!(*token != Token::DestinationIfTrue || matches[index].is_some(), "unmatched `@` at {index}");
+            assert!(!matches!(token, Token::Digit(_)) || in_literal, "stray digit at {index}");
+            assert!(*token != Token::CreatingNumber || !in_literal, "`#` in a literal at {index}");
+        }
+    }
+}
+
+#[test]
+fn observations_hold_the_final_state() {
+    let mut harness = harness(&[Backend::Reference, Backend::Threaded]);
+    let observation = harness.observe(Backend::Reference, &try_tokenize("#5U #6U F R .").unwrap(), b"").unwrap();
+    assert_eq!(observation.ending, Ending::Stopped);
+    let state = observation.state.unwrap();
+    // `R` moves the cell into the accumulator, emptying it
+    assert_eq!(state.acc, Some(6));
+    assert_eq!(state.tape.into_iter().collect::<Vec<_>>(), [(0, 5)]);
+
+    let observation = harness.observe(Backend::Threaded, &try_tokenize("I .").unwrap(), b"x\n").unwrap();
+    assert_eq!(observation.ending, Ending::Threw(guryvsr::errors::err::Error::InputError));
+    // invalid input is thrown without the details
+    assert!(observation.state.is_none());
+}
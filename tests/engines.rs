@@ -0,0 +1,66 @@
+mod common;
+
+use std::path::Path;
+use common::*;
+
+/// Runs a program with both engines and checks that they print the same things and exit with the same status.
+fn assert_same_behavior(program: &Path, input: &[u8]) {
+    for flags in [&[][..], &["--details"], &["--exit-with-acc"], &["--details", "--exit-with-acc"]] {
+        let expected = interpret(program, flags, input);
+        let actual = interpret(program, &[flags, &["--engine", "threaded"]].concat(), input);
+        assert_eq!(String::from_utf8_lossy(&actual.stdout), String::from_utf8_lossy(&expected.stdout), "stdout of {program:?} with {flags:?}");
+        assert_eq!(String::from_utf8_lossy(&actual.stderr), String::from_utf8_lossy(&expected.stderr), "stderr of {program:?} with {flags:?}");
+        assert_eq!(actual.status.code(), expected.status.code(), "exit status of {program:?} with {flags:?}");
+    }
+}
+
+#[test]
+fn threaded_engine_runs_the_corpus_like_the_reference_engine() {
+    for program in programs() {
+        assert_same_behavior(&program, &input_for(&program));
+    }
+}
+
+#[test]
+fn threaded_engine_throws_the_same_errors() {
+    let programs = [
+        "#7-u F #2 u f/ U i .",
+        "#7-u F #2-u f% U i .",
+        "#7 u F #0 u f/.",
+        "#300.",
+        "#92233720368547758070.",
+        "##",
+        "3.",
+        "#5 J _ _ _ _ _ #1 U i .",
+        "#100 j",
+        "? _ _",
+        "#1U? . @ i .",
+        "#65 u s #-1 u s .",
+        "#1 K i .",
+        "#1 T _ @ .",
+        "I i S s .",
+        "C",
+    ];
+    for (index, source) in programs.iter().enumerate() {
+        assert_same_behavior(&program("engines", &index.to_string(), source), "42\nhé\n".as_bytes());
+    }
+}
+
+#[test]
+fn threaded_engine_runs_literals_and_their_jumps_in_one_go_without_changing_what_they_do() {
+    let programs = [
+        "#6j _ _ #12 U i .",
+        "#4 J _ _ #9 U i .",
+        "#1 #2 .",
+        "#3-J",
+        "#2-j",
+        "#1 U #9 j #1 U i . _ _ i .",
+        "#1 U #3- #12 J _ _ i .",
+        "#9223372036854775807 K #26j _ _ .",
+        "#5 #-1 F j .",
+        "#12",
+    ];
+    for (index, source) in programs.iter().enumerate() {
+        assert_same_behavior(&program("engines-literals", &index.to_string(), source), b"");
+    }
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::*;
+
+/// Runs a program with `--exit-with-acc` on both engines, checking that they agree, and returns its exit status and standard error.
+fn exit_with_acc(name: &str, source: &str) -> (Option<i32>, String) {
+    let program = program("exit-with-acc", name, source);
+    let output = interpret(&program, &["--exit-with-acc"], b"");
+    let threaded = interpret(&program, &["--exit-with-acc", "--engine", "threaded"], b"");
+    assert_eq!(threaded.status.code(), output.status.code(), "{source}");
+    assert_eq!(threaded.stderr, output.stderr, "{source}");
+    (output.status.code(), String::from_utf8_lossy(&output.stderr).into_owned())
+}
+
+#[test]
+fn stop_exits_with_the_accumulator() {
+    assert_eq!(exit_with_acc("forty_two", "#42 ."), (Some(42), "".to_string()));
+    assert_eq!(exit_with_acc("highest", "#255 ."), (Some(255), "".to_string()));
+    // without the flag, `.` always exits with 0
+    assert_eq!(interpret(&program("exit-with-acc", "flagless", "#42 ."), &[], b"").status.code(), Some(0));
+}
+
+#[test]
+fn an_empty_accumulator_exits_with_zero() {
+    assert_eq!(exit_with_acc("empty", "_ ."), (Some(0), "".to_string()));
+    // unloading into a cell empties the accumulator
+    assert_eq!(exit_with_acc("unloaded", "#42 U ."), (Some(0), "".to_string()));
+}
+
+#[test]
+fn statuses_out_of_range_are_overflows() {
+    let (status, stderr) = exit_with_acc("negative", "#1- .");
+    assert_eq!(status, Some(17));
+    assert!(stderr.contains("exit status -1 is not between 0 and 255"), "{stderr}");
+    let (status, stderr) = exit_with_acc("above", "#256 .");
+    assert_eq!(status, Some(17));
+    assert!(stderr.contains("exit status 256 is not between 0 and 255"), "{stderr}");
+}
+
+#[test]
+fn error_statuses_are_told_apart_by_standard_error() {
+    let (status, stderr) = exit_with_acc("fourteen", "#14 .");
+    assert_eq!(status, Some(14));
+    assert!(stderr.is_empty());
+    let (status, stderr) = exit_with_acc("syntax_error", "#14 ##");
+    assert_eq!(status, Some(14));
+    assert!(stderr.contains("SyntaxError"), "{stderr}");
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use guryvsr::lexer::lex::*;
+use common::*;
+
+fn export(name: &str, source: &str) -> String {
+    let output = guryvsr().args(["export", "--html"]).arg(program("export", name, source).as_os_str()).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn commands_are_classed_by_category() {
+    let page = export("classes", "#1U ? j @ F + s _");
+    for (index, class, text) in [
+        (0, "literal", "#"),
+        (1, "literal", "1"),
+        (2, "memory", "U"),
+        (4, "jump", "j"),
+        (6, "pointer-motion", "F"),
+        (7, "arithmetic", "+"),
+        (8, "io", "s"),
+        (9, "control", "_"),
+    ] {
+        let span = format!("<span class=\"cmd {class}\" data-index=\"{index}\" title=\"command {index}\">{text}</span>");
+        assert!(page.contains(&span), "{span} is missing from:\n{page}");
+    }
+    assert!(page.contains("<span class=\"cmd conditional\" data-index=\"3\" data-match=\"5\" data-depth=\"0\" title=\"command 3, matches command 5\">?</span>"));
+    assert!(page.contains("<span class=\"cmd conditional\" data-index=\"5\" data-match=\"3\" data-depth=\"0\" title=\"command 5, matches command 3\">@</span>"));
+}
+
+#[test]
+fn html_in_the_source_and_the_title_is_escaped() {
+    let page = export("a&b", "`<b>\"quoted\" & more</b>` < > &\n");
+    assert!(page.contains("<title>a&amp;b.gur</title>"));
+    assert!(page.contains("<span class=\"comment\">`&lt;b&gt;&quot;quoted&quot; &amp; more&lt;/b&gt;`</span>"), "{page}");
+    assert!(page.contains("title=\"command 0\">&lt;</span> <span"));
+    assert!(page.contains("title=\"command 1\">&gt;</span>"));
+    assert!(page.contains("title=\"command 2\">&amp;</span>\n</pre>"));
+    assert!(!page.contains("<b>"));
+}
+
+#[test]
+fn every_command_has_a_category() {
+    let categories: Vec<Category> = "#5UuRrCc?!TtAa@JjKkFfMm+-*/%=Nn>Gg<Ll&|~^isIS_.".chars()
+        .map(|c| symbol_to_token(c).unwrap().category())
+        .collect();
+    let count = |category| categories.iter().filter(|&&other| other == category).count();
+    assert_eq!(count(Category::Literal), 2);
+    assert_eq!(count(Category::Memory), 6);
+    assert_eq!(count(Category::Conditional), 7);
+    assert_eq!(count(Category::Jump), 2);
+    assert_eq!(count(Category::PointerMotion), 6);
+    assert_eq!(count(Category::Arithmetic), 18);
+    assert_eq!(count(Category::Io), 4);
+    assert_eq!(count(Category::Control), 2);
+}
+
+#[test]
+fn macro_directives_are_not_commands() {
+    let page = export("macros", "{define one #1U}\n{one} .");
+    assert!(page.contains("<span class=\"directive\">{define one #1U}</span>"), "{page}");
+    assert!(page.contains("<span class=\"directive\" title=\"expands into commands 0 to 2\">{one}</span>"), "{page}");
+    assert!(page.contains("<span class=\"cmd control\" data-index=\"3\" title=\"command 3\">.</span>"), "{page}");
+}
+
+#[test]
+fn commands_after_a_macro_call_keep_the_index_they_run_at() {
+    let page = export("expanded", "{define s(n) #$n U}\n{s(12)} i .");
+    assert!(page.contains("<span class=\"directive\" title=\"expands into commands 0 to 3\">{s(12)}</span>"), "{page}");
+    assert!(page.contains("<span class=\"cmd io\" data-index=\"4\" title=\"command 4\">i</span>"), "{page}");
+    assert!(page.contains("<span class=\"cmd control\" data-index=\"5\" title=\"command 5\">.</span>"), "{page}");
+    let page = export("matched", "{define end _ @}\n#1U ? {end} .");
+    assert!(page.contains("data-index=\"3\" data-match=\"5\" data-depth=\"0\" title=\"command 3, matches command 5\">?</span>"), "{page}");
+    assert!(page.contains("title=\"command 6\">.</span>"), "{page}");
+}
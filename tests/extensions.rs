@@ -0,0 +1,122 @@
+use guryvsr::debugger::debug::*;
+use guryvsr::errors::err::Error;
+use guryvsr::interpreter::interp::*;
+
+/// Writes how many times it has run to the current cell.
+struct Counter {
+    symbol: char,
+    halts: bool,
+    runs: isize,
+}
+
+impl CommandExtension for Counter {
+    fn symbol(&self) -> char {
+        self.symbol
+    }
+
+    fn halts_data_pointer(&self) -> bool {
+        self.halts
+    }
+
+    fn execute(&mut self, state: &mut StateView) -> Result<(), (Error, String)> {
+        self.runs += 1;
+        let index = state.data_ptr();
+        state.set_cell(index, Some(self.runs));
+        Ok(())
+    }
+}
+
+/// Moves the accumulator's value, doubled, into the current cell.
+struct Double;
+
+impl CommandExtension for Double {
+    fn symbol(&self) -> char {
+        'd'
+    }
+
+    fn execute(&mut self, state: &mut StateView) -> Result<(), (Error, String)> {
+        let Some(value) = state.acc() else { return Err((Error::AccumulatorError, "nothing to double".to_string())) };
+        let Some(doubled) = value.checked_mul(2) else { return Err((Error::OverflowError, "doubling overflowed".to_string())) };
+        let index = state.data_ptr();
+        state.set_cell(index, Some(doubled));
+        state.set_acc(None);
+        Ok(())
+    }
+}
+
+fn counter(symbol: char, halts: bool) -> Counter {
+    Counter { symbol, halts, runs: 0 }
+}
+
+#[test]
+fn extensions_run_between_built_in_commands() {
+    let interpreter = InterpreterBuilder::new().extension(counter('x', false)).extension(counter('h', true)).build().unwrap();
+    // `x` moves on to cell 1, so `F` flips back to cell 0 to print it
+    let run = interpreter.run("x F i .", "").unwrap();
+    assert_eq!(run.output, "1");
+    assert!(matches!(run.outcome, Some(Outcome::Stopped(0))), "{:?}", run.outcome);
+    // `h` stays on the cell it wrote
+    let run = interpreter.run("h i .", "").unwrap();
+    assert_eq!(run.output, "1");
+    // the extensions are shared by every program the interpreter runs
+    assert_eq!(interpreter.run("x F i .", "").unwrap().output, "2");
+}
+
+#[test]
+fn extensions_see_input_and_throw_errors() {
+    let interpreter = InterpreterBuilder::new().extension(Double).build().unwrap();
+    assert_eq!(interpreter.run("I d F i .", "21\n").unwrap().output, "42");
+
+    let run = interpreter.run("_ _ d .", "").unwrap();
+    let Some(Outcome::Failed(fault)) = run.outcome else { panic!("{:?}", run.outcome) };
+    assert_eq!(fault.error, Error::AccumulatorError);
+    assert_eq!(fault.msg, "nothing to double");
+    assert_eq!(fault.code_ptr_index, 2);
+}
+
+#[test]
+fn stepping_back_undoes_extensions() {
+    let interpreter = InterpreterBuilder::new().extension(Double).build().unwrap();
+    let mut machine = interpreter.load("#5 d .").unwrap();
+    machine.step();
+    machine.step();
+    assert_eq!(machine.state().acc.get_value(), Ok(5));
+    machine.step();
+    assert!(machine.state().acc.is_empty());
+    assert_eq!(machine.state().data_ptr_index, 1);
+    assert!(machine.step_back());
+    assert_eq!(machine.state().acc.get_value(), Ok(5));
+    assert_eq!(machine.state().data_ptr_index, 0);
+}
+
+#[test]
+fn symbols_that_are_taken_are_rejected() {
+    let built_in = InterpreterBuilder::new().extension(counter('+', false)).build();
+    assert_eq!(built_in.err().unwrap(), "`+` is already the symbol of a built-in command");
+    let twice = InterpreterBuilder::new().extension(counter('x', false)).extension(counter('x', true)).build();
+    assert_eq!(twice.err().unwrap(), "`x` is already the symbol of another extension");
+    assert!(InterpreterBuilder::new().extension(counter('`', false)).build().is_err());
+    assert!(InterpreterBuilder::new().extension(counter(' ', false)).build().is_err());
+}
+
+#[test]
+fn unregistered_symbols_are_still_unknown() {
+    let interpreter = InterpreterBuilder::new().extension(counter('x', false)).build().unwrap();
+    assert_eq!(interpreter.tokenize("x _ `y` .").unwrap().extensions, [Some('x'), None, None]);
+    let (error, msg) = interpreter.tokenize("x _ y .").unwrap_err();
+    assert_eq!(error, Error::UnknownSymbolError);
+    assert_eq!(msg, "unrecognized symbol y found at index 2");
+    assert!(InterpreterBuilder::new().build().unwrap().run("x .", "").is_err());
+}
+
+#[test]
+fn runs_can_be_limited_to_a_number_of_steps() {
+    let interpreter = InterpreterBuilder::new().extension(counter('x', true)).build().unwrap();
+    // `#0j` continues at the command after index 0, which is the `#0j` itself, forever
+    let run = interpreter.run_with_limit("x #0j", "", 1000).unwrap();
+    assert!(run.outcome.is_none(), "{:?}", run.outcome);
+    let run = interpreter.run_with_limit("x i .", "", 1000).unwrap();
+    // `x` ran once in the program that ran out of steps
+    assert_eq!(run.output, "2");
+    assert!(matches!(run.outcome, Some(Outcome::Stopped(0))), "{:?}", run.outcome);
+}
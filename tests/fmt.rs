@@ -0,0 +1,52 @@
+mod common;
+
+use guryvsr::lexer::lex::*;
+use common::*;
+
+fn fmt(args: &[&str], path: &std::path::Path) -> std::process::Output {
+    guryvsr().arg("fmt").args(args).arg(path).output().unwrap()
+}
+
+#[test]
+fn formatting_keeps_every_command_and_settles() {
+    let dir = temp_dir("fmt");
+    for program in programs() {
+        let source = std::fs::read_to_string(&program).unwrap();
+        for width in ["80", "12"] {
+            let path = write(&dir, &format!("{}-{width}.gur", program.file_stem().unwrap().to_str().unwrap()), &source);
+            let output = fmt(&["--width", width], &path);
+            assert!(output.status.success(), "{program:?}: {}", String::from_utf8_lossy(&output.stderr));
+            let formatted = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(try_tokenize(&formatted).unwrap(), try_tokenize(&source).unwrap(), "commands of {program:?} at width {width}");
+
+            // formatting a formatted file changes nothing
+            let output = fmt(&["--width", width, "--check"], &path);
+            assert!(output.status.success(), "{program:?} at width {width} isn't formatted after formatting:\n{formatted}");
+            assert!(fmt(&["--width", width], &path).status.success());
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), formatted, "{program:?} at width {width}");
+        }
+    }
+}
+
+#[test]
+fn check_lists_unformatted_files() {
+    let dir = temp_dir("fmt-check");
+    let formatted = write(&dir, "formatted.gur", "#1U?\n    F\n@\n.\n");
+    let unformatted = write(&dir, "unformatted.gur", "#1U ? F @ .");
+    let output = guryvsr().args(["fmt", "--check"]).args([&formatted, &unformatted]).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unformatted.gur\" is not formatted") && !stderr.contains("/formatted.gur"), "{stderr}");
+    assert_eq!(std::fs::read_to_string(&unformatted).unwrap(), "#1U ? F @ .");
+}
+
+#[test]
+fn macro_directives_are_kept_as_written() {
+    let dir = temp_dir("fmt-macros");
+    let path = write(&dir, "macros.gur", "{define store(n) #$n U}\n{define twice(x)\n  {store($x)}{store($x)}}\n{twice(4)} F + U F i .\n");
+    let output = fmt(&[], &path);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let formatted = std::fs::read_to_string(&path).unwrap();
+    assert!(formatted.starts_with("{define store(n) #$n U}\n{define twice(x)\n  {store($x)}{store($x)}}\n{twice(4)}"), "{formatted}");
+    assert!(fmt(&["--check"], &path).status.success(), "{formatted}");
+}
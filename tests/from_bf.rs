@@ -0,0 +1,99 @@
+mod common;
+
+use std::path::PathBuf;
+
+/// A plain Brainfuck interpreter with 8-bit wrapping cells, where the end of input reads as 0.
+fn reference(program: &str, input: &[u8]) -> Vec<u8> {
+    let code: Vec<u8> = program.bytes().collect();
+    let mut tape = vec![0u8; 30000];
+    let mut ptr = 0;
+    let mut input = input.iter();
+    let mut output = vec![];
+    let mut pc = 0;
+    while pc < code.len() {
+        match code[pc] {
+            b'+' => tape[ptr] = tape[ptr].wrapping_add(1),
+            b'-' => tape[ptr] = tape[ptr].wrapping_sub(1),
+            b'>' => ptr += 1,
+            b'<' => ptr -= 1,
+            b'.' => output.push(tape[ptr]),
+            b',' => tape[ptr] = input.next().copied().unwrap_or(0),
+            b'[' if tape[ptr] == 0 => {
+                let mut depth = 0;
+                loop {
+                    match code[pc] {
+                        b'[' => depth += 1,
+                        b']' => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 { break }
+                    pc += 1;
+                }
+            }
+            b']' if tape[ptr] != 0 => {
+                let mut depth = 0;
+                loop {
+                    match code[pc] {
+                        b'[' => depth -= 1,
+                        b']' => depth += 1,
+                        _ => {}
+                    }
+                    if depth == 0 { break }
+                    pc -= 1;
+                }
+            }
+            _ => {}
+        }
+        pc += 1;
+    }
+    output
+}
+
+fn guryvsr(args: &[&str], input: &[u8]) -> Vec<u8> {
+    let output = common::run(common::guryvsr().args(args), input);
+    assert!(output.status.success(), "guryvsr {args:?} failed");
+    output.stdout
+}
+
+/// Translates a program, runs the translation and checks that it prints what the reference interpreter prints.
+fn assert_same_output(name: &str, program: &str, input: &[u8]) {
+    let dir = common::temp_dir("from-bf");
+    let bf: PathBuf = dir.join(format!("{name}.b"));
+    let gur: PathBuf = dir.join(format!("{name}.gur"));
+    std::fs::write(&bf, program).unwrap();
+    guryvsr(&["from-bf", bf.to_str().unwrap(), "-o", gur.to_str().unwrap()], b"");
+    let expected = reference(program, input);
+    assert_eq!(String::from_utf8_lossy(&guryvsr(&[gur.to_str().unwrap()], input)), String::from_utf8_lossy(&expected));
+}
+
+#[test]
+fn hello_world() {
+    assert_same_output("hello", "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.>++.", b"");
+}
+
+#[test]
+fn cells_wrap_around() {
+    // 0 - 2 halves to 127, and 0 - 1 divided by 3 is 85
+    assert_same_output("wrap", "--[>+<--]>.>-[--->+<]>.", b"");
+}
+
+#[test]
+fn empty_cells_read_as_zero() {
+    assert_same_output("empty", ">>>[-]<<<++++++[>>++++++++<<-]>>.<<[.]", b"");
+}
+
+#[test]
+fn digits() {
+    assert_same_output("digits", "++++++++[>++++++<-]>[<+>-]++++++++++[<.+>-]++++++++++.", b"");
+}
+
+#[test]
+fn reads_input_a_line_at_a_time() {
+    assert_same_output("cat", ",[.,]", b"abc\nxyz\n");
+    assert_same_output("reverse", ">,[>,]<[.<]", b"hello\n");
+}
+
+#[test]
+fn comment_loop_at_start() {
+    assert_same_output("comment", "[this loop never runs, so the text is a comment.]+++++++[>+++++++<-]>.", b"");
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use common::*;
+
+/// Long enough for any of the programs to finish, which they each do in a few milliseconds once fixed.
+const HALT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The inputs the fuzz targets in `fuzz/` found crashes or hangs with, saved as programs.
+#[test]
+fn fuzz_regressions_halt_normally_on_every_engine() {
+    let programs = corpus("fuzz_regressions");
+    assert!(!programs.is_empty());
+    for program in programs {
+        let mut outputs = vec![];
+        for flags in [&["--details"][..], &["--details", "--engine", "threaded"], &["--details", "--watch-cell", "0"]] {
+            let Some(output) = run_with_timeout(guryvsr().arg(&program).args(flags), &input_for(&program), HALT_TIMEOUT) else { panic!("{program:?} with {flags:?} didn't halt") };
+            let status = output.status.code();
+            assert!(status.is_some_and(|status| status == 0 || (10..=18).contains(&status)), "{program:?} with {flags:?} exited with {status:?}: {}", String::from_utf8_lossy(&output.stderr));
+            outputs.push((flags, output));
+        }
+        let (_, expected) = &outputs[0];
+        for (flags, actual) in &outputs[1..] {
+            assert_eq!(actual.status.code(), expected.status.code(), "exit status of {program:?} with {flags:?}");
+            assert_eq!(String::from_utf8_lossy(&actual.stdout), String::from_utf8_lossy(&expected.stdout), "stdout of {program:?} with {flags:?}");
+        }
+    }
+}
+
+/// Compiles a program with both compilers into `dir`, returning the executables of the ones whose tools can be run.
+fn compile(dir: &Path, program: &Path) -> Vec<PathBuf> {
+    let name = program.file_stem().unwrap().to_str().unwrap();
+    let mut binaries = vec![];
+    if has_tool("rustc") {
+        let source = dir.join(format!("{name}.rs"));
+        let binary = dir.join(format!("{name}-rust"));
+        assert!(guryvsr().args(["compile", "--target", "rust", "-o"]).args([&source, program]).status().unwrap().success());
+        assert!(Command::new("rustc").args(["-O", "-o"]).args([&binary, &source]).status().unwrap().success(), "rustc rejected {name}");
+        binaries.push(binary);
+    }
+    if cfg!(all(target_arch = "x86_64", target_os = "linux")) && has_tool("as") && has_tool("ld") {
+        let source = dir.join(format!("{name}.s"));
+        let object = dir.join(format!("{name}.o"));
+        let binary = dir.join(format!("{name}-x86_64"));
+        assert!(guryvsr().args(["compile", "--target", "x86_64-linux", "-o"]).args([&source, program]).status().unwrap().success());
+        assert!(Command::new("as").arg("-o").args([&object, &source]).status().unwrap().success(), "as rejected {name}");
+        assert!(Command::new("ld").arg("-o").args([&binary, &object]).status().unwrap().success(), "ld rejected {name}");
+        binaries.push(binary);
+    }
+    binaries
+}
+
+#[test]
+fn fuzz_regressions_halt_the_same_when_compiled() {
+    let dir = temp_dir("fuzz-regressions");
+    for program in corpus("fuzz_regressions") {
+        let Some(expected) = run_with_timeout(guryvsr().arg(&program), &input_for(&program), HALT_TIMEOUT) else { panic!("{program:?} didn't halt") };
+        for binary in compile(&dir, &program) {
+            let Some(actual) = run_with_timeout(&mut Command::new(&binary), &input_for(&program), HALT_TIMEOUT) else { panic!("{binary:?} didn't halt") };
+            assert_eq!(actual.status.code(), expected.status.code(), "exit status of {binary:?}");
+            assert_eq!(String::from_utf8_lossy(&actual.stdout), String::from_utf8_lossy(&expected.stdout), "stdout of {binary:?}");
+            assert_eq!(String::from_utf8_lossy(&actual.stderr), String::from_utf8_lossy(&expected.stderr), "stderr of {binary:?}");
+        }
+    }
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::*;
+
+fn graph(name: &str, source: &str) -> String {
+    let output = guryvsr().args(["graph", "--format", "dot"]).arg(program("graph", name, source).as_os_str()).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn conditionals_have_taken_and_untaken_edges() {
+    let dot = graph("loop", "#1-FUF #1k #3U #1k i #1k + F U F ! . @ #11j");
+    assert!(dot.starts_with("digraph program {"));
+    assert!(dot.contains("b0 [label=\"0-11\\l#1-FUF#1k#3U\\l\"];"));
+    assert!(dot.contains("b2 [label=\"24\\l.\\l\", peripheries=2];"));
+    assert!(dot.contains("start -> b0;"));
+    assert!(dot.contains("b0 -> b1;"));
+    // the `!` jumps past its `@` to the `#11j`, and the `#11j` jumps back to the command after index 11
+    assert!(dot.contains("b1 -> b4 [label=\"! taken\""));
+    assert!(dot.contains("b1 -> b2 [label=\"! not taken\""));
+    assert!(dot.contains("b4 -> b1 [label=\"j 11\""));
+    assert!(!dot.contains("computed"));
+}
+
+#[test]
+fn jumps_without_a_literal_are_computed() {
+    let dot = graph("jump_table", &std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/programs/jump_table.gur")).unwrap());
+    assert!(dot.contains("b0 -> computed [label=\"j computed\""));
+    assert!(dot.contains("computed [shape=diamond"));
+}
+
+#[test]
+fn relative_jumps_use_negated_literals() {
+    // `J` at index 6 jumps back by 6 to index 0, so the `+` at index 1 runs next
+    let dot = graph("relative", "#1+ #6-J");
+    assert!(dot.contains("b0 [label=\"0\\l#\\l\"];"));
+    assert!(dot.contains("b1 -> b1 [label=\"J -6\""));
+}
+
+#[test]
+fn certain_errors_have_their_own_nodes() {
+    let dot = graph("errors", "? #99j");
+    assert!(dot.contains("b0 -> SyntaxError [label=\"? taken\""));
+    assert!(dot.contains("b1 -> OverflowError [label=\"j 99\""));
+    assert!(dot.contains("SyntaxError [shape=octagon"));
+
+    let dot = graph("end", "#1U");
+    assert!(dot.contains("b0 -> OutOfBoundsError;"));
+}
+
+#[test]
+fn jumps_landed_on_are_computed() {
+    // `#5j` continues at the `J` at index 6, which then runs without its literal
+    let dot = graph("landing", "#5j . #1J .");
+    assert!(dot.contains("b0 -> b3 [label=\"j 5\""));
+    assert!(dot.contains("b3 [label=\"6\\lJ\\l\"];"));
+    assert!(dot.contains("b3 -> computed [label=\"J computed\""));
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use std::process::Output;
+use common::*;
+
+fn write(name: &str, source: &str) -> TempProgram {
+    program("labels", name, source)
+}
+
+fn guryvsr(args: &[&str], name: &str, source: &str) -> Output {
+    let path = write(name, source);
+    common::guryvsr().args(args).arg(path.file_name().unwrap()).current_dir(path.parent().unwrap()).output().unwrap()
+}
+
+fn expand(name: &str, source: &str) -> String {
+    let output = guryvsr(&["expand"], name, source);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn jumps_continue_at_their_label() {
+    // the `/`s would throw an `OpError` if they ran
+    let output = guryvsr(&[], "absolute", "{to skip} j / {label skip} .");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let output = guryvsr(&[], "relative", "{by skip} J / / {label skip} .");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(expand("relative", "{by skip} J / / {label skip} ."), "#2 J / /  .");
+    assert_eq!(expand("backwards", "_ {label top} _ _ {by top} J ."), "_  _ _ #6- J .");
+}
+
+#[test]
+fn literals_are_resolved_until_their_lengths_settle() {
+    // `#0` makes the label's index 13 and `#12` makes it 14
+    assert_eq!(expand("settle", "{to end} j __________ {label end} ."), "#13 j __________  .");
+    // a literal that would get shorter is padded instead, so that resolving always ends
+    let source = "{by end} J {label end} .";
+    assert_eq!(expand("short", source), "#0 J  .");
+}
+
+#[test]
+fn bad_references_are_syntax_errors() {
+    let cases = [
+        ("missing", "{to nowhere} j .", "missing.gur:1:1: there is no label `nowhere`"),
+        ("first", "{label start} _ {to start} j", "first.gur:1:17: the label `start` is before the first command"),
+        ("twice", "{label a} _ {label a} .", "twice.gur:1:13: the label `a` is already at twice.gur:1:1"),
+    ];
+    for (name, source, message) in cases {
+        let output = guryvsr(&[], name, source);
+        assert_eq!(output.status.code(), Some(14), "{name}");
+        assert!(String::from_utf8_lossy(&output.stderr).contains(message), "{name}: {}", String::from_utf8_lossy(&output.stderr));
+    }
+}
+
+#[test]
+fn check_flags_raw_jumps_that_miss_labels() {
+    let output = guryvsr(&["check"], "raw", "_ #3j #9U {label end} .\n#6j .");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.lines().count(), 1, "{stderr}");
+    // the first `j` continues at index 4, and the second at index 7, which is the label's
+    assert!(stderr.contains("raw.gur:1:5: `j` at index 3 continues at index 4, which is not right after a label"), "{stderr}");
+
+    let output = guryvsr(&["check"], "referenced", "_ {to end} j #9U {label end} .");
+    assert!(output.stderr.is_empty(), "{}", String::from_utf8_lossy(&output.stderr));
+    // without labels, every jump is counted by hand
+    let output = guryvsr(&["check"], "unlabelled", "_ #3j #9U .");
+    assert!(output.stderr.is_empty(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = guryvsr(&["check"], "unresolved", "{to nowhere} j .");
+    assert_eq!(output.status.code(), Some(1));
+}
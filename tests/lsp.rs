@@ -0,0 +1,138 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use serde_json::{json, Value};
+
+/// Drives `guryvsr lsp` the way an editor would.
+struct Client {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: u64,
+}
+
+impl Client {
+    fn start() -> Client {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_guryvsr"))
+            .arg("lsp")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Client { child, stdin, stdout, next_id: 1 }
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            self.stdout.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break
+            }
+            if let Some(value) = line.strip_prefix("Content-Length:") {
+                length = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        self.stdout.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        let response = self.receive();
+        assert_eq!(response["id"], id);
+        response["result"].clone()
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+}
+
+const URI: &str = "file:///test.gur";
+const PROGRAM: &str = "`setup`\n#1U\n?\n    #2U\n@\n`output`\nis 5 x .\n";
+
+fn open(client: &mut Client, text: &str) -> Value {
+    client.request("initialize", json!({ "capabilities": {} }));
+    client.notify("initialized", json!({}));
+    client.notify("textDocument/didOpen", json!({
+        "textDocument": { "uri": URI, "languageId": "gur", "version": 1, "text": text }
+    }));
+    let diagnostics = client.receive();
+    assert_eq!(diagnostics["method"], "textDocument/publishDiagnostics");
+    diagnostics["params"]["diagnostics"].clone()
+}
+
+fn at(line: u64, character: u64) -> Value {
+    json!({ "textDocument": { "uri": URI }, "position": { "line": line, "character": character } })
+}
+
+#[test]
+fn publishes_diagnostics() {
+    let mut client = Client::start();
+    let diagnostics = open(&mut client, PROGRAM);
+    let codes: Vec<&str> = diagnostics.as_array().unwrap().iter().map(|d| d["code"].as_str().unwrap()).collect();
+    assert_eq!(codes, ["SyntaxError", "UnknownSymbolError"]);
+    assert_eq!(diagnostics[0]["range"]["start"], json!({ "line": 6, "character": 3 }));
+    assert_eq!(diagnostics[1]["range"]["start"], json!({ "line": 6, "character": 5 }));
+
+    client.notify("textDocument/didChange", json!({
+        "textDocument": { "uri": URI, "version": 2 },
+        "contentChanges": [{ "text": "?\n" }]
+    }));
+    let diagnostics = client.receive()["params"]["diagnostics"].clone();
+    assert_eq!(diagnostics[0]["code"], "SyntaxError");
+    assert!(diagnostics[0]["message"].as_str().unwrap().contains("corresponding `@`"));
+}
+
+#[test]
+fn describes_commands_on_hover() {
+    let mut client = Client::start();
+    open(&mut client, PROGRAM);
+    let hover = client.request("textDocument/hover", at(1, 2));
+    let text = hover["contents"]["value"].as_str().unwrap();
+    assert!(text.contains("**`U`** unload (command 2)"));
+    assert!(text.contains("Sets c to a and clears A."));
+    assert_eq!(client.request("textDocument/hover", at(0, 2)), Value::Null);
+}
+
+#[test]
+fn goes_to_matching_destination() {
+    let mut client = Client::start();
+    open(&mut client, PROGRAM);
+    let location = client.request("textDocument/definition", at(2, 0));
+    assert_eq!(location["range"]["start"], json!({ "line": 4, "character": 0 }));
+    let location = client.request("textDocument/definition", at(4, 0));
+    assert_eq!(location["range"]["start"], json!({ "line": 2, "character": 0 }));
+}
+
+#[test]
+fn lists_comment_labelled_sections() {
+    let mut client = Client::start();
+    open(&mut client, PROGRAM);
+    let symbols = client.request("textDocument/documentSymbol", json!({ "textDocument": { "uri": URI } }));
+    let names: Vec<&str> = symbols.as_array().unwrap().iter().map(|s| s["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["setup", "output"]);
+    assert_eq!(symbols[0]["detail"], "commands 0 to 7");
+}
+
+#[test]
+fn exits_after_shutdown() {
+    let mut client = Client::start();
+    client.request("initialize", json!({ "capabilities": {} }));
+    assert_eq!(client.request("shutdown", Value::Null), Value::Null);
+    client.notify("exit", Value::Null);
+    assert!(client.child.wait().unwrap().success());
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use std::path::Path;
+use std::process::Output;
+use common::write;
+
+fn dir(name: &str) -> common::TempDir {
+    common::temp_dir(&format!("macros-{name}"))
+}
+
+fn guryvsr(args: &[&str], path: &Path) -> Output {
+    common::guryvsr().args(args).arg(path.file_name().unwrap()).current_dir(path.parent().unwrap()).output().unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn macros_expand_with_their_arguments() {
+    let dir = dir("expand");
+    let path = write(&dir, "prog.gur", "{define store(n) #$n U}\n{define twice(x) {store($x)}{store($x)}}\n{twice(4)} F + U F i .\n");
+    let output = guryvsr(&["expand"], &path);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), "#4 U#4 U F + U F i .");
+
+    let output = guryvsr(&[], &path);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(output.stdout, b"8");
+}
+
+#[test]
+fn includes_are_relative_to_the_including_file() {
+    let dir = dir("include");
+    std::fs::create_dir_all(dir.join("lib")).unwrap();
+    write(&dir, "lib/store.gur", "{define store(n) #$n U}\n{include \"one.gur\"}");
+    write(&dir, "lib/one.gur", "{define one #1U}");
+    let path = write(&dir, "prog.gur", "{include \"lib/store.gur\"}\n{store(2)} {one} F * i .\n");
+    let output = guryvsr(&[], &path);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(output.stdout, b"2");
+}
+
+#[test]
+fn include_cycles_are_syntax_errors() {
+    let dir = dir("cycle");
+    write(&dir, "a.gur", "{include \"b.gur\"}");
+    write(&dir, "b.gur", "{include \"a.gur\"}");
+    let output = guryvsr(&[], &dir.join("a.gur"));
+    assert_eq!(output.status.code(), Some(14));
+    assert!(stderr(&output).contains("makes a cycle"), "{}", stderr(&output));
+
+    let output = guryvsr(&[], &write(&dir, "missing.gur", "{include \"nowhere.gur\"}"));
+    assert_eq!(output.status.code(), Some(10));
+}
+
+#[test]
+fn bad_calls_are_syntax_errors() {
+    let dir = dir("calls");
+    let cases = [
+        ("undefined.gur", "{store(1)}", "undefined.gur:1:1: `store` is not a defined macro"),
+        ("arity.gur", "{define store(n) #$n U}\n {store(1, 2)}", "arity.gur:2:2: `store` takes 1 arguments but was given 2"),
+        ("recursive.gur", "{define a {b}}{define b {a}}{a}", "`a` expands into itself: `a` calls `b` calls `a`"),
+        ("self.gur", "{define f(x) {f($x)}}{f(1)}", "`f` expands into itself: `f` calls `f`"),
+        ("redefined.gur", "{define a _}{define a .}", "`a` is already defined at"),
+        ("unmatched.gur", "{define a _", "`{` does not have a corresponding `}`"),
+    ];
+    for (name, source, message) in cases {
+        let output = guryvsr(&[], &write(&dir, name, source));
+        assert_eq!(output.status.code(), Some(14), "{name}");
+        assert!(stderr(&output).contains(message), "{name}: {}", stderr(&output));
+    }
+}
+
+#[test]
+fn errors_note_where_the_command_was_written() {
+    let dir = dir("note");
+    write(&dir, "lib.gur", "{define divide /}");
+    let path = write(&dir, "prog.gur", "{include \"lib.gur\"}\n{define go #1U {divide}}\n{go} .\n");
+    let output = guryvsr(&[], &path);
+    assert_eq!(output.status.code(), Some(12));
+    assert!(stderr(&output).contains("command 3 is at lib.gur:1:16, in `divide` called at prog.gur:2:16, in `go` called at prog.gur:3:1"),
+            "{}", stderr(&output));
+
+    let output = guryvsr(&[], &write(&dir, "plain.gur", "#1U / ."));
+    assert_eq!(output.status.code(), Some(12));
+    assert!(!stderr(&output).contains("note:"));
+}
+
+#[test]
+fn calls_list_the_commands_they_expand_into() {
+    let dir = dir("list");
+    let path = write(&dir, "prog.gur", "{define store(n) #$n U}\n{define nothing `empty`}\n#1U {store(23)}{nothing} .\n");
+    let output = guryvsr(&["expand", "--calls"], &path);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let listing = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(listing, "prog.gur:3:5: `store` expands into commands 3 to 6\nprog.gur:3:16: `nothing` expands into no commands\n");
+}
+
+#[test]
+fn calls_can_be_passed_to_the_macro_they_are_arguments_of() {
+    let dir = dir("nested");
+    let path = write(&dir, "prog.gur", "{define twice(x) $x $x}\n{twice({twice(#1U)})} .\n");
+    let output = guryvsr(&["expand"], &path);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), "#1U #1U #1U #1U .");
+}
+
+#[test]
+fn unrecognized_symbols_are_reported_where_they_are_written() {
+    let dir = dir("unknown");
+    let path = write(&dir, "prog.gur", "{define f(x) $x q}\n#1U {f(#1)} .\n");
+    for args in [&[][..], &["compile", "--target", "rust"], &["minify"]] {
+        let output = guryvsr(args, &path);
+        assert_eq!(output.status.code(), Some(11), "{args:?}");
+        assert!(stderr(&output).contains("prog.gur:1:17: unrecognized symbol q found at index 5, in `f` called at prog.gur:2:5"), "{args:?}: {}", stderr(&output));
+    }
+}
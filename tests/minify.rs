@@ -0,0 +1,55 @@
+mod common;
+
+use std::process::Output;
+use common::*;
+
+fn minify(args: &[&str], source: &str) -> Output {
+    guryvsr().arg("minify").args(args).arg(unnamed_program("minify", source).as_os_str()).output().unwrap()
+}
+
+fn minified(args: &[&str], source: &str) -> String {
+    let output = minify(args, source);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap().trim_end().to_string()
+}
+
+#[test]
+fn minified_programs_behave_like_the_originals() {
+    let dir = temp_dir("minify-corpus");
+    for program in programs() {
+        let input = input_for(&program);
+        let expected = interpret(&program, &[], &input);
+        let name = program.file_stem().unwrap().to_str().unwrap();
+        for flags in [&[][..], &["--normalize-aliases"], &["--strip-noops"], &["--normalize-aliases", "--strip-noops"]] {
+            let output = guryvsr().arg("minify").args(flags).arg(&program).output().unwrap();
+            assert!(output.status.success(), "{name} with {flags:?}: {}", String::from_utf8_lossy(&output.stderr));
+            let minified = write(&dir, &format!("{name}.gur"), &String::from_utf8(output.stdout).unwrap());
+            let actual = interpret(&minified, &[], &input);
+            assert_eq!(String::from_utf8_lossy(&actual.stdout), String::from_utf8_lossy(&expected.stdout), "stdout of {name} with {flags:?}");
+            assert_eq!(actual.status.code(), expected.status.code(), "exit status of {name} with {flags:?}");
+        }
+    }
+}
+
+#[test]
+fn noops_before_a_moved_data_pointer_are_stripped() {
+    assert_eq!(minified(&["--strip-noops"], "_ `gone` #1k _ @ #2k i ."), "#1k#2ki.");
+    // `i` reads the cell the `_` moved the data pointer to
+    assert_eq!(minified(&["--strip-noops"], "_ i ."), "_i.");
+    assert_eq!(minified(&["--normalize-aliases"], "f n g l _"), "FNGL_");
+}
+
+#[test]
+fn noops_around_jump_targets_are_kept() {
+    // the `j` continues after index 6, so nothing at or before it can move
+    assert_eq!(minified(&["--strip-noops"], "#6j _ #1k ."), "#6j_#1k.");
+    // the `J` continues after index 2 + 4, so only the `_` past that can go
+    assert_eq!(minified(&["--strip-noops"], "#4J _ #1k _ #1k ."), "#4J_#1k#1k.");
+}
+
+#[test]
+fn computed_jumps_keep_every_noop() {
+    let output = minify(&["--strip-noops"], "I j _ #1k .");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim_end(), "Ij_#1k.");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("`j` at index 1 jumps to a computed command index"));
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use guryvsr::lexer::lex::*;
+use guryvsr::parser::ast::*;
+
+#[test]
+fn conditionals_own_their_bodies() {
+    let program = parse("#12U ? F a _ @ @ .").unwrap();
+    assert_eq!(program.len(), 11);
+    let kinds: Vec<&NodeKind> = program.nodes.iter().map(|node| &node.kind).collect();
+    assert_eq!(kinds[0], &NodeKind::Literal(Some(12)));
+    assert_eq!(kinds[1], &NodeKind::Command(Token::Unload));
+    assert_eq!(kinds[3], &NodeKind::Command(Token::Stop));
+
+    let conditional = &program.nodes[2];
+    assert_eq!(conditional.commands, 4..10);
+    assert_eq!(conditional.span, 5..16);
+    let NodeKind::Conditional { test, body, end } = &conditional.kind else { panic!("not a conditional") };
+    assert_eq!(*test, Token::ZeroOrEmpty);
+    assert_eq!(*end, 15..16);
+    assert_eq!(body.len(), 2);
+    assert_eq!(body[0].kind, NodeKind::Command(Token::FlipD(true)));
+    let NodeKind::Conditional { test, body, .. } = &body[1].kind else { panic!("not a conditional") };
+    assert_eq!(*test, Token::AccNotZeroOrEmpty);
+    assert_eq!(body[0].index(), 7);
+}
+
+#[test]
+fn unmatched_and_misplaced_commands_stay_single() {
+    let program = parse("# 5 @ ? #99999999999999999999 .").unwrap();
+    let kinds: Vec<&NodeKind> = program.nodes.iter().map(|node| &node.kind).collect();
+    assert_eq!(kinds, [
+        // whitespace doesn't end a literal
+        &NodeKind::Literal(Some(5)),
+        &NodeKind::Command(Token::DestinationIfTrue),
+        &NodeKind::Command(Token::ZeroOrEmpty),
+        &NodeKind::Literal(None),
+        &NodeKind::Command(Token::Stop),
+    ]);
+    assert_eq!(parse("5 #").unwrap().nodes.iter().map(|node| node.kind.clone()).collect::<Vec<_>>(),
+               [NodeKind::Command(Token::Digit(5)), NodeKind::Command(Token::CreatingNumber)]);
+    assert_eq!(parse("_ x"), Err(('x', 1)));
+}
+
+#[test]
+fn tokens_parse_like_their_minified_source() {
+    let src = "#3U ! `comment` F @ .";
+    let tokens = try_tokenize(src).unwrap();
+    let minified: String = tokens.iter().map(token_to_symbol).collect();
+    assert_eq!(parse_tokens(&tokens), parse(&minified).unwrap());
+}
+
+#[derive(Default)]
+struct Outline {
+    depth: usize,
+    lines: Vec<String>,
+}
+
+impl Visitor for Outline {
+    fn visit_command(&mut self, node: &Node, token: Token) {
+        self.lines.push(format!("{}{} {}", "  ".repeat(self.depth), node.index(), token_to_symbol(&token)));
+    }
+
+    fn visit_literal(&mut self, node: &Node, value: Option<isize>) {
+        self.lines.push(format!("{}{} literal {value:?}", "  ".repeat(self.depth), node.index()));
+    }
+
+    fn enter_conditional(&mut self, node: &Node, test: Token) {
+        self.lines.push(format!("{}{} if {}", "  ".repeat(self.depth), node.index(), token_to_symbol(&test)));
+        self.depth += 1;
+    }
+
+    fn leave_conditional(&mut self, node: &Node, _test: Token) {
+        self.depth -= 1;
+        self.lines.push(format!("{}{} end", "  ".repeat(self.depth), node.commands.end - 1));
+    }
+}
+
+#[test]
+fn visitors_walk_in_source_order() {
+    let mut outline = Outline::default();
+    parse("#7U ? T #1 @ @ .").unwrap().walk(&mut outline);
+    assert_eq!(outline.lines, [
+        "0 literal Some(7)",
+        "2 U",
+        "3 if ?",
+        "  4 if T",
+        "    5 literal Some(1)",
+        "  7 end",
+        "8 end",
+        "9 .",
+    ]);
+}
+
+#[test]
+fn the_corpus_parses_back_into_its_commands() {
+    for path in common::programs() {
+        let src = std::fs::read_to_string(&path).unwrap();
+        let program = parse(&src).unwrap();
+        let tokens = try_tokenize(&src).unwrap();
+        assert_eq!(program.len(), tokens.len(), "{path:?}");
+        // the span of every top-level node starts at its first command
+        let starts: Vec<usize> = scan(&src).unwrap().into_iter()
+            .filter(|lexeme| matches!(lexeme.kind, LexemeKind::Command(_)))
+            .map(|lexeme| lexeme.span.start)
+            .collect();
+        for node in &program.nodes {
+            assert_eq!(node.span.start, starts[node.index()], "{path:?}");
+        }
+    }
+}
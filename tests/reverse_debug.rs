@@ -0,0 +1,42 @@
+mod common;
+
+use std::process::Output;
+use common::*;
+
+fn debug(source: &str, script: &str) -> Output {
+    interpret(&unnamed_program("reverse-debug", source), &["--reverse-debug"], script.as_bytes())
+}
+
+#[test]
+fn stepping_back_from_an_error_restores_the_state_before_it() {
+    let output = debug("#9223372036854775807U#1UF_F+.", "continue\nback\nprint\nreverse-continue\nprint\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("command `+` caused overflow"), "{stderr}");
+    assert!(stderr.contains("[step 27] command 27: `+`"), "{stderr}");
+    assert!(stderr.contains("already at the start of the program"), "{stderr}");
+    let details: Vec<&str> = stderr.split("[Details]").skip(1).collect();
+    // the error, `print` after stepping back, and `print` at the start
+    assert_eq!(details.len(), 3, "{stderr}");
+    assert!(details[1].contains("left cell value: 9223372036854775807"), "{stderr}");
+    assert!(details[2].contains("current cell value: <none>"), "{stderr}");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn breakpoints_stop_in_both_directions() {
+    // prints 3, 2 and 1, with the counter in cell 1 and -1 in cell 0
+    let output = debug("#1-FUF #1k #3U #1k i #1k + F U F ! . @ #11j", "break 15\ncontinue\ncontinue\nreverse-continue\ncell 1\nquit\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stops: Vec<&str> = stderr.lines().filter_map(|line| line.rsplit("(gur) ").next()).filter(|line| line.starts_with("[step")).collect();
+    assert_eq!(stops, ["[step 0] command 0: `#`", "[step 0] command 0: `#`", "[step 15] command 15: `i`", "[step 31] command 15: `i`", "[step 15] command 15: `i`"], "{stderr}");
+    assert!(stderr.contains("cell 1: 3"), "{stderr}");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "3");
+}
+
+#[test]
+fn output_and_input_are_not_repeated_when_steps_are_run_again() {
+    let output = debug("S i I F i .", "step 2\nhi\nback 2\nstep 6\n7\nquit\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "105105", "{stderr}");
+    assert!(stderr.contains("the program stopped with status 0"), "{stderr}");
+}
@@ -0,0 +1,12 @@
+use std::process::Command;
+
+#[test]
+fn visualize_needs_a_terminal() {
+    let output = Command::new(env!("CARGO_BIN_EXE_guryvsr"))
+        .args(["visualize", concat!(env!("CARGO_MANIFEST_DIR"), "/tests/programs/hello.gur")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("standard output is not a terminal"));
+    assert!(output.stdout.is_empty());
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::*;
+
+fn program(source: &str) -> TempProgram {
+    unnamed_program("watch", source)
+}
+
+#[test]
+fn watching_does_not_change_what_programs_do() {
+    for program in programs() {
+        let input = input_for(&program);
+        for flags in [&[][..], &["--details"], &["--exit-with-acc"]] {
+            let expected = interpret(&program, flags, &input);
+            let actual = interpret(&program, &[flags, &["--watch-cell=-5..5", "--watch-acc"]].concat(), &input);
+            let stderr = String::from_utf8_lossy(&actual.stderr);
+            let unwatched: Vec<&str> = stderr.lines().filter(|line| !line.contains("watch:")).collect();
+            assert_eq!(String::from_utf8_lossy(&actual.stdout), String::from_utf8_lossy(&expected.stdout), "stdout of {program:?} with {flags:?}");
+            assert_eq!(unwatched, String::from_utf8_lossy(&expected.stderr).lines().collect::<Vec<_>>(), "stderr of {program:?} with {flags:?}");
+            assert_eq!(actual.status.code(), expected.status.code(), "exit status of {program:?} with {flags:?}");
+        }
+    }
+}
+
+#[test]
+fn watched_cells_and_accumulator_are_reported() {
+    let output = interpret(&program("#5U #2u F R F c ."), &["--watch-cell", "1", "--watch-acc"], b"");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let hits: Vec<&str> = stderr.lines().map(|line| line.rsplit("watch:\x1b[0m ").next().unwrap()).collect();
+    assert_eq!(hits, [
+        "command 1 `5` filled the accumulator: <none> -> 5",
+        "command 2 `U` emptied the accumulator: 5 -> <none>",
+        "command 4 `2` filled the accumulator: <none> -> 2",
+        "command 5 `u` wrote cell 1: <none> -> 2",
+        "command 7 `R` read cell 1: 2",
+        "command 7 `R` cleared cell 1: 2 -> <none>",
+        "command 9 `c` cleared cell 1: <none> -> <none>",
+    ]);
+}
+
+#[test]
+fn debugger_pauses_at_watchpoints_in_both_directions() {
+    let script = b"continue\ncontinue\nreverse-continue\nunwatch 1\ncontinue\n";
+    let output = interpret(&program("#5U #2u F R F c ."), &["--reverse-debug", "--watch-cell", "1"], script);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stops: Vec<&str> = stderr.lines().filter_map(|line| line.rsplit("(gur) ").next()).filter(|line| line.starts_with("[step")).collect();
+    assert_eq!(stops, [
+        "[step 0] command 0: `#`",
+        "[step 6] command 6: `F`",
+        "[step 8] command 8: `F`",
+        "[step 7] command 7: `R`",
+        "[step 11] the program has ended; step back to keep debugging",
+    ], "{stderr}");
+    assert!(stderr.contains("command 7 `R` cleared cell 1: 2 -> <none>"), "{stderr}");
+}
+
+#[test]
+fn watching_is_rejected_with_the_threaded_engine() {
+    for flags in [&["--watch-acc"][..], &["--watch-cell", "0"], &["--reverse-debug"]] {
+        let output = interpret(&program("#1 ."), &[flags, &["--engine", "threaded"]].concat(), b"");
+        assert_eq!(output.status.code(), Some(2), "{flags:?}");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"), "{flags:?}");
+    }
+    assert!(interpret(&program("#1 ."), &["--watch-acc", "--engine", "reference"], b"").status.success());
+}